@@ -0,0 +1,399 @@
+use anyhow::{bail, Result};
+use console::style;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::api::rest::{Auth, Rest};
+use crate::api::{self, InboxItem, MergeItem, MergeOption, Provider};
+use crate::config::Remote;
+use crate::db::{ActivityEvent, Epoch, RepoMeta};
+use crate::{errors, util};
+
+pub struct Bitbucket {
+    rest: Rest,
+}
+
+/// The paginated response of Bitbucket, the `next` is absent on the last
+/// page.
+#[derive(Deserialize, Debug)]
+struct BitbucketPage<T> {
+    values: Vec<T>,
+    next: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BitbucketRepo {
+    full_name: String,
+
+    mainbranch: Option<BitbucketBranch>,
+    parent: Option<Box<BitbucketRepo>>,
+
+    #[serde(default)]
+    description: String,
+
+    /// The size in bytes.
+    #[serde(default)]
+    size: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct BitbucketBranch {
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct BitbucketUser {
+    account_id: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct BitbucketCommit {
+    hash: String,
+    #[serde(default)]
+    message: String,
+    date: String,
+    author: BitbucketAuthor,
+}
+
+#[derive(Deserialize, Debug)]
+struct BitbucketAuthor {
+    /// The raw author in format "name <email>".
+    #[serde(default)]
+    raw: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct BitbucketPull {
+    id: u64,
+    #[serde(default)]
+    title: String,
+    created_on: String,
+    author: Option<BitbucketAccount>,
+
+    source: BitbucketEndpoint,
+    destination: BitbucketEndpoint,
+
+    links: BitbucketLinks,
+}
+
+#[derive(Deserialize, Debug)]
+struct BitbucketAccount {
+    #[serde(default)]
+    display_name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct BitbucketEndpoint {
+    branch: BitbucketBranch,
+    repository: Option<BitbucketRepoRef>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BitbucketRepoRef {
+    full_name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct BitbucketLinks {
+    html: BitbucketLink,
+}
+
+#[derive(Deserialize, Debug)]
+struct BitbucketLink {
+    href: String,
+}
+
+impl Bitbucket {
+    const API_URL: &str = "https://api.bitbucket.org/2.0";
+    const WEB_URL: &str = "https://bitbucket.org";
+    const PAGE_LEN: usize = 100;
+
+    pub fn new<U, T>(url: U, token: T, retry: u32) -> Result<Box<dyn Provider>>
+    where
+        U: AsRef<str>,
+        T: AsRef<str>,
+    {
+        let url = match url.as_ref() {
+            "" => Self::API_URL,
+            url => url,
+        };
+        // The token can be an app password in format
+        // "<username>:<app-password>", or an access token.
+        let auth = match token.as_ref() {
+            "" => Auth::None,
+            token => match token.split_once(':') {
+                Some((user, password)) => Auth::Basic(user.to_string(), password.to_string()),
+                None => Auth::Header(format!("Bearer {}", token)),
+            },
+        };
+        let rest = Rest::new("bitbucket", url, auth)?.with_retry(retry);
+        Ok(Box::new(Bitbucket { rest }))
+    }
+
+    fn repo_path(name: &str) -> Result<String> {
+        let (workspace, slug) = util::split_name(name);
+        if workspace.is_empty() || slug.is_empty() {
+            bail!("invalid bitbucket repository name {}", style(name).yellow())
+        }
+        Ok(format!("repositories/{}/{}", workspace, slug))
+    }
+
+    fn get_repo(&self, name: &str) -> Result<BitbucketRepo> {
+        self.rest.get(&Self::repo_path(name)?, &[])
+    }
+
+    /// Get the values of all pages.
+    fn list_all<T>(&self, path: &str, query: &[(&str, &str)]) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let mut values = vec![];
+        let page_len = Self::PAGE_LEN.to_string();
+        let mut page: usize = 1;
+        loop {
+            let page_str = page.to_string();
+            let mut page_query = vec![("pagelen", page_len.as_str()), ("page", page_str.as_str())];
+            page_query.extend_from_slice(query);
+            let resp: BitbucketPage<T> = self.rest.get(path, &page_query)?;
+            values.extend(resp.values);
+            if resp.next.is_none() {
+                return Ok(values);
+            }
+            page += 1;
+        }
+    }
+
+    fn merge_item(repo: String, pull: BitbucketPull) -> Result<MergeItem> {
+        Ok(MergeItem {
+            repo,
+            number: pull.id,
+            title: pull.title,
+            author: pull
+                .author
+                .map(|author| author.display_name)
+                .unwrap_or_default(),
+            source: pull.source.branch.name,
+            url: pull.links.html.href,
+            created: api::parse_time(&pull.created_on)?,
+            // The build status requires a request for each pull request.
+            ci: String::new(),
+        })
+    }
+
+    /// Find the open pull request from source branch to target branch. In
+    /// upstream mode, the pull request is in the upstream repo, and its
+    /// source is the forked repo.
+    fn find_pull(&self, opts: &MergeOption) -> Result<Option<BitbucketPull>> {
+        let (target_repo, source_repo) = match &opts.upstream {
+            Some(upstream) => (upstream.as_str(), opts.repo.as_str()),
+            None => (opts.repo.as_str(), opts.repo.as_str()),
+        };
+        let query = format!(
+            "state=\"OPEN\" AND source.branch.name=\"{}\" AND destination.branch.name=\"{}\"",
+            opts.source, opts.target
+        );
+        let path = format!("{}/pullrequests", Self::repo_path(target_repo)?);
+        let pulls: BitbucketPage<BitbucketPull> = self.rest.get(&path, &[("q", &query)])?;
+        Ok(pulls.values.into_iter().find(|pull| {
+            let source = pull.source.repository.as_ref();
+            let destination = pull.destination.repository.as_ref();
+            source.is_none_or(|repo| repo.full_name.eq_ignore_ascii_case(source_repo))
+                && destination.is_none_or(|repo| repo.full_name.eq_ignore_ascii_case(target_repo))
+        }))
+    }
+}
+
+impl Provider for Bitbucket {
+    fn list(&self, group: &str) -> Result<Vec<String>> {
+        let path = format!("repositories/{}", group);
+        let repos: Vec<BitbucketRepo> = self.list_all(&path, &[])?;
+        Ok(repos.into_iter().map(|repo| repo.full_name).collect())
+    }
+
+    fn create_repo(&self, repo: &str, private: bool) -> Result<()> {
+        let body = json!({ "scm": "git", "is_private": private });
+        let _: BitbucketRepo = self.rest.post(&Self::repo_path(repo)?, &body)?;
+        Ok(())
+    }
+
+    fn delete_repo(&self, repo: &str) -> Result<()> {
+        self.rest.delete(&Self::repo_path(repo)?)
+    }
+
+    fn get_default_branch(&self, repo: &str) -> Result<String> {
+        match self.get_repo(repo)?.mainbranch {
+            Some(branch) => Ok(branch.name),
+            None => bail!(
+                "bitbucket repository {} has no main branch",
+                style(repo).yellow()
+            ),
+        }
+    }
+
+    fn get_upstream(&self, repo: &str) -> Result<String> {
+        match self.get_repo(repo)?.parent {
+            Some(parent) => Ok(parent.full_name),
+            None => bail!(errors::REPO_NO_UPSTREAM),
+        }
+    }
+
+    fn get_meta(&self, repo: &str) -> Result<RepoMeta> {
+        // Bitbucket has no stars, topics or archiving.
+        let repo = self.get_repo(repo)?;
+        Ok(RepoMeta {
+            description: repo.description,
+            ..Default::default()
+        })
+    }
+
+    fn get_size(&self, repo: &str) -> Result<u64> {
+        Ok(self.get_repo(repo)?.size)
+    }
+
+    fn list_activity(&self, repo: &str, since: Epoch) -> Result<Vec<ActivityEvent>> {
+        // Bitbucket has no releases, only the commits are listed. The commits
+        // are sorted by time desc, stop at the first one before `since`.
+        let path = format!("{}/commits", Self::repo_path(repo)?);
+        let mut events = vec![];
+        let mut page: usize = 1;
+        loop {
+            let page_str = page.to_string();
+            let commits: BitbucketPage<BitbucketCommit> =
+                self.rest.get(&path, &[("page", page_str.as_str())])?;
+            for commit in commits.values {
+                let time = api::parse_time(&commit.date)?;
+                if time < since {
+                    return Ok(events);
+                }
+                let author = match commit.author.raw.split_once(" <") {
+                    Some((name, _)) => name.to_string(),
+                    None => commit.author.raw,
+                };
+                events.push(api::commit_activity(
+                    &commit.hash,
+                    &commit.message,
+                    author,
+                    time,
+                ));
+            }
+            if commits.next.is_none() {
+                return Ok(events);
+            }
+            page += 1;
+        }
+    }
+
+    fn get_merge(&self, opts: &MergeOption) -> Result<Option<String>> {
+        Ok(self.find_pull(opts)?.map(|pull| pull.links.html.href))
+    }
+
+    fn get_merge_number(&self, opts: &MergeOption) -> Result<Option<u64>> {
+        Ok(self.find_pull(opts)?.map(|pull| pull.id))
+    }
+
+    fn create_merge(&self, opts: &MergeOption) -> Result<String> {
+        if opts.milestone.is_some() || opts.project.is_some() {
+            bail!("sorry, bitbucket does not support milestone or project")
+        }
+        let target_repo = opts.upstream.as_deref().unwrap_or(&opts.repo);
+        let body = json!({
+            "title": opts.title,
+            "description": opts.body,
+            "source": {
+                "branch": { "name": opts.source },
+                "repository": { "full_name": opts.repo },
+            },
+            "destination": {
+                "branch": { "name": opts.target },
+            },
+        });
+        let path = format!("{}/pullrequests", Self::repo_path(target_repo)?);
+        let pull: BitbucketPull = self.rest.post(&path, &body)?;
+        Ok(pull.links.html.href)
+    }
+
+    fn list_my_merges(&self) -> Result<Vec<MergeItem>> {
+        let user: BitbucketUser = self.rest.get("user", &[])?;
+        let path = format!("pullrequests/{}", user.account_id);
+        let pulls: Vec<BitbucketPull> = self.list_all(&path, &[("state", "OPEN")])?;
+        let mut items = Vec::with_capacity(pulls.len());
+        for mut pull in pulls {
+            let repo = match pull.destination.repository.take() {
+                Some(repo) => repo.full_name,
+                None => continue,
+            };
+            items.push(Self::merge_item(repo, pull)?);
+        }
+        Ok(items)
+    }
+
+    fn list_review_merges(&self) -> Result<Vec<MergeItem>> {
+        bail!("sorry, bitbucket does not support listing pull requests to review")
+    }
+
+    fn list_merges(&self, repo: &str) -> Result<Vec<MergeItem>> {
+        let path = format!("{}/pullrequests", Self::repo_path(repo)?);
+        let pulls: Vec<BitbucketPull> = self.list_all(&path, &[("state", "OPEN")])?;
+        pulls
+            .into_iter()
+            .map(|pull| Self::merge_item(repo.to_string(), pull))
+            .collect()
+    }
+
+    fn approve_merge(&self, repo: &str, number: u64) -> Result<()> {
+        let path = format!("{}/pullrequests/{}/approve", Self::repo_path(repo)?, number);
+        let _: serde_json::Value = self.rest.post(&path, &json!({}))?;
+        Ok(())
+    }
+
+    fn comment_merge(&self, repo: &str, number: u64, body: &str) -> Result<()> {
+        let path = format!(
+            "{}/pullrequests/{}/comments",
+            Self::repo_path(repo)?,
+            number
+        );
+        let body = json!({ "content": { "raw": body } });
+        let _: serde_json::Value = self.rest.post(&path, &body)?;
+        Ok(())
+    }
+
+    fn list_inbox(&self) -> Result<Vec<InboxItem>> {
+        bail!("sorry, bitbucket does not support notifications")
+    }
+
+    fn mark_inbox_read(&self, _id: &str) -> Result<()> {
+        bail!("sorry, bitbucket does not support notifications")
+    }
+
+    fn get_merge_ref(&self, _repo: &str, _number: u64) -> Result<String> {
+        bail!("sorry, bitbucket does not expose the git ref of pull request")
+    }
+
+    fn get_repo_url(&self, name: &str, branch: Option<String>, _remote: &Remote) -> Result<String> {
+        match branch {
+            Some(branch) => Ok(format!("{}/{}/src/{}", Self::WEB_URL, name, branch)),
+            None => Ok(format!("{}/{}", Self::WEB_URL, name)),
+        }
+    }
+
+    fn get_compare_url(
+        &self,
+        repo: &str,
+        base: &str,
+        head: &str,
+        _remote: &Remote,
+    ) -> Result<String> {
+        if head.contains(':') {
+            bail!("sorry, bitbucket does not support compare url across forks")
+        }
+        // Bitbucket separates the branches with a carriage return.
+        Ok(format!(
+            "{}/{}/branches/compare/{}%0D{}",
+            Self::WEB_URL,
+            repo,
+            head,
+            base
+        ))
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDateTime;
+use console::style;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::api::rest::{Auth, Rest};
+use crate::api::{self, InboxItem, MergeItem, MergeOption, Provider};
+use crate::config::Remote;
+use crate::db::{ActivityEvent, Epoch, RepoMeta};
+use crate::util::{self, Shell};
+
+pub struct Gerrit {
+    rest: Rest,
+
+    url: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GerritChange {
+    project: String,
+
+    #[serde(rename = "_number")]
+    number: u64,
+
+    #[serde(default)]
+    subject: String,
+    #[serde(default)]
+    topic: String,
+    #[serde(default)]
+    created: String,
+    #[serde(default)]
+    submitted: String,
+
+    owner: Option<GerritAccount>,
+
+    #[serde(default)]
+    labels: HashMap<String, GerritLabel>,
+
+    current_revision: Option<String>,
+
+    #[serde(default)]
+    revisions: HashMap<String, GerritRevision>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GerritAccount {
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GerritLabel {
+    approved: Option<serde_json::Value>,
+    rejected: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GerritProject {
+    #[serde(default)]
+    description: String,
+
+    #[serde(default)]
+    state: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GerritRevision {
+    #[serde(rename = "ref")]
+    git_ref: String,
+}
+
+impl Gerrit {
+    /// Gerrit prefixes every JSON response with this magic line to prevent
+    /// XSSI attacks, it must be stripped before parsing.
+    const XSSI_PREFIX: &str = ")]}'";
+
+    pub fn new<U, T>(url: U, token: T, retry: u32) -> Result<Box<dyn Provider>>
+    where
+        U: AsRef<str>,
+        T: AsRef<str>,
+    {
+        if url.as_ref().is_empty() {
+            bail!("for gerrit provider, you must specify api url, please check your config")
+        }
+        let url = url.as_ref().trim_end_matches('/').to_string();
+        // The token for Gerrit is "<username>:<http-password>". Authenticated
+        // requests must be prefixed with "/a".
+        let rest = match token.as_ref() {
+            "" => Rest::new("gerrit", &url, Auth::None)?,
+            token => match token.split_once(':') {
+                Some((user, password)) => {
+                    let auth = Auth::Basic(user.to_string(), password.to_string());
+                    Rest::new("gerrit", format!("{}/a", url), auth)?
+                }
+                None => bail!("gerrit token should be in format <username>:<http-password>"),
+            },
+        };
+        let rest = rest.with_strip_prefix(Self::XSSI_PREFIX).with_retry(retry);
+        Ok(Box::new(Gerrit { rest, url }))
+    }
+
+    fn query_change(&self, opts: &MergeOption) -> Result<Option<GerritChange>> {
+        let query = format!(
+            "status:open project:{} branch:{} topic:{}",
+            opts.repo, opts.target, opts.source
+        );
+        let changes: Vec<GerritChange> = self.rest.get(
+            "changes/",
+            &[("q", &query), ("n", "1"), ("o", "CURRENT_REVISION")],
+        )?;
+        Ok(changes.into_iter().next())
+    }
+
+    /// In Gerrit, a change is created or updated (as a new patchset) by
+    /// pushing to the magic ref "refs/for/<target>". We use source branch as
+    /// the topic, so that we can find the change later.
+    fn push_change(opts: &MergeOption) -> Result<()> {
+        let refspec = format!(
+            "{}:refs/for/{}%topic={}",
+            opts.source, opts.target, opts.source
+        );
+        Shell::git()
+            .args(["push", "origin", refspec.as_str()])
+            .exec()?;
+        Ok(())
+    }
+
+    fn change_url(&self, change: &GerritChange) -> String {
+        format!("{}/c/{}/+/{}", self.url, change.project, change.number)
+    }
+
+    fn list_changes(&self, query: &str) -> Result<Vec<MergeItem>> {
+        let changes: Vec<GerritChange> = self.rest.get(
+            "changes/",
+            &[("q", query), ("o", "LABELS"), ("o", "DETAILED_ACCOUNTS")],
+        )?;
+        let mut items = Vec::with_capacity(changes.len());
+        for change in changes {
+            // The CI reports to the "Verified" label.
+            let ci = match change.labels.get("Verified") {
+                Some(label) if label.rejected.is_some() => String::from("failure"),
+                Some(label) if label.approved.is_some() => String::from("success"),
+                Some(_) => String::from("pending"),
+                None => String::new(),
+            };
+            items.push(MergeItem {
+                ci,
+                url: self.change_url(&change),
+                created: parse_time(&change.created)?,
+                author: change.owner.map(|owner| owner.name).unwrap_or_default(),
+                repo: change.project,
+                number: change.number,
+                title: change.subject,
+                // The change created by us uses the source branch as topic.
+                source: change.topic,
+            });
+        }
+        Ok(items)
+    }
+}
+
+impl Provider for Gerrit {
+    fn list(&self, group: &str) -> Result<Vec<String>> {
+        let prefix = format!("{}/", group);
+        let projects: HashMap<String, serde_json::Value> =
+            self.rest.get("projects/", &[("p", &prefix)])?;
+        let mut names: Vec<String> = projects.into_keys().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn create_repo(&self, repo: &str, private: bool) -> Result<()> {
+        if private {
+            bail!("sorry, gerrit does not support private repo, please use access rights")
+        }
+        let path = format!("projects/{}", urlencode(repo));
+        let _: serde_json::Value = self.rest.put(&path, &json!({}))?;
+        Ok(())
+    }
+
+    fn delete_repo(&self, _repo: &str) -> Result<()> {
+        bail!("sorry, gerrit does not support deleting repo")
+    }
+
+    fn get_default_branch(&self, repo: &str) -> Result<String> {
+        let path = format!("projects/{}/HEAD", urlencode(repo));
+        let head: String = self.rest.get(&path, &[])?;
+        match head.strip_prefix("refs/heads/") {
+            Some(branch) => Ok(branch.to_string()),
+            None => bail!("gerrit returned invalid HEAD {}", style(head).yellow()),
+        }
+    }
+
+    fn get_upstream(&self, _repo: &str) -> Result<String> {
+        bail!("sorry, gerrit does not support upstream features")
+    }
+
+    fn get_meta(&self, repo: &str) -> Result<RepoMeta> {
+        // Gerrit has no stars or topics, a read-only project is treated as
+        // archived.
+        let path = format!("projects/{}", urlencode(repo));
+        let project: GerritProject = self.rest.get(&path, &[])?;
+        Ok(RepoMeta {
+            description: project.description,
+            archived: project.state == "READ_ONLY",
+            ..Default::default()
+        })
+    }
+
+    fn list_activity(&self, repo: &str, since: Epoch) -> Result<Vec<ActivityEvent>> {
+        // Gerrit has no releases, the commits are the merged changes.
+        let after = api::to_datetime(since).format("%Y-%m-%d %H:%M:%S");
+        let query = format!("status:merged project:{} after:\"{}\"", repo, after);
+        let changes: Vec<GerritChange> = self.rest.get(
+            "changes/",
+            &[
+                ("q", &query),
+                ("o", "CURRENT_REVISION"),
+                ("o", "DETAILED_ACCOUNTS"),
+            ],
+        )?;
+        let mut events = Vec::with_capacity(changes.len());
+        for change in changes {
+            let author = change.owner.map(|owner| owner.name).unwrap_or_default();
+            let time = match change.submitted.as_str() {
+                "" => parse_time(&change.created)?,
+                submitted => parse_time(submitted)?,
+            };
+            let sha = change.current_revision.unwrap_or_default();
+            events.push(api::commit_activity(&sha, &change.subject, author, time));
+        }
+        Ok(events)
+    }
+
+    fn get_merge(&self, opts: &MergeOption) -> Result<Option<String>> {
+        if opts.upstream.is_some() {
+            bail!("sorry, gerrit does not support upstream features")
+        }
+        let change = self.query_change(opts)?;
+        Ok(change.map(|change| self.change_url(&change)))
+    }
+
+    fn get_merge_number(&self, opts: &MergeOption) -> Result<Option<u64>> {
+        if opts.upstream.is_some() {
+            bail!("sorry, gerrit does not support upstream features")
+        }
+        Ok(self.query_change(opts)?.map(|change| change.number))
+    }
+
+    fn create_merge(&self, opts: &MergeOption) -> Result<String> {
+        if opts.milestone.is_some() || opts.project.is_some() {
+            bail!("sorry, gerrit does not support milestone or project")
+        }
+        Self::push_change(opts)?;
+        match self.query_change(opts)? {
+            Some(change) => Ok(self.change_url(&change)),
+            None => bail!("could not find the change pushed to gerrit"),
+        }
+    }
+
+    fn update_merge(&self, opts: &MergeOption) -> Result<()> {
+        let change = match self.query_change(opts)? {
+            Some(change) => change,
+            None => return Ok(()),
+        };
+        let head = Shell::git()
+            .mute()
+            .args(["rev-parse", opts.source.as_str()])
+            .exec()?;
+        if change.current_revision.as_deref() == Some(head.as_str()) {
+            return Ok(());
+        }
+        println!();
+        // Declining keeps the change as is, it is still opened.
+        let upload = util::ask(format!(
+            "the change {} is outdated, do you want to upload a new patchset",
+            style(change.number).yellow()
+        ))?;
+        match upload {
+            true => Self::push_change(opts),
+            false => Ok(()),
+        }
+    }
+
+    fn list_my_merges(&self) -> Result<Vec<MergeItem>> {
+        self.list_changes("status:open owner:self")
+    }
+
+    fn list_review_merges(&self) -> Result<Vec<MergeItem>> {
+        self.list_changes("status:open reviewer:self -owner:self")
+    }
+
+    fn list_merges(&self, repo: &str) -> Result<Vec<MergeItem>> {
+        self.list_changes(&format!("status:open project:{}", repo))
+    }
+
+    fn approve_merge(&self, repo: &str, number: u64) -> Result<()> {
+        // Approving in Gerrit means voting the max "Code-Review" score.
+        let path = format!(
+            "changes/{}~{}/revisions/current/review",
+            urlencode(repo),
+            number
+        );
+        let body = json!({ "labels": { "Code-Review": 2 } });
+        let _: serde_json::Value = self.rest.post(&path, &body)?;
+        Ok(())
+    }
+
+    fn comment_merge(&self, repo: &str, number: u64, body: &str) -> Result<()> {
+        let path = format!(
+            "changes/{}~{}/revisions/current/review",
+            urlencode(repo),
+            number
+        );
+        let body = json!({ "message": body });
+        let _: serde_json::Value = self.rest.post(&path, &body)?;
+        Ok(())
+    }
+
+    fn list_inbox(&self) -> Result<Vec<InboxItem>> {
+        bail!("sorry, gerrit does not support notifications")
+    }
+
+    fn mark_inbox_read(&self, _id: &str) -> Result<()> {
+        bail!("sorry, gerrit does not support notifications")
+    }
+
+    fn merge_require_input(&self) -> bool {
+        false
+    }
+
+    fn get_merge_ref(&self, _repo: &str, number: u64) -> Result<String> {
+        // The ref of a change is "refs/changes/<nn>/<number>/<patchset>", ask
+        // Gerrit for the ref of current patchset.
+        let path = format!("changes/{}", number);
+        let change: GerritChange = self.rest.get(&path, &[("o", "CURRENT_REVISION")])?;
+        let revision = change
+            .current_revision
+            .and_then(|id| change.revisions.get(&id));
+        match revision {
+            Some(revision) => Ok(revision.git_ref.clone()),
+            None => bail!(
+                "gerrit did not return current revision for change {}",
+                number
+            ),
+        }
+    }
+
+    fn get_repo_url(&self, name: &str, branch: Option<String>, _remote: &Remote) -> Result<String> {
+        match branch {
+            Some(branch) => Ok(format!("{}/q/project:{}+branch:{}", self.url, name, branch)),
+            None => Ok(format!("{}/admin/repos/{}", self.url, name)),
+        }
+    }
+
+    fn get_compare_url(
+        &self,
+        _repo: &str,
+        _base: &str,
+        _head: &str,
+        _remote: &Remote,
+    ) -> Result<String> {
+        bail!("sorry, gerrit does not support compare url")
+    }
+}
+
+/// Gerrit uses UTC time in format "2006-01-02 15:04:05.000000000".
+fn parse_time(s: &str) -> Result<Epoch> {
+    let time = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+        .with_context(|| format!("invalid time {} returned by gerrit", style(s).yellow()))?;
+    Ok(time.timestamp().max(0) as Epoch)
+}
+
+fn urlencode(s: &str) -> String {
+    s.replace('/', "%2F")
+}
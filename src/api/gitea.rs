@@ -0,0 +1,553 @@
+use anyhow::{bail, Result};
+use console::style;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::api::rest::{Auth, Rest};
+use crate::api::{self, InboxItem, MergeItem, MergeOption, Provider};
+use crate::config::Remote;
+use crate::db::{ActivityEvent, ActivityKind, Epoch, RepoMeta};
+use crate::{errors, util};
+
+pub struct Gitea {
+    rest: Rest,
+
+    url: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaRepo {
+    full_name: String,
+    default_branch: String,
+
+    #[serde(default)]
+    fork: bool,
+    parent: Option<Box<GiteaRepo>>,
+
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    stars_count: u64,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    topics: Vec<String>,
+
+    /// The size in KiB.
+    #[serde(default)]
+    size: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaUser {
+    login: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaCommit {
+    sha: String,
+    commit: GiteaCommitDetail,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaCommitDetail {
+    message: String,
+    author: GiteaCommitAuthor,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaCommitAuthor {
+    name: String,
+    date: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaPull {
+    number: u64,
+    #[serde(default)]
+    title: String,
+    html_url: String,
+    created_at: String,
+    user: Option<GiteaUser>,
+
+    head: GiteaPullBranch,
+    base: GiteaPullBranch,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaPullBranch {
+    #[serde(rename = "ref")]
+    branch: String,
+    #[serde(default)]
+    sha: String,
+    repo: Option<GiteaRepoRef>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaRepoRef {
+    full_name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaIssue {
+    number: u64,
+    repository: GiteaRepoRef,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaMilestone {
+    id: u64,
+    title: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaStatus {
+    #[serde(default)]
+    state: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaNotification {
+    id: u64,
+    repository: GiteaRepoRef,
+    subject: GiteaSubject,
+    updated_at: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaSubject {
+    #[serde(default)]
+    title: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    html_url: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GiteaRelease {
+    tag_name: String,
+    name: String,
+    #[serde(default)]
+    draft: bool,
+    published_at: String,
+    author: GiteaUser,
+}
+
+impl Gitea {
+    const CODEBERG_URL: &str = "https://codeberg.org";
+    const QUERY_LIMIT: usize = 50;
+
+    pub fn new<U, T>(url: U, token: T, retry: u32) -> Result<Box<dyn Provider>>
+    where
+        U: AsRef<str>,
+        T: AsRef<str>,
+    {
+        if url.as_ref().is_empty() {
+            bail!("for gitea provider, you must specify api url, please check your config")
+        }
+        let url = url.as_ref().trim_end_matches('/').to_string();
+        let auth = match token.as_ref() {
+            "" => Auth::None,
+            token => Auth::Header(format!("token {}", token)),
+        };
+        let rest = Rest::new("gitea", format!("{}/api/v1", url), auth)?.with_retry(retry);
+        Ok(Box::new(Gitea { rest, url }))
+    }
+
+    /// Codeberg is a public Gitea instance, the api url can be omitted.
+    pub fn codeberg<U, T>(url: U, token: T, retry: u32) -> Result<Box<dyn Provider>>
+    where
+        U: AsRef<str>,
+        T: AsRef<str>,
+    {
+        match url.as_ref() {
+            "" => Self::new(Self::CODEBERG_URL, token, retry),
+            url => Self::new(url, token, retry),
+        }
+    }
+
+    fn get_repo(&self, name: &str) -> Result<GiteaRepo> {
+        self.rest.get(&Self::repo_path(name)?, &[])
+    }
+
+    fn repo_path(name: &str) -> Result<String> {
+        let (owner, base) = util::split_name(name);
+        if owner.is_empty() || base.is_empty() {
+            bail!("invalid gitea repository name {}", style(name).yellow())
+        }
+        Ok(format!("repos/{}/{}", owner, base))
+    }
+
+    /// Find the open pull request from source branch to target branch. In
+    /// upstream mode, the pull request is in the upstream repo, and its head
+    /// is in the forked repo.
+    fn find_pull(&self, opts: &MergeOption) -> Result<Option<GiteaPull>> {
+        let target_repo = opts.upstream.as_deref().unwrap_or(&opts.repo);
+        let path = format!("{}/pulls", Self::repo_path(target_repo)?);
+        let limit = Self::QUERY_LIMIT.to_string();
+        let mut page: usize = 1;
+        loop {
+            let page_str = page.to_string();
+            let query = [
+                ("state", "open"),
+                ("limit", limit.as_str()),
+                ("page", page_str.as_str()),
+            ];
+            let pulls: Vec<GiteaPull> = self.rest.get(&path, &query)?;
+            let done = pulls.len() < Self::QUERY_LIMIT;
+            let found = pulls.into_iter().find(|pull| {
+                pull.head.branch == opts.source
+                    && pull.base.branch == opts.target
+                    && pull
+                        .head
+                        .repo
+                        .as_ref()
+                        .is_none_or(|repo| repo.full_name.eq_ignore_ascii_case(&opts.repo))
+            });
+            if found.is_some() || done {
+                return Ok(found);
+            }
+            page += 1;
+        }
+    }
+
+    fn get_milestone(&self, repo: &str, title: &str) -> Result<u64> {
+        let path = format!("{}/milestones", Self::repo_path(repo)?);
+        let milestones: Vec<GiteaMilestone> = self
+            .rest
+            .get(&path, &[("state", "open"), ("name", title)])?;
+        match milestones
+            .into_iter()
+            .find(|milestone| milestone.title == title)
+        {
+            Some(milestone) => Ok(milestone.id),
+            None => bail!("could not find milestone {}", style(title).yellow()),
+        }
+    }
+
+    /// Search the open pull requests related to current user across repos,
+    /// the `filter` is a query flag such as "created".
+    fn search_pulls(&self, filter: &str) -> Result<Vec<MergeItem>> {
+        let limit = Self::QUERY_LIMIT.to_string();
+        let query = [
+            ("type", "pulls"),
+            ("state", "open"),
+            (filter, "true"),
+            ("limit", limit.as_str()),
+        ];
+        let issues: Vec<GiteaIssue> = self.rest.get("repos/issues/search", &query)?;
+        // The issues have no branch, get the pull requests for them.
+        let mut items = Vec::with_capacity(issues.len());
+        for issue in issues {
+            let path = format!(
+                "{}/pulls/{}",
+                Self::repo_path(&issue.repository.full_name)?,
+                issue.number
+            );
+            let pull: GiteaPull = self.rest.get(&path, &[])?;
+            items.push(self.merge_item(issue.repository.full_name, pull)?);
+        }
+        Ok(items)
+    }
+
+    /// Convert the pull request to merge item, the CI status is queried for
+    /// its head commit.
+    fn merge_item(&self, repo: String, pull: GiteaPull) -> Result<MergeItem> {
+        let path = format!(
+            "{}/commits/{}/status",
+            Self::repo_path(&repo)?,
+            pull.head.sha
+        );
+        let ci = match self.rest.get_opt::<GiteaStatus>(&path, &[])? {
+            Some(status) => match status.state.as_str() {
+                "success" => String::from("success"),
+                "failure" | "error" => String::from("failure"),
+                "pending" | "warning" => String::from("pending"),
+                _ => String::new(),
+            },
+            None => String::new(),
+        };
+        Ok(MergeItem {
+            repo,
+            number: pull.number,
+            title: pull.title,
+            author: pull.user.map(|user| user.login).unwrap_or_default(),
+            source: pull.head.branch,
+            url: pull.html_url,
+            created: api::parse_time(&pull.created_at)?,
+            ci,
+        })
+    }
+
+    fn list_all(&self, path: &str) -> Result<Option<Vec<String>>> {
+        let mut names = vec![];
+        let limit = Self::QUERY_LIMIT.to_string();
+        let mut page: usize = 1;
+        loop {
+            let page_str = page.to_string();
+            let query = [("limit", limit.as_str()), ("page", page_str.as_str())];
+            let repos: Vec<GiteaRepo> = match self.rest.get_opt(path, &query)? {
+                Some(repos) => repos,
+                None => return Ok(None),
+            };
+            let done = repos.len() < Self::QUERY_LIMIT;
+            names.extend(repos.into_iter().map(|repo| repo.full_name));
+            if done {
+                return Ok(Some(names));
+            }
+            page += 1;
+        }
+    }
+}
+
+impl Provider for Gitea {
+    fn list(&self, group: &str) -> Result<Vec<String>> {
+        // The group can be an organization or a user, try organization first.
+        if let Some(names) = self.list_all(&format!("orgs/{}/repos", group))? {
+            return Ok(names);
+        }
+        match self.list_all(&format!("users/{}/repos", group))? {
+            Some(names) => Ok(names),
+            None => bail!("could not find gitea owner {}", style(group).yellow()),
+        }
+    }
+
+    fn create_repo(&self, repo: &str, private: bool) -> Result<()> {
+        let (owner, base) = util::split_name(repo);
+        if owner.is_empty() || base.is_empty() {
+            bail!("invalid gitea repository name {}", style(repo).yellow())
+        }
+        let user: GiteaUser = self.rest.get("user", &[])?;
+        let path = match user.login == owner {
+            true => String::from("user/repos"),
+            false => format!("orgs/{}/repos", owner),
+        };
+        let body = json!({ "name": base, "private": private });
+        let _: GiteaRepo = self.rest.post(&path, &body)?;
+        Ok(())
+    }
+
+    fn delete_repo(&self, repo: &str) -> Result<()> {
+        self.rest.delete(&Self::repo_path(repo)?)
+    }
+
+    fn get_default_branch(&self, repo: &str) -> Result<String> {
+        Ok(self.get_repo(repo)?.default_branch)
+    }
+
+    fn get_upstream(&self, repo: &str) -> Result<String> {
+        let repo = self.get_repo(repo)?;
+        if !repo.fork {
+            bail!(errors::REPO_NO_UPSTREAM)
+        }
+        match repo.parent {
+            Some(parent) => Ok(parent.full_name),
+            None => bail!(errors::REPO_NO_UPSTREAM),
+        }
+    }
+
+    fn get_meta(&self, repo: &str) -> Result<RepoMeta> {
+        let repo = self.get_repo(repo)?;
+        Ok(RepoMeta {
+            description: repo.description,
+            stars: repo.stars_count,
+            archived: repo.archived,
+            topics: repo.topics,
+            updated: 0,
+        })
+    }
+
+    fn get_size(&self, repo: &str) -> Result<u64> {
+        Ok(self.get_repo(repo)?.size * 1024)
+    }
+
+    fn list_activity(&self, repo: &str, since: Epoch) -> Result<Vec<ActivityEvent>> {
+        let (owner, base) = util::split_name(repo);
+        if owner.is_empty() || base.is_empty() {
+            bail!("invalid gitea repository name {}", style(repo).yellow())
+        }
+        // The old gitea ignores "since", so the commits are filtered again.
+        let since_str = api::to_datetime(since).to_rfc3339();
+        let limit = Self::QUERY_LIMIT.to_string();
+        let path = format!("repos/{}/{}/commits", owner, base);
+        let query = [
+            ("since", since_str.as_str()),
+            ("limit", limit.as_str()),
+            ("stat", "false"),
+        ];
+        let commits: Vec<GiteaCommit> = self.rest.get(&path, &query)?;
+        let mut events = Vec::with_capacity(commits.len());
+        for commit in commits {
+            let time = api::parse_time(&commit.commit.author.date)?;
+            if time < since {
+                break;
+            }
+            events.push(api::commit_activity(
+                &commit.sha,
+                &commit.commit.message,
+                commit.commit.author.name,
+                time,
+            ));
+        }
+
+        let path = format!("repos/{}/{}/releases", owner, base);
+        let releases: Vec<GiteaRelease> = self.rest.get(&path, &[("limit", limit.as_str())])?;
+        for release in releases {
+            if release.draft {
+                continue;
+            }
+            let time = api::parse_time(&release.published_at)?;
+            if time < since {
+                break;
+            }
+            events.push(ActivityEvent {
+                kind: ActivityKind::Release,
+                id: release.tag_name,
+                title: release.name,
+                author: release.author.login,
+                time,
+            });
+        }
+        Ok(events)
+    }
+
+    fn get_merge(&self, opts: &MergeOption) -> Result<Option<String>> {
+        Ok(self.find_pull(opts)?.map(|pull| pull.html_url))
+    }
+
+    fn get_merge_number(&self, opts: &MergeOption) -> Result<Option<u64>> {
+        Ok(self.find_pull(opts)?.map(|pull| pull.number))
+    }
+
+    fn create_merge(&self, opts: &MergeOption) -> Result<String> {
+        if opts.project.is_some() {
+            bail!("sorry, gitea does not support adding pull request to project")
+        }
+        // In upstream mode, the pull request is created in upstream, with
+        // head "owner:branch".
+        let (target_repo, head) = match &opts.upstream {
+            Some(upstream) => {
+                let (owner, _) = util::split_name(&opts.repo);
+                (upstream.as_str(), format!("{}:{}", owner, opts.source))
+            }
+            None => (opts.repo.as_str(), opts.source.clone()),
+        };
+        let milestone = match &opts.milestone {
+            Some(title) => Some(self.get_milestone(target_repo, title)?),
+            None => None,
+        };
+        let mut body = json!({
+            "title": opts.title,
+            "body": opts.body,
+            "head": head,
+            "base": opts.target,
+        });
+        if let Some(milestone) = milestone {
+            body["milestone"] = json!(milestone);
+        }
+        let path = format!("{}/pulls", Self::repo_path(target_repo)?);
+        let pull: GiteaPull = self.rest.post(&path, &body)?;
+        Ok(pull.html_url)
+    }
+
+    fn list_my_merges(&self) -> Result<Vec<MergeItem>> {
+        self.search_pulls("created")
+    }
+
+    fn list_review_merges(&self) -> Result<Vec<MergeItem>> {
+        self.search_pulls("review_requested")
+    }
+
+    fn list_merges(&self, repo: &str) -> Result<Vec<MergeItem>> {
+        let path = format!("{}/pulls", Self::repo_path(repo)?);
+        let limit = Self::QUERY_LIMIT.to_string();
+        let mut items = vec![];
+        let mut page: usize = 1;
+        loop {
+            let page_str = page.to_string();
+            let query = [
+                ("state", "open"),
+                ("limit", limit.as_str()),
+                ("page", page_str.as_str()),
+            ];
+            let pulls: Vec<GiteaPull> = self.rest.get(&path, &query)?;
+            let done = pulls.len() < Self::QUERY_LIMIT;
+            for pull in pulls {
+                items.push(self.merge_item(repo.to_string(), pull)?);
+            }
+            if done {
+                return Ok(items);
+            }
+            page += 1;
+        }
+    }
+
+    fn approve_merge(&self, repo: &str, number: u64) -> Result<()> {
+        let path = format!("{}/pulls/{}/reviews", Self::repo_path(repo)?, number);
+        let body = json!({ "event": "APPROVED" });
+        let _: serde_json::Value = self.rest.post(&path, &body)?;
+        Ok(())
+    }
+
+    fn comment_merge(&self, repo: &str, number: u64, body: &str) -> Result<()> {
+        // The comments of pull request are issue comments.
+        let path = format!("{}/issues/{}/comments", Self::repo_path(repo)?, number);
+        let body = json!({ "body": body });
+        let _: serde_json::Value = self.rest.post(&path, &body)?;
+        Ok(())
+    }
+
+    fn list_inbox(&self) -> Result<Vec<InboxItem>> {
+        // Only the unread notifications are returned by default.
+        let limit = Self::QUERY_LIMIT.to_string();
+        let notifications: Vec<GiteaNotification> = self
+            .rest
+            .get("notifications", &[("limit", limit.as_str())])?;
+        let mut items = Vec::with_capacity(notifications.len());
+        for notification in notifications {
+            // Keep the same kind as Github.
+            let kind = match notification.subject.kind.as_str() {
+                "Pull" => String::from("PullRequest"),
+                _ => notification.subject.kind,
+            };
+            items.push(InboxItem {
+                id: notification.id.to_string(),
+                repo: notification.repository.full_name,
+                kind,
+                title: notification.subject.title,
+                // Gitea does not tell why the notification is sent.
+                reason: String::new(),
+                url: notification.subject.html_url,
+                updated: api::parse_time(&notification.updated_at)?,
+            });
+        }
+        Ok(items)
+    }
+
+    fn mark_inbox_read(&self, id: &str) -> Result<()> {
+        let path = format!("notifications/threads/{}", id);
+        self.rest.patch(&path, &[("to-status", "read")])
+    }
+
+    fn get_merge_ref(&self, _repo: &str, number: u64) -> Result<String> {
+        Ok(format!("refs/pull/{}/head", number))
+    }
+
+    fn get_repo_url(&self, name: &str, branch: Option<String>, _remote: &Remote) -> Result<String> {
+        match branch {
+            Some(branch) => Ok(format!("{}/{}/src/branch/{}", self.url, name, branch)),
+            None => Ok(format!("{}/{}", self.url, name)),
+        }
+    }
+
+    fn get_compare_url(
+        &self,
+        repo: &str,
+        base: &str,
+        head: &str,
+        _remote: &Remote,
+    ) -> Result<String> {
+        Ok(format!("{}/{}/compare/{}...{}", self.url, repo, base, head))
+    }
+}
@@ -1,11 +1,24 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::thread;
+use std::time::Duration;
+
 use anyhow::{bail, Context, Result};
 use console::style;
+use octocrab::service::middleware::retry::RetryConfig;
 use octocrab::{models, Octocrab};
-use serde::Serialize;
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use tokio::runtime::Runtime;
 
 use crate::{
-    api::{self, MergeOption, Provider},
+    api::{
+        self, InboxItem, IssueItem, MergeItem, MergeOption, MergeSource, Provider, Retry, Retryable,
+    },
+    db::{ActivityEvent, ActivityKind, Epoch, HttpCache, HttpEntry, RepoMeta},
     errors, util,
 };
 
@@ -13,6 +26,18 @@ pub struct Github {
     runtime: Runtime,
     instance: Octocrab,
 
+    /// The graphql endpoint of GitHub Enterprise is "/api/graphql" rather
+    /// than under the rest api url ("/api/v3"), so it needs another instance.
+    graphql: Octocrab,
+    retry: Retry,
+
+    /// Opened when the first cached request is sent.
+    cache: RefCell<Option<HttpCache>>,
+
+    /// The url of web pages, "https://github.com" or the url of GitHub
+    /// Enterprise, such as "https://github.example.com".
+    web_url: String,
+
     query_opt: GithubQueryOption,
 }
 
@@ -21,6 +46,66 @@ struct GithubQueryOption {
     per_page: u32,
 }
 
+#[derive(Serialize, Debug)]
+struct GithubSinceOption {
+    since: String,
+    per_page: u32,
+}
+
+#[derive(Deserialize, Debug)]
+struct GithubCommit {
+    sha: String,
+    commit: GithubCommitDetail,
+}
+
+#[derive(Deserialize, Debug)]
+struct GithubCommitDetail {
+    message: String,
+    author: Option<GithubCommitAuthor>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GithubCommitAuthor {
+    name: String,
+    date: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GithubRelease {
+    tag_name: String,
+    name: Option<String>,
+    published_at: Option<String>,
+    author: Option<GithubUser>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GithubUser {
+    login: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GithubSearch<T> {
+    items: Vec<T>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GithubDeviceCode {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    expires_in: u64,
+    interval: u64,
+}
+
+/// The access token, or the error if the user has not authorized yet.
+#[derive(Deserialize, Debug)]
+struct GithubAccessToken {
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+    interval: Option<u64>,
+}
+
 #[derive(Debug)]
 struct GithubPullOption {
     owner: String,
@@ -34,32 +119,81 @@ struct GithubPullOption {
 impl Github {
     const QUERY_PER_PAGE: u32 = 200;
 
-    pub fn new(token: impl AsRef<str>) -> Result<Box<dyn Provider>> {
-        let mut builder = Octocrab::builder();
-        if !token.as_ref().is_empty() {
-            builder = builder.personal_token(token.as_ref().to_string());
-        }
+    const WEB_HOST: &str = "github.com";
+
+    /// Create the provider, the `url` is the api url of GitHub Enterprise,
+    /// such as "https://github.example.com/api/v3", empty means github.com.
+    pub fn new<U, T>(url: U, token: T, retry: u32) -> Result<Box<dyn Provider>>
+    where
+        U: AsRef<str>,
+        T: AsRef<str>,
+    {
+        let web_url = Self::web_url(url.as_ref());
+        let build = |base: &str| -> Result<Octocrab> {
+            let mut builder = Octocrab::builder();
+            // The failed requests are retried by our policy, with backoff.
+            builder.add_retry_config(RetryConfig::None);
+            if !base.is_empty() {
+                builder = builder
+                    .base_uri(base)
+                    .with_context(|| format!("invalid github api url {}", style(base).yellow()))?;
+            }
+            if !token.as_ref().is_empty() {
+                builder = builder.personal_token(token.as_ref().to_string());
+            }
+            Ok(builder.build()?)
+        };
         // The octocrab can only run in tokio. Create a runtime for it.
         let runtime = Runtime::new().context("unable to create tokio runtime")?;
-        let instance = runtime.block_on(async { builder.build() })?;
+        let (instance, graphql) = runtime.block_on(async {
+            let instance = build(url.as_ref())?;
+            let graphql = match url.as_ref() {
+                "" => instance.clone(),
+                _ => build(&format!("{}/api", web_url))?,
+            };
+            Ok::<_, anyhow::Error>((instance, graphql))
+        })?;
         let query_opt = GithubQueryOption {
             per_page: Self::QUERY_PER_PAGE,
         };
         Ok(Box::new(Github {
             runtime,
             instance,
+            retry: Retry::new("github", retry),
+            cache: RefCell::new(None),
+            graphql,
+            web_url,
             query_opt,
         }))
     }
+
+    /// Derive the web url from api url, empty means github.com. The api of
+    /// GitHub Enterprise is served under the web host, and "api.github.com"
+    /// is for github.com. The scheme is kept, such as "http://" of a local
+    /// server.
+    fn web_url(url: &str) -> String {
+        let (scheme, host) = match url.split_once("://") {
+            Some((scheme, rest)) => (scheme, rest),
+            None => ("https", url),
+        };
+        let host = host.split('/').next().unwrap_or_default();
+        match host {
+            "" => format!("https://{}", Self::WEB_HOST),
+            host => match host.strip_prefix("api.") {
+                Some(Self::WEB_HOST) => format!("{}://{}", scheme, Self::WEB_HOST),
+                _ => format!("{}://{}", scheme, host),
+            },
+        }
+    }
 }
 
 impl Provider for Github {
     fn list(&self, group: &str) -> Result<Vec<String>> {
-        let url = format!("/users/{}/repos", group);
-
-        let repos: Vec<models::Repository> = self
-            .runtime
-            .block_on(self.instance.get(url, Some(&self.query_opt)))?;
+        let url = format!(
+            "/users/{}/repos?per_page={}",
+            group, self.query_opt.per_page
+        );
+        let repos: Vec<models::Repository> = self.get_cached(&url)?;
         let mut names: Vec<String> = Vec::with_capacity(repos.len());
         for repo in repos {
             if let Some(name) = repo.full_name {
@@ -70,6 +204,33 @@ impl Provider for Github {
         Ok(names)
     }
 
+    fn create_repo(&self, repo: &str, private: bool) -> Result<()> {
+        let (owner, name) = Self::parse_repo_name(repo)?;
+        let user = self
+            .send(Method::GET, || async {
+                self.instance.current().user().await
+            })
+            .context("unable to get current user from github")?;
+        let url = match user.login == owner {
+            true => String::from("/user/repos"),
+            false => format!("/orgs/{}/repos", owner),
+        };
+        let body = json!({ "name": name, "private": private });
+        let _: models::Repository = self
+            .send(Method::POST, || self.instance.post(&url, Some(&body)))
+            .context("unable to create repository in github")?;
+        Ok(())
+    }
+
+    fn delete_repo(&self, repo: &str) -> Result<()> {
+        let (owner, name) = Self::parse_repo_name(repo)?;
+        self.send(Method::DELETE, || async {
+            self.instance.repos(&owner, &name).delete().await
+        })
+        .context("unable to delete repository in github")?;
+        Ok(())
+    }
+
     fn get_default_branch(&self, repo: &str) -> Result<String> {
         let (owner, name) = Self::parse_repo_name(repo)?;
         let repo = self.get_repo(&owner, &name)?;
@@ -86,55 +247,314 @@ impl Provider for Github {
         Ok(name.to_string())
     }
 
-    fn get_merge(&self, opts: &MergeOption) -> Result<Option<String>> {
-        let pr = Self::pr_options(opts)?;
+    fn get_meta(&self, repo: &str) -> Result<RepoMeta> {
+        let (owner, name) = Self::parse_repo_name(repo)?;
+        let repo = self.get_repo(&owner, &name)?;
+        Ok(RepoMeta {
+            description: repo.description.unwrap_or_default(),
+            stars: repo.stargazers_count.unwrap_or_default() as u64,
+            archived: repo.archived.unwrap_or_default(),
+            topics: repo.topics.unwrap_or_default(),
+            updated: 0,
+        })
+    }
 
-        let query = match &opts.upstream {
-            Some(upstream) => format!(
-                "is:open is:pr author:{} head:{} base:{} repo:{}",
-                pr.head_owner, opts.source, opts.target, upstream
-            ),
-            None => format!(
-                "is:open is:pr head:{} base:{} repo:{}",
-                opts.source, opts.target, opts.repo
-            ),
+    fn get_size(&self, repo: &str) -> Result<u64> {
+        // Github reports the size in KiB.
+        let (owner, name) = Self::parse_repo_name(repo)?;
+        let repo = self.get_repo(&owner, &name)?;
+        Ok(repo.size.unwrap_or_default() as u64 * 1024)
+    }
+
+    fn list_activity(&self, repo: &str, since: Epoch) -> Result<Vec<ActivityEvent>> {
+        let (owner, name) = Self::parse_repo_name(repo)?;
+        // The commits api lists the default branch if no sha is given.
+        let url = format!("/repos/{}/{}/commits", owner, name);
+        let opt = GithubSinceOption {
+            since: api::to_datetime(since).to_rfc3339(),
+            per_page: 100,
         };
-        let mut issues = self.runtime.block_on(
-            self.instance
-                .search()
-                .issues_and_pull_requests(&query)
-                .send(),
-        )?;
-        let issues = issues.take_items();
-        if issues.is_empty() {
-            return Ok(None);
+        let commits: Vec<GithubCommit> = self
+            .send(Method::GET, || self.instance.get(&url, Some(&opt)))
+            .context("unable to list commits in github")?;
+        let mut events = Vec::with_capacity(commits.len());
+        for commit in commits {
+            let (author, time) = match commit.commit.author {
+                Some(author) => (author.name, api::parse_time(&author.date)?),
+                None => (String::new(), since),
+            };
+            events.push(api::commit_activity(
+                &commit.sha,
+                &commit.commit.message,
+                author,
+                time,
+            ));
         }
-        let pr = &issues[0];
-        return Ok(Some(pr.html_url.to_string()));
+
+        // The releases are listed from the latest, stop at the first old one.
+        let url = format!("/repos/{}/{}/releases", owner, name);
+        let releases: Vec<GithubRelease> = self
+            .send(Method::GET, || self.instance.get(&url, None::<&()>))
+            .context("unable to list releases in github")?;
+        for release in releases {
+            // The draft release is not published.
+            let time = match &release.published_at {
+                Some(time) => api::parse_time(time)?,
+                None => continue,
+            };
+            if time < since {
+                break;
+            }
+            events.push(ActivityEvent {
+                kind: ActivityKind::Release,
+                title: release.name.unwrap_or_default(),
+                id: release.tag_name,
+                author: release.author.map(|user| user.login).unwrap_or_default(),
+                time,
+            });
+        }
+        Ok(events)
+    }
+
+    fn get_merge(&self, opts: &MergeOption) -> Result<Option<String>> {
+        Ok(self.find_pull(opts)?.map(|pr| pr.html_url.to_string()))
+    }
+
+    fn get_merge_number(&self, opts: &MergeOption) -> Result<Option<u64>> {
+        Ok(self.find_pull(opts)?.map(|pr| pr.number))
     }
 
     fn create_merge(&self, opts: &MergeOption) -> Result<String> {
-        let pr = Self::pr_options(opts)?;
-        let pr = self.runtime.block_on(
+        let pr_opts = Self::pr_options(opts)?;
+
+        // Resolve milestone and project before creating the PR, so that a
+        // typo in them will not leave a half-configured PR behind.
+        let milestone = match &opts.milestone {
+            Some(title) => Some(self.get_milestone(&pr_opts.owner, &pr_opts.name, title)?),
+            None => None,
+        };
+        let project = match &opts.project {
+            Some(number) => Some(self.get_project_id(&pr_opts.owner, number)?),
+            None => None,
+        };
+
+        let pr = self.send(Method::POST, || async {
             self.instance
-                .pulls(&pr.owner, &pr.name)
-                .create(&opts.title, &pr.head, &opts.target)
+                .pulls(&pr_opts.owner, &pr_opts.name)
+                .create(&opts.title, &pr_opts.head, &opts.target)
                 .body(&opts.body)
-                .send(),
-        )?;
+                .send()
+                .await
+        })?;
+
+        if let Some(milestone) = milestone {
+            self.send(Method::PATCH, || async {
+                self.instance
+                    .issues(&pr_opts.owner, &pr_opts.name)
+                    .update(pr.number)
+                    .milestone(milestone)
+                    .send()
+                    .await
+            })
+            .context("unable to set milestone for pr")?;
+        }
+        if let Some(project_id) = project {
+            let node_id = match &pr.node_id {
+                Some(id) => id,
+                None => bail!("github didnot return node_id for pr"),
+            };
+            self.add_project_item(&project_id, node_id)?;
+        }
+
         match &pr.html_url {
             Some(url) => Ok(url.to_string()),
             None => bail!("github didnot return html_url for pr"),
         }
     }
 
+    fn list_my_merges(&self) -> Result<Vec<MergeItem>> {
+        self.search_pulls("is:pr is:open archived:false author:@me")
+    }
+
+    fn list_review_merges(&self) -> Result<Vec<MergeItem>> {
+        self.search_pulls("is:pr is:open archived:false review-requested:@me")
+    }
+
+    fn list_merges(&self, repo: &str) -> Result<Vec<MergeItem>> {
+        self.search_pulls(&format!("is:pr is:open repo:{}", repo))
+    }
+
+    fn approve_merge(&self, repo: &str, number: u64) -> Result<()> {
+        let (owner, name) = Self::parse_repo_name(repo)?;
+        let url = format!("/repos/{}/{}/pulls/{}/reviews", owner, name, number);
+        let body = json!({ "event": "APPROVE" });
+        let _: Value = self
+            .send(Method::POST, || self.instance.post(&url, Some(&body)))
+            .context("unable to approve pull request in github")?;
+        Ok(())
+    }
+
+    fn comment_merge(&self, repo: &str, number: u64, body: &str) -> Result<()> {
+        // The comments of PR are issue comments.
+        let (owner, name) = Self::parse_repo_name(repo)?;
+        self.send(Method::POST, || async {
+            self.instance
+                .issues(&owner, &name)
+                .create_comment(number, body)
+                .await
+        })
+        .context("unable to comment pull request in github")?;
+        Ok(())
+    }
+
+    fn list_inbox(&self) -> Result<Vec<InboxItem>> {
+        // Only the unread notifications are returned by default.
+        let notifications: Vec<models::activity::Notification> = self
+            .send(Method::GET, || {
+                self.instance.get("/notifications", Some(&self.query_opt))
+            })
+            .context("unable to list notifications in github")?;
+        let mut items = Vec::with_capacity(notifications.len());
+        for notification in notifications {
+            let repo = match notification.repository.full_name {
+                Some(name) => name,
+                None => notification.repository.name,
+            };
+            items.push(InboxItem {
+                id: notification.id.to_string(),
+                url: Self::subject_url(
+                    notification
+                        .repository
+                        .html_url
+                        .as_ref()
+                        .map(|url| url.as_str()),
+                    &notification.subject,
+                ),
+                repo,
+                kind: notification.subject.r#type,
+                title: notification.subject.title,
+                reason: notification.reason,
+                updated: notification.updated_at.timestamp().max(0) as u64,
+            });
+        }
+        Ok(items)
+    }
+
+    fn mark_inbox_read(&self, id: &str) -> Result<()> {
+        let id: u64 = match id.parse() {
+            Ok(id) => id,
+            Err(_) => bail!("invalid github notification id {}", style(id).yellow()),
+        };
+        self.send(Method::PATCH, || async {
+            self.instance
+                .activity()
+                .notifications()
+                .mark_as_read(id.into())
+                .await
+        })
+        .context("unable to mark notification as read in github")?;
+        Ok(())
+    }
+
+    fn list_issues(&self, repo: &str) -> Result<Vec<IssueItem>> {
+        let (owner, name) = Self::parse_repo_name(repo)?;
+        let page = self
+            .send(Method::GET, || async {
+                self.instance
+                    .issues(&owner, &name)
+                    .list()
+                    .state(octocrab::params::State::Open)
+                    .per_page(100)
+                    .send()
+                    .await
+            })
+            .context("unable to list issues in github")?;
+        // The PRs are issues too in github, skip them.
+        Ok(page
+            .items
+            .into_iter()
+            .filter(|issue| issue.pull_request.is_none())
+            .map(|issue| IssueItem {
+                number: issue.number,
+                title: issue.title,
+                author: issue.user.login,
+                labels: issue.labels.into_iter().map(|label| label.name).collect(),
+                created: issue.created_at.timestamp().max(0) as u64,
+            })
+            .collect())
+    }
+
+    fn get_issue(&self, repo: &str, number: u64) -> Result<String> {
+        let (owner, name) = Self::parse_repo_name(repo)?;
+        let issue = self
+            .send(Method::GET, || async {
+                self.instance.issues(&owner, &name).get(number).await
+            })
+            .context("unable to get issue in github")?;
+        Ok(issue.html_url.to_string())
+    }
+
+    fn create_issue(&self, repo: &str, title: &str, body: &str) -> Result<String> {
+        let (owner, name) = Self::parse_repo_name(repo)?;
+        let issue = self
+            .send(Method::POST, || async {
+                self.instance
+                    .issues(&owner, &name)
+                    .create(title)
+                    .body(body)
+                    .send()
+                    .await
+            })
+            .context("unable to create issue in github")?;
+        Ok(issue.html_url.to_string())
+    }
+
+    fn get_merge_ref(&self, _repo: &str, number: u64) -> Result<String> {
+        Ok(format!("refs/pull/{}/head", number))
+    }
+
+    fn get_merge_source(&self, repo: &str, number: u64) -> Result<Option<MergeSource>> {
+        let (owner, name) = Self::parse_repo_name(repo)?;
+        let pr = self
+            .send(Method::GET, || async {
+                self.instance.pulls(&owner, &name).get(number).await
+            })
+            .context("unable to get pull request in github")?;
+        // The repo is missing if the fork was deleted.
+        let source_repo = match pr.head.repo.and_then(|repo| repo.full_name) {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        Ok(Some(MergeSource {
+            repo: source_repo,
+            branch: pr.head.ref_field,
+        }))
+    }
+
     fn get_repo_url(
         &self,
         name: &str,
         branch: Option<String>,
         _remote: &crate::config::Remote,
     ) -> Result<String> {
-        api::get_repo_url("github.com", name, branch)
+        let mut url = format!("{}/{}", self.web_url, name);
+        if let Some(branch) = branch {
+            url = format!("{}/tree/{}", url, branch);
+        }
+        Ok(url)
+    }
+
+    fn get_compare_url(
+        &self,
+        repo: &str,
+        base: &str,
+        head: &str,
+        _remote: &crate::config::Remote,
+    ) -> Result<String> {
+        Ok(format!(
+            "{}/{}/compare/{}...{}",
+            self.web_url, repo, base, head
+        ))
     }
 }
 
@@ -171,6 +591,165 @@ impl Github {
         })
     }
 
+    /// Search the open PR of the merge, PR is a kind of issue in github.
+    fn find_pull(&self, opts: &MergeOption) -> Result<Option<models::issues::Issue>> {
+        let pr = Self::pr_options(opts)?;
+
+        let query = match &opts.upstream {
+            Some(upstream) => format!(
+                "is:open is:pr author:{} head:{} base:{} repo:{}",
+                pr.head_owner, opts.source, opts.target, upstream
+            ),
+            None => format!(
+                "is:open is:pr head:{} base:{} repo:{}",
+                opts.source, opts.target, opts.repo
+            ),
+        };
+        let url = format!("/search/issues?q={}", query_escape(&query));
+        let result: GithubSearch<models::issues::Issue> = self.get_cached(&url)?;
+        Ok(result.items.into_iter().next())
+    }
+
+    /// Send a GET request with the validators of the cached response, the
+    /// cached response is used if github replies "not modified", which does
+    /// not count against the rate limit.
+    fn get_cached<R: DeserializeOwned>(&self, url: &str) -> Result<R> {
+        let mut cache = self.cache.borrow_mut();
+        let cache = match cache.as_mut() {
+            Some(cache) => cache,
+            None => cache.insert(HttpCache::open()?),
+        };
+        let key = format!("{}{}", self.web_url, url);
+
+        let mut headers = HeaderMap::new();
+        if let Some(entry) = cache.data.get(&key) {
+            if let Ok(etag) = HeaderValue::from_str(&entry.etag) {
+                headers.insert(header::IF_NONE_MATCH, etag);
+            }
+            if let Ok(modified) = HeaderValue::from_str(&entry.last_modified) {
+                headers.insert(header::IF_MODIFIED_SINCE, modified);
+            }
+        }
+        let fetched = self.send(Method::GET, || async {
+            let resp = self
+                .instance
+                ._get_with_headers(url, Some(headers.clone()))
+                .await?;
+            if resp.status() == StatusCode::NOT_MODIFIED {
+                return Ok(None);
+            }
+            let resp = octocrab::map_github_error(resp).await?;
+            let get_header = |name| {
+                resp.headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let etag = get_header(header::ETAG);
+            let last_modified = get_header(header::LAST_MODIFIED);
+            let body = self.instance.body_to_string(resp).await?;
+            Ok::<_, octocrab::Error>(Some((etag, last_modified, body)))
+        })?;
+
+        let now = util::current_time()?;
+        let entry = match fetched {
+            Some((etag, last_modified, body)) => HttpEntry {
+                etag,
+                last_modified,
+                body,
+                updated: now,
+            },
+            None => match cache.data.remove(&key) {
+                Some(entry) => HttpEntry {
+                    updated: now,
+                    ..entry
+                },
+                None => bail!("github replied not modified for uncached request"),
+            },
+        };
+        let value = serde_json::from_str(&entry.body).context("could not parse github response");
+        // The response without validators can not be revalidated.
+        if !entry.etag.is_empty() || !entry.last_modified.is_empty() {
+            cache.data.insert(key, entry);
+            cache.save()?;
+        }
+        value
+    }
+
+    /// Send the request in runtime with the retry policy. The `request` is
+    /// called to build the request again for every attempt.
+    fn send<T, F, R>(&self, method: Method, request: F) -> Result<T>
+    where
+        F: Fn() -> R,
+        R: Future<Output = octocrab::Result<T>>,
+    {
+        self.retry.run(&method, || {
+            let result = self.runtime.block_on(request());
+            let retryable = match &result {
+                Ok(_) => None,
+                Err(err) => self.retryable(err),
+            };
+            (result.map_err(Into::into), retryable)
+        })
+    }
+
+    fn retryable(&self, err: &octocrab::Error) -> Option<Retryable> {
+        match err {
+            // Github reports the rate limit in message, the reset time can be
+            // got from the rate limit api, which does not count against it.
+            octocrab::Error::GitHub { source, .. } if source.message.contains("rate limit") => {
+                Some(Retryable::RateLimited(self.rate_limit_reset()))
+            }
+            // The error page replied by proxy (such as 502) is not JSON.
+            octocrab::Error::Serde { .. } | octocrab::Error::Hyper { .. } => {
+                Some(Retryable::Transient)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the latest reset time of the used up rate limits, None means the
+    /// secondary rate limit is exceeded, which does not report reset time.
+    fn rate_limit_reset(&self) -> Option<Epoch> {
+        let limit = self
+            .runtime
+            .block_on(self.instance.ratelimit().get())
+            .ok()?;
+        let resources = limit.resources;
+        [
+            Some(resources.core),
+            Some(resources.search),
+            resources.graphql,
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|rate| rate.remaining == 0)
+        .map(|rate| rate.reset as Epoch)
+        .max()
+    }
+
+    /// The subject url of notification is an api url, convert it to the web
+    /// url. Fallback to the repo page if the subject has no number.
+    fn subject_url(html_url: Option<&str>, subject: &models::activity::Subject) -> String {
+        let html_url = match html_url {
+            Some(url) => url.trim_end_matches('/'),
+            None => return String::new(),
+        };
+        let number = subject
+            .url
+            .as_ref()
+            .and_then(|url| url.path_segments())
+            .and_then(|mut segments| segments.next_back())
+            .and_then(|last| last.parse::<u64>().ok());
+        match (subject.r#type.as_str(), number) {
+            ("PullRequest", Some(number)) => format!("{}/pull/{}", html_url, number),
+            ("Issue", Some(number)) => format!("{}/issues/{}", html_url, number),
+            ("Release", _) => format!("{}/releases", html_url),
+            _ => html_url.to_string(),
+        }
+    }
+
     fn parse_repo_name(repo: &str) -> Result<(String, String)> {
         let (owner, name) = util::split_name(repo);
         if owner.is_empty() || name.is_empty() {
@@ -180,11 +759,132 @@ impl Github {
     }
 
     fn get_repo(&self, owner: &str, name: &str) -> Result<models::Repository> {
-        let repo = self
-            .runtime
-            .block_on(self.instance.repos(owner, name).get())
-            .context("unable to get repository from github")?;
-        Ok(repo)
+        let url = format!("/repos/{}/{}", owner, name);
+        self.get_cached(&url)
+            .context("unable to get repository from github")
+    }
+
+    fn get_milestone(&self, owner: &str, name: &str, title: &str) -> Result<u64> {
+        let url = format!("/repos/{}/{}/milestones", owner, name);
+        let milestones: Vec<models::Milestone> = self
+            .send(Method::GET, || {
+                self.instance.get(&url, Some(&self.query_opt))
+            })
+            .context("unable to list milestones from github")?;
+        match milestones.iter().find(|m| m.title == title) {
+            Some(milestone) => Ok(milestone.number as u64),
+            None => bail!("could not find milestone {}", style(title).yellow()),
+        }
+    }
+
+    fn get_project_id(&self, owner: &str, number: &str) -> Result<String> {
+        let number: u32 = number
+            .parse()
+            .with_context(|| format!("invalid github project number {}", style(number).yellow()))?;
+        let query = r#"query($owner: String!, $number: Int!) {
+            repositoryOwner(login: $owner) {
+                ... on ProjectV2Owner { projectV2(number: $number) { id } }
+            }
+        }"#;
+        let data = self.graphql(query, json!({ "owner": owner, "number": number }))?;
+        match data
+            .pointer("/repositoryOwner/projectV2/id")
+            .and_then(|id| id.as_str())
+        {
+            Some(id) => Ok(id.to_string()),
+            None => bail!(
+                "could not find project {} for {}",
+                style(number).yellow(),
+                style(owner).yellow()
+            ),
+        }
+    }
+
+    fn add_project_item(&self, project_id: &str, content_id: &str) -> Result<()> {
+        let query = r#"mutation($project: ID!, $content: ID!) {
+            addProjectV2ItemById(input: {projectId: $project, contentId: $content}) {
+                item { id }
+            }
+        }"#;
+        self.graphql(
+            query,
+            json!({ "project": project_id, "content": content_id }),
+        )
+        .context("unable to add pr to project")?;
+        Ok(())
+    }
+
+    /// Search PRs across repos, only the first 100 results are returned,
+    /// which should be enough for a personal queue.
+    fn search_pulls(&self, search: &str) -> Result<Vec<MergeItem>> {
+        let query = r#"query($search: String!) {
+            search(query: $search, type: ISSUE, first: 100) {
+                nodes {
+                    ... on PullRequest {
+                        number title url headRefName createdAt
+                        author { login }
+                        repository { nameWithOwner }
+                        commits(last: 1) { nodes { commit { statusCheckRollup { state } } } }
+                    }
+                }
+            }
+        }"#;
+        let data = self.graphql(query, json!({ "search": search }))?;
+        let nodes = match data.pointer("/search/nodes").and_then(|n| n.as_array()) {
+            Some(nodes) => nodes,
+            None => bail!("github graphql did not return search result"),
+        };
+        let mut items = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            let field = |pointer: &str| {
+                node.pointer(pointer)
+                    .and_then(|value| value.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            items.push(MergeItem {
+                repo: field("/repository/nameWithOwner"),
+                number: node
+                    .get("number")
+                    .and_then(|number| number.as_u64())
+                    .unwrap_or_default(),
+                title: field("/title"),
+                author: field("/author/login"),
+                source: field("/headRefName"),
+                url: field("/url"),
+                created: api::parse_time(&field("/createdAt"))?,
+                ci: match field("/commits/nodes/0/commit/statusCheckRollup/state").as_str() {
+                    "SUCCESS" => String::from("success"),
+                    "FAILURE" | "ERROR" => String::from("failure"),
+                    "PENDING" | "EXPECTED" => String::from("pending"),
+                    _ => String::new(),
+                },
+            });
+        }
+        Ok(items)
+    }
+
+    fn graphql(&self, query: &str, variables: Value) -> Result<Value> {
+        let body = json!({ "query": query, "variables": variables });
+        // The queries are sent by POST as well, but only the mutations change
+        // something, the queries are safe to retry as GET.
+        let method = match query.trim_start().starts_with("mutation") {
+            true => Method::POST,
+            false => Method::GET,
+        };
+        let resp: Value = self.send(method, || self.graphql.post("/graphql", Some(&body)))?;
+        // Github returns 200 for graphql errors, the errors are in the body.
+        if let Some(errors) = resp.get("errors").and_then(|e| e.as_array()) {
+            let msgs: Vec<&str> = errors
+                .iter()
+                .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+                .collect();
+            bail!("github graphql error: {}", msgs.join("; "))
+        }
+        match resp.get("data") {
+            Some(data) => Ok(data.clone()),
+            None => bail!("github graphql did not return data"),
+        }
     }
 
     fn must_get_upstream<'a>(repo: &'a models::Repository) -> Result<&'a str> {
@@ -208,3 +908,146 @@ impl Github {
         }
     }
 }
+
+/// The OAuth device flow of Github, the user enters the code in browser to
+/// authorize, and we poll for the token meanwhile.
+pub struct DeviceFlow {
+    client: reqwest::blocking::Client,
+
+    /// Such as "https://github.com".
+    web_url: String,
+    client_id: String,
+
+    pub user_code: String,
+    pub verification_uri: String,
+
+    device_code: String,
+    interval: u64,
+    expires: Epoch,
+}
+
+impl DeviceFlow {
+    /// The scopes required by the commands, such as creating repos, PRs and
+    /// reading notifications.
+    const SCOPES: &str = "repo read:org notifications project";
+
+    /// The "slow down" error requires to add 5 seconds to the interval.
+    const SLOW_DOWN_DELAY: u64 = 5;
+
+    /// The client id of our OAuth app on github.com, filled by the release
+    /// build, see `GZ_GITHUB_CLIENT_ID` in the release workflow.
+    const DEFAULT_CLIENT_ID: Option<&'static str> = option_env!("GZ_GITHUB_CLIENT_ID");
+
+    /// Get the client id shipped with the binary, only for github.com (the
+    /// `url` is empty), the Github Enterprise needs its own OAuth app.
+    pub fn default_client_id(url: &str) -> Option<&'static str> {
+        match url.is_empty() {
+            true => Self::DEFAULT_CLIENT_ID.filter(|id| !id.is_empty()),
+            false => None,
+        }
+    }
+
+    /// Request the device code, the `url` is the api url, empty means
+    /// github.com.
+    pub fn start(url: &str, client_id: &str) -> Result<DeviceFlow> {
+        let client = reqwest::blocking::Client::builder()
+            .user_agent(concat!("git-zoxide/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .context("unable to init github client")?;
+        let mut flow = DeviceFlow {
+            client,
+            web_url: Github::web_url(url),
+            client_id: client_id.to_string(),
+            user_code: String::new(),
+            verification_uri: String::new(),
+            device_code: String::new(),
+            interval: 0,
+            expires: 0,
+        };
+        let code: GithubDeviceCode = flow.post(
+            "/login/device/code",
+            &[("client_id", client_id), ("scope", Self::SCOPES)],
+        )?;
+        flow.user_code = code.user_code;
+        flow.verification_uri = code.verification_uri;
+        flow.device_code = code.device_code;
+        flow.interval = code.interval;
+        flow.expires = util::current_time()? + code.expires_in;
+        Ok(flow)
+    }
+
+    /// Poll for the token until the user authorizes, denies, or the code
+    /// expires.
+    pub fn wait(&mut self) -> Result<String> {
+        loop {
+            thread::sleep(Duration::from_secs(self.interval));
+            if util::current_time()? >= self.expires {
+                bail!("the device code expired, please login again")
+            }
+            let token: GithubAccessToken = self.post(
+                "/login/oauth/access_token",
+                &[
+                    ("client_id", self.client_id.as_str()),
+                    ("device_code", self.device_code.as_str()),
+                    ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ],
+            )?;
+            if let Some(token) = token.access_token {
+                return Ok(token);
+            }
+            match token.error.as_deref() {
+                Some("authorization_pending") => {}
+                Some("slow_down") => {
+                    self.interval = token
+                        .interval
+                        .unwrap_or(self.interval + Self::SLOW_DOWN_DELAY);
+                }
+                Some("expired_token") => bail!("the device code expired, please login again"),
+                Some("access_denied") => bail!("the authorization is denied"),
+                Some(err) => bail!(
+                    "github login failed: {}",
+                    token.error_description.as_deref().unwrap_or(err)
+                ),
+                None => bail!("github did not return access token"),
+            }
+        }
+    }
+
+    /// The OAuth endpoints reply form data by default, ask for JSON.
+    fn post<R: DeserializeOwned>(&self, path: &str, form: &[(&str, &str)]) -> Result<R> {
+        let url = format!("{}{}", self.web_url, path);
+        let resp = self
+            .client
+            .post(&url)
+            .header(header::ACCEPT, "application/json")
+            .form(form)
+            .send()
+            .with_context(|| format!("unable to send request to {}", style(&url).yellow()))?;
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().unwrap_or_default();
+            bail!(
+                "github returned {} for {}: {}",
+                status,
+                style(&url).yellow(),
+                text.trim()
+            )
+        }
+        resp.json()
+            .with_context(|| format!("unable to parse response of {}", style(&url).yellow()))
+    }
+}
+
+/// Escape the query parameter, only the unreserved characters are kept.
+fn query_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                escaped.push(b as char)
+            }
+            b => escaped.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    escaped
+}
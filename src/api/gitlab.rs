@@ -1,20 +1,35 @@
 use anyhow::{bail, Context, Result};
+use console::style;
+use reqwest::StatusCode;
+use serde::Deserialize;
 
 // Gitlab api
 use gitlab::api;
+use gitlab::api::common::{NameOrId, VisibilityLevel};
+use gitlab::api::endpoint_prelude::{Cow, Endpoint, Method, QueryParams};
 use gitlab::api::groups::projects::GroupProjects;
-use gitlab::api::projects::merge_requests::{CreateMergeRequest, MergeRequestState, MergeRequests};
-use gitlab::api::projects::Project;
-use gitlab::api::{Pagination, Query};
+use gitlab::api::projects::issues::{CreateIssue, Issue, IssueState, Issues};
+use gitlab::api::projects::merge_requests::notes::CreateMergeRequestNote;
+use gitlab::api::projects::merge_requests::{
+    ApproveMergeRequest, CreateMergeRequest, MergeRequest, MergeRequestState, MergeRequests,
+};
+use gitlab::api::projects::releases::ProjectReleases;
+use gitlab::api::projects::repository::commits::Commits;
+use gitlab::api::projects::{CreateProject, Project};
+use gitlab::api::users::CurrentUser;
+use gitlab::api::{ApiError, Pageable, Pagination, Query};
 
 // Gitlab models
 use gitlab::types;
+use gitlab::RestError;
 
-use crate::api::Provider;
-use crate::errors;
+use crate::api::{InboxItem, IssueItem, MergeItem, MergeSource, Provider, Retry, Retryable};
+use crate::db::{ActivityEvent, ActivityKind, Epoch, RepoMeta};
+use crate::{errors, util};
 
 pub struct Gitlab {
     client: gitlab::Gitlab,
+    retry: Retry,
 }
 
 impl Provider for Gitlab {
@@ -23,8 +38,8 @@ impl Provider for Gitlab {
             .group(group)
             .build()
             .context("unable to build gitlab group endpoint")?;
-        let projects: Vec<types::Project> = api::paged(endpoint, Pagination::All)
-            .query(&self.client)
+        let projects: Vec<types::Project> = self
+            .send(Method::GET, &api::paged(endpoint, Pagination::All))
             .context("unable to query gitlab projects")?;
 
         let mut repos: Vec<String> = Vec::with_capacity(projects.len());
@@ -35,6 +50,41 @@ impl Provider for Gitlab {
         Ok(repos)
     }
 
+    fn create_repo(&self, repo: &str, private: bool) -> Result<()> {
+        let (group, base) = util::split_name(repo);
+        if group.is_empty() || base.is_empty() {
+            bail!("invalid gitlab repository name {}", style(repo).yellow())
+        }
+        let namespace: GitlabNamespace = self
+            .send(Method::GET, &GroupNamespace { group: &group })
+            .with_context(|| {
+                format!("unable to get gitlab namespace {}", style(&group).yellow())
+            })?;
+        let visibility = match private {
+            true => VisibilityLevel::Private,
+            false => VisibilityLevel::Public,
+        };
+        let endpoint = CreateProject::builder()
+            .path(base.as_str())
+            .namespace_id(namespace.id)
+            .visibility(visibility)
+            .build()
+            .context("unable to build gitlab create_project endpoint")?;
+        let _: types::Project = self
+            .send(Method::POST, &endpoint)
+            .context("unable to create gitlab project")?;
+        Ok(())
+    }
+
+    fn delete_repo(&self, repo: &str) -> Result<()> {
+        self.send(
+            Method::DELETE,
+            &api::ignore(DeleteProject { project: repo }),
+        )
+        .context("unable to delete gitlab project")?;
+        Ok(())
+    }
+
     fn get_default_branch(&self, repo: &str) -> Result<String> {
         let project = self.get_project(repo)?;
         match project.default_branch {
@@ -51,44 +101,265 @@ impl Provider for Gitlab {
         }
     }
 
-    fn get_merge(&self, opts: &super::MergeOption) -> Result<Option<String>> {
-        if let Some(_) = opts.upstream {
-            bail!("sorry, gitlab now does not support upstream features")
+    fn get_meta(&self, repo: &str) -> Result<RepoMeta> {
+        let project = self.get_project(repo)?;
+        Ok(RepoMeta {
+            description: project.description.unwrap_or_default(),
+            stars: project.star_count,
+            archived: project.archived,
+            topics: project.tag_list,
+            updated: 0,
+        })
+    }
+
+    fn list_activity(&self, repo: &str, since: Epoch) -> Result<Vec<ActivityEvent>> {
+        // The commits of default branch are listed if no ref is given.
+        let endpoint = Commits::builder()
+            .project(repo)
+            .since(super::to_datetime(since))
+            .build()
+            .context("unable to build gitlab commits endpoint")?;
+        let commits: Vec<types::RepoCommit> = self
+            .send(Method::GET, &api::paged(endpoint, Pagination::Limit(100)))
+            .context("unable to query commits")?;
+        let mut events = Vec::with_capacity(commits.len());
+        for commit in commits {
+            events.push(super::commit_activity(
+                commit.id.value(),
+                &commit.message,
+                commit.author_name,
+                commit.committed_date.timestamp().max(0) as Epoch,
+            ));
         }
-        let endpoint = MergeRequests::builder()
-            .state(MergeRequestState::Opened)
-            .project(opts.repo.as_str())
-            .target_branch(&opts.target)
-            .source_branch(&opts.source)
+
+        // The releases are sorted by release time, the latest first.
+        let endpoint = ProjectReleases::builder()
+            .project(repo)
             .build()
-            .context("unable to build gitlab merge_requests endpoint")?;
-        let mrs: Vec<types::MergeRequest> = endpoint
-            .query(&self.client)
-            .context("unable to query merge_request")?;
-        if mrs.is_empty() {
-            return Ok(None);
+            .context("unable to build gitlab releases endpoint")?;
+        let releases: Vec<GitlabRelease> = self
+            .send(Method::GET, &api::paged(endpoint, Pagination::Limit(20)))
+            .context("unable to query releases")?;
+        for release in releases {
+            let time = super::parse_time(&release.released_at)?;
+            if time < since {
+                break;
+            }
+            events.push(ActivityEvent {
+                kind: ActivityKind::Release,
+                id: release.tag_name,
+                title: release.name,
+                author: release
+                    .author
+                    .map(|author| author.username)
+                    .unwrap_or_default(),
+                time,
+            });
         }
-        Ok(Some(mrs[0].web_url.clone()))
+        Ok(events)
+    }
+
+    fn get_merge(&self, opts: &super::MergeOption) -> Result<Option<String>> {
+        Ok(self.find_merge(opts)?.map(|mr| mr.web_url))
+    }
+
+    fn get_merge_number(&self, opts: &super::MergeOption) -> Result<Option<u64>> {
+        Ok(self.find_merge(opts)?.map(|mr| mr.iid.value()))
     }
 
     fn create_merge(&self, opts: &super::MergeOption) -> Result<String> {
-        if let Some(_) = opts.upstream {
-            bail!("sorry, gitlab now does not support upstream features")
+        if opts.project.is_some() {
+            bail!("sorry, gitlab does not support adding merge request to project")
         }
-        let endpoint = CreateMergeRequest::builder()
+        let mut builder = CreateMergeRequest::builder();
+        builder
             .project(opts.repo.as_str())
             .title(&opts.title)
             .source_branch(&opts.source)
-            .target_branch(&opts.target)
+            .target_branch(&opts.target);
+        let target_repo = match &opts.upstream {
+            Some(upstream) => {
+                let target = self.get_project(upstream)?;
+                builder.target_project_id(target.id.value());
+                upstream.as_str()
+            }
+            None => opts.repo.as_str(),
+        };
+        if let Some(title) = &opts.milestone {
+            builder.milestone_id(self.get_milestone(target_repo, title)?);
+        }
+        let endpoint = builder
             .build()
             .context("unable to build create_merge_request endpoint")?;
-        let mr: types::MergeRequest = endpoint
-            .query(&self.client)
+        let mr: types::MergeRequest = self
+            .send(Method::POST, &endpoint)
             .context("unable to create merge_request")?;
 
         Ok(mr.web_url)
     }
 
+    fn list_my_merges(&self) -> Result<Vec<MergeItem>> {
+        self.list_user_merges("created_by_me", None)
+    }
+
+    fn list_review_merges(&self) -> Result<Vec<MergeItem>> {
+        let endpoint = CurrentUser::builder()
+            .build()
+            .context("unable to build gitlab user endpoint")?;
+        let user: types::UserPublic = self
+            .send(Method::GET, &endpoint)
+            .context("unable to get current user")?;
+        self.list_user_merges("all", Some(&user.username))
+    }
+
+    fn list_merges(&self, repo: &str) -> Result<Vec<MergeItem>> {
+        let endpoint = MergeRequests::builder()
+            .state(MergeRequestState::Opened)
+            .project(repo)
+            .build()
+            .context("unable to build gitlab merge_requests endpoint")?;
+        let merges: Vec<GitlabMerge> = self
+            .send(Method::GET, &api::paged(endpoint, Pagination::All))
+            .context("unable to query merge requests")?;
+        Self::merge_items(merges)
+    }
+
+    fn approve_merge(&self, repo: &str, number: u64) -> Result<()> {
+        let endpoint = ApproveMergeRequest::builder()
+            .project(repo)
+            .merge_request(number)
+            .build()
+            .context("unable to build gitlab approve endpoint")?;
+        self.send(Method::POST, &api::ignore(endpoint))
+            .context("unable to approve merge request")?;
+        Ok(())
+    }
+
+    fn comment_merge(&self, repo: &str, number: u64, body: &str) -> Result<()> {
+        let endpoint = CreateMergeRequestNote::builder()
+            .project(repo)
+            .merge_request(number)
+            .body(body)
+            .build()
+            .context("unable to build gitlab note endpoint")?;
+        self.send(Method::POST, &api::ignore(endpoint))
+            .context("unable to comment merge request")?;
+        Ok(())
+    }
+
+    fn list_inbox(&self) -> Result<Vec<InboxItem>> {
+        // The notifications in Gitlab are the pending todos.
+        let todos: Vec<GitlabTodo> = self
+            .send(Method::GET, &api::paged(PendingTodos, Pagination::All))
+            .context("unable to query todos")?;
+        let mut items = Vec::with_capacity(todos.len());
+        for todo in todos {
+            let repo = match todo.project {
+                Some(project) => project.path_with_namespace,
+                None => String::new(),
+            };
+            let title = match todo.target.and_then(|target| target.title) {
+                Some(title) => title,
+                None => todo.body,
+            };
+            items.push(InboxItem {
+                id: todo.id.to_string(),
+                repo,
+                kind: todo.target_type,
+                title,
+                reason: todo.action_name,
+                url: todo.target_url,
+                updated: super::parse_time(&todo.created_at)?,
+            });
+        }
+        Ok(items)
+    }
+
+    fn mark_inbox_read(&self, id: &str) -> Result<()> {
+        self.send(Method::POST, &api::ignore(MarkTodoDone { id }))
+            .context("unable to mark todo as done")?;
+        Ok(())
+    }
+
+    fn list_issues(&self, repo: &str) -> Result<Vec<IssueItem>> {
+        let endpoint = Issues::builder()
+            .project(repo)
+            .state(IssueState::Opened)
+            .build()
+            .context("unable to build gitlab issues endpoint")?;
+        let issues: Vec<GitlabIssue> = self
+            .send(Method::GET, &api::paged(endpoint, Pagination::All))
+            .context("unable to query issues")?;
+        let mut items = Vec::with_capacity(issues.len());
+        for issue in issues {
+            items.push(IssueItem {
+                number: issue.iid,
+                title: issue.title,
+                author: issue.author.username,
+                labels: issue.labels,
+                created: super::parse_time(&issue.created_at)?,
+            });
+        }
+        Ok(items)
+    }
+
+    fn get_issue(&self, repo: &str, number: u64) -> Result<String> {
+        let endpoint = Issue::builder()
+            .project(repo)
+            .issue(number)
+            .build()
+            .context("unable to build gitlab issue endpoint")?;
+        let issue: GitlabIssue = self
+            .send(Method::GET, &endpoint)
+            .context("unable to query issue")?;
+        Ok(issue.web_url)
+    }
+
+    fn create_issue(&self, repo: &str, title: &str, body: &str) -> Result<String> {
+        let endpoint = CreateIssue::builder()
+            .project(repo)
+            .title(title)
+            .description(body)
+            .build()
+            .context("unable to build gitlab create issue endpoint")?;
+        let issue: GitlabIssue = self
+            .send(Method::POST, &endpoint)
+            .context("unable to create issue")?;
+        Ok(issue.web_url)
+    }
+
+    fn get_merge_ref(&self, _repo: &str, number: u64) -> Result<String> {
+        Ok(format!("refs/merge-requests/{}/head", number))
+    }
+
+    fn get_merge_source(&self, repo: &str, number: u64) -> Result<Option<MergeSource>> {
+        let endpoint = MergeRequest::builder()
+            .project(repo)
+            .merge_request(number)
+            .build()
+            .context("unable to build gitlab merge_request endpoint")?;
+        let merge: GitlabMerge = self
+            .send(Method::GET, &endpoint)
+            .context("unable to query merge_request")?;
+        let source_repo = match merge.source_project_id == merge.target_project_id {
+            true => repo.to_string(),
+            false => {
+                let endpoint = Project::builder()
+                    .project(merge.source_project_id)
+                    .build()
+                    .context("unable to build gitlab project endpoint")?;
+                let project: types::Project = self
+                    .send(Method::GET, &endpoint)
+                    .context("unable to get source project")?;
+                project.path_with_namespace
+            }
+        };
+        Ok(Some(MergeSource {
+            repo: source_repo,
+            branch: merge.source_branch,
+        }))
+    }
+
     fn get_repo_url(
         &self,
         name: &str,
@@ -101,10 +372,32 @@ impl Provider for Gitlab {
         let clone = remote.clone.as_ref().unwrap();
         crate::api::get_repo_url(&clone.domain, name, branch)
     }
+
+    fn get_compare_url(
+        &self,
+        repo: &str,
+        base: &str,
+        head: &str,
+        remote: &crate::config::Remote,
+    ) -> Result<String> {
+        if head.contains(':') {
+            bail!("sorry, gitlab now does not support comparing across forks")
+        }
+        let clone = match &remote.clone {
+            Some(clone) => clone,
+            None => bail!(
+                "you must provide clone config to get gitlab compare url, please check your config"
+            ),
+        };
+        Ok(format!(
+            "https://{}/{}/-/compare/{}...{}",
+            clone.domain, repo, base, head
+        ))
+    }
 }
 
 impl Gitlab {
-    pub fn new<U, T>(url: U, token: T) -> Result<Box<dyn Provider>>
+    pub fn new<U, T>(url: U, token: T, retry: u32) -> Result<Box<dyn Provider>>
     where
         U: AsRef<str>,
         T: AsRef<str>,
@@ -114,7 +407,43 @@ impl Gitlab {
         }
         let client = gitlab::Gitlab::new(url.as_ref(), token.as_ref())
             .context("unable to init gitlab client")?;
-        Ok(Box::new(Gitlab { client }))
+        Ok(Box::new(Gitlab {
+            client,
+            retry: Retry::new("gitlab", retry),
+        }))
+    }
+
+    /// Send the query with the retry policy, the `method` of query decides
+    /// whether it is safe to retry after server error.
+    fn send<T, Q>(&self, method: Method, query: &Q) -> Result<T>
+    where
+        Q: Query<T, gitlab::Gitlab>,
+    {
+        self.retry.run(&method, || {
+            let result = query.query(&self.client);
+            let retryable = match &result {
+                Ok(_) => None,
+                Err(err) => Self::retryable(err),
+            };
+            (result.map_err(Into::into), retryable)
+        })
+    }
+
+    /// The error page (such as 429 from rate limiter, or 502 from proxy) is
+    /// not JSON, gitlab client reports it as service error with the status.
+    fn retryable(err: &ApiError<RestError>) -> Option<Retryable> {
+        match err {
+            ApiError::GitlabService { status, .. } if *status == StatusCode::TOO_MANY_REQUESTS => {
+                Some(Retryable::RateLimited(None))
+            }
+            ApiError::GitlabService { status, .. } if status.is_server_error() => {
+                Some(Retryable::Transient)
+            }
+            ApiError::Client {
+                source: RestError::Communication { .. },
+            } => Some(Retryable::Transient),
+            _ => None,
+        }
     }
 
     fn get_project(&self, name: impl AsRef<str>) -> Result<types::Project> {
@@ -122,9 +451,272 @@ impl Gitlab {
             .project(name.as_ref())
             .build()
             .context("unable to build gitlab project endpoint")?;
-        let project = endpoint
-            .query(&self.client)
+        let project = self
+            .send(Method::GET, &endpoint)
             .context("unable to get project")?;
         Ok(project)
     }
+
+    fn find_merge(&self, opts: &super::MergeOption) -> Result<Option<types::MergeRequest>> {
+        // For upstream merge, the merge request lives in the target project,
+        // filter it by the source project.
+        let (project, source_project) = match &opts.upstream {
+            Some(upstream) => (upstream.as_str(), Some(self.get_project(&opts.repo)?.id)),
+            None => (opts.repo.as_str(), None),
+        };
+        let endpoint = MergeRequests::builder()
+            .state(MergeRequestState::Opened)
+            .project(project)
+            .target_branch(&opts.target)
+            .source_branch(&opts.source)
+            .build()
+            .context("unable to build gitlab merge_requests endpoint")?;
+        let mrs: Vec<types::MergeRequest> = self
+            .send(Method::GET, &endpoint)
+            .context("unable to query merge_request")?;
+        let mr = mrs.into_iter().find(|mr| match source_project {
+            Some(id) => mr.source_project_id == Some(id),
+            None => true,
+        });
+        Ok(mr)
+    }
+
+    fn list_user_merges(&self, scope: &str, reviewer: Option<&str>) -> Result<Vec<MergeItem>> {
+        let endpoint = UserMergeRequests { scope, reviewer };
+        let merges: Vec<GitlabMerge> = self
+            .send(Method::GET, &api::paged(endpoint, Pagination::All))
+            .context("unable to query merge requests")?;
+        Self::merge_items(merges)
+    }
+
+    fn merge_items(merges: Vec<GitlabMerge>) -> Result<Vec<MergeItem>> {
+        let mut items = Vec::with_capacity(merges.len());
+        for merge in merges {
+            // The full reference is in format "<project>!<iid>".
+            let repo = match merge.references.full.rsplit_once('!') {
+                Some((repo, _)) => repo.to_string(),
+                None => merge.references.full,
+            };
+            items.push(MergeItem {
+                repo,
+                number: merge.iid,
+                title: merge.title,
+                author: merge.author.username,
+                source: merge.source_branch,
+                url: merge.web_url,
+                created: super::parse_time(&merge.created_at)?,
+                // The pipeline is not returned when listing merge requests.
+                ci: String::new(),
+            });
+        }
+        Ok(items)
+    }
+
+    fn get_milestone(&self, repo: &str, title: &str) -> Result<u64> {
+        let endpoint = ProjectMilestones {
+            project: repo,
+            title,
+        };
+        let milestones: Vec<types::Milestone> = self
+            .send(Method::GET, &endpoint)
+            .context("unable to query milestones")?;
+        match milestones.into_iter().find(|m| m.title == title) {
+            Some(milestone) => Ok(milestone.id.value()),
+            None => bail!("could not find milestone {}", style(title).yellow()),
+        }
+    }
+}
+
+/// The gitlab crate does not provide endpoint to list milestones, this is
+/// a minimal implementation for searching milestone by title.
+struct ProjectMilestones<'a> {
+    project: &'a str,
+    title: &'a str,
+}
+
+impl<'a> Endpoint for ProjectMilestones<'a> {
+    fn method(&self) -> Method {
+        Method::GET
+    }
+
+    fn endpoint(&self) -> Cow<'static, str> {
+        let project = NameOrId::from(self.project);
+        format!("projects/{}/milestones", project).into()
+    }
+
+    fn parameters(&self) -> QueryParams<'_> {
+        let mut params = QueryParams::default();
+        params.push("title", self.title);
+        params
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct GitlabMerge {
+    iid: u64,
+    title: String,
+    web_url: String,
+    source_branch: String,
+    #[serde(default)]
+    source_project_id: u64,
+    #[serde(default)]
+    target_project_id: u64,
+    created_at: String,
+    references: GitlabReferences,
+    author: GitlabAuthor,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitlabIssue {
+    iid: u64,
+    title: String,
+    web_url: String,
+    created_at: String,
+    #[serde(default)]
+    labels: Vec<String>,
+    author: GitlabAuthor,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitlabReferences {
+    full: String,
+}
+
+/// The gitlab crate only provides endpoint to list merge requests of a
+/// project, this lists the open merge requests related to current user
+/// across projects.
+struct UserMergeRequests<'a> {
+    scope: &'a str,
+    reviewer: Option<&'a str>,
+}
+
+impl<'a> Endpoint for UserMergeRequests<'a> {
+    fn method(&self) -> Method {
+        Method::GET
+    }
+
+    fn endpoint(&self) -> Cow<'static, str> {
+        "merge_requests".into()
+    }
+
+    fn parameters(&self) -> QueryParams<'_> {
+        let mut params = QueryParams::default();
+        params.push("scope", self.scope);
+        params.push("state", "opened");
+        params.push_opt("reviewer_username", self.reviewer);
+        params
+    }
+}
+
+impl<'a> Pageable for UserMergeRequests<'a> {}
+
+#[derive(Deserialize, Debug)]
+struct GitlabRelease {
+    tag_name: String,
+    #[serde(default)]
+    name: String,
+    released_at: String,
+    author: Option<GitlabAuthor>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitlabAuthor {
+    username: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitlabTodo {
+    id: u64,
+    project: Option<GitlabTodoProject>,
+    action_name: String,
+    target_type: String,
+    target: Option<GitlabTodoTarget>,
+    target_url: String,
+    #[serde(default)]
+    body: String,
+    created_at: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitlabTodoProject {
+    path_with_namespace: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitlabTodoTarget {
+    title: Option<String>,
+}
+
+/// The gitlab crate does not provide endpoints for todos, this lists the
+/// pending todos of current user.
+struct PendingTodos;
+
+impl Endpoint for PendingTodos {
+    fn method(&self) -> Method {
+        Method::GET
+    }
+
+    fn endpoint(&self) -> Cow<'static, str> {
+        "todos".into()
+    }
+
+    fn parameters(&self) -> QueryParams<'_> {
+        let mut params = QueryParams::default();
+        params.push("state", "pending");
+        params
+    }
+}
+
+impl Pageable for PendingTodos {}
+
+struct MarkTodoDone<'a> {
+    id: &'a str,
+}
+
+impl<'a> Endpoint for MarkTodoDone<'a> {
+    fn method(&self) -> Method {
+        Method::POST
+    }
+
+    fn endpoint(&self) -> Cow<'static, str> {
+        format!("todos/{}/mark_as_done", self.id).into()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct GitlabNamespace {
+    id: u64,
+}
+
+/// The namespace can be a group or a user, this is used to find where to
+/// create the project.
+struct GroupNamespace<'a> {
+    group: &'a str,
+}
+
+impl<'a> Endpoint for GroupNamespace<'a> {
+    fn method(&self) -> Method {
+        Method::GET
+    }
+
+    fn endpoint(&self) -> Cow<'static, str> {
+        let group = NameOrId::from(self.group);
+        format!("namespaces/{}", group).into()
+    }
+}
+
+/// The gitlab crate does not provide endpoint to delete project.
+struct DeleteProject<'a> {
+    project: &'a str,
+}
+
+impl<'a> Endpoint for DeleteProject<'a> {
+    fn method(&self) -> Method {
+        Method::DELETE
+    }
+
+    fn endpoint(&self) -> Cow<'static, str> {
+        let project = NameOrId::from(self.project);
+        format!("projects/{}", project).into()
+    }
 }
@@ -1,12 +1,28 @@
+mod bitbucket;
+mod gerrit;
+mod gitea;
 mod github;
 mod gitlab;
+pub mod rest;
+mod sourcehut;
+mod template;
 
+pub use github::DeviceFlow;
+
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
 use std::{path::PathBuf, str::FromStr};
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, TimeZone, Utc};
 use console::style;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
 
 use crate::config::{self, Remote};
+use crate::db::{ActivityEvent, ActivityKind, Epoch, RepoMeta};
+use crate::util;
 
 pub struct MergeOption {
     pub repo: String,
@@ -17,6 +33,9 @@ pub struct MergeOption {
 
     pub source: String,
     pub target: String,
+
+    pub milestone: Option<String>,
+    pub project: Option<String>,
 }
 
 impl MergeOption {
@@ -47,10 +66,69 @@ impl MergeOption {
     }
 }
 
+/// A merge (PR/MR) listed across repos.
+pub struct MergeItem {
+    pub repo: String,
+    pub number: u64,
+    pub title: String,
+
+    /// The login (or name) of the author, empty if unknown.
+    pub author: String,
+
+    /// The source branch of the merge.
+    pub source: String,
+    pub url: String,
+    pub created: Epoch,
+
+    /// The CI status, "success", "failure", "pending", or empty if unknown.
+    pub ci: String,
+}
+
+/// The source of a merge.
+pub struct MergeSource {
+    /// The repo holding the source branch, the fork for a merge from fork.
+    pub repo: String,
+    pub branch: String,
+}
+
+/// An open issue of repo.
+pub struct IssueItem {
+    pub number: u64,
+    pub title: String,
+
+    /// The login (or name) of the author, empty if unknown.
+    pub author: String,
+    pub labels: Vec<String>,
+    pub created: Epoch,
+}
+
+/// An unread notification (todo for Gitlab) of current user.
+pub struct InboxItem {
+    /// The id used to mark the notification as read.
+    pub id: String,
+    pub repo: String,
+
+    /// The type of the subject, such as "PullRequest" or "Issue".
+    pub kind: String,
+    pub title: String,
+
+    /// Why the notification is sent, such as "mention" or "review_requested".
+    pub reason: String,
+    pub url: String,
+    pub updated: Epoch,
+}
+
 pub trait Provider {
     // list all repos for a group, the group can be owner or org in Github.
     fn list(&self, group: &str) -> Result<Vec<String>>;
 
+    // Create a repo in provider, the owner of the repo can be the current
+    // user or a group (organization) the user belongs to.
+    fn create_repo(&self, repo: &str, private: bool) -> Result<()>;
+
+    // Delete the repo in provider, this cannot be undone.
+    fn delete_repo(&self, repo: &str) -> Result<()>;
+
     // Get default branch name.
     fn get_default_branch(&self, repo: &str) -> Result<String>;
 
@@ -58,14 +136,91 @@ pub trait Provider {
     // `errors.REPO_NO_UPSTREAM` for no forked repo.
     fn get_upstream(&self, repo: &str) -> Result<String>;
 
+    // Get the metadata of repo, such as description and stars. The `updated`
+    // field is left to the caller.
+    fn get_meta(&self, repo: &str) -> Result<RepoMeta>;
+
+    // List the commits on default branch and the releases created since
+    // `since`.
+    fn list_activity(&self, repo: &str, since: Epoch) -> Result<Vec<ActivityEvent>>;
+
     // Try to get URL for merge request (or PR for Github). If merge request
     // not exists, return Ok(None).
     fn get_merge(&self, opts: &MergeOption) -> Result<Option<String>>;
+    // Try to get the number of the merge, the same as `get_merge`.
+    fn get_merge_number(&self, opts: &MergeOption) -> Result<Option<u64>>;
     // Create merge request (or PR for Github), and return its URL.
     fn create_merge(&self, opts: &MergeOption) -> Result<String>;
 
+    // List the open merges authored by current user, across all repos.
+    fn list_my_merges(&self) -> Result<Vec<MergeItem>>;
+    // List the open merges requesting review from current user.
+    fn list_review_merges(&self) -> Result<Vec<MergeItem>>;
+    // List the open merges targeting the repo.
+    fn list_merges(&self, repo: &str) -> Result<Vec<MergeItem>>;
+
+    // Approve the merge as current user.
+    fn approve_merge(&self, repo: &str, number: u64) -> Result<()>;
+    // Add a comment to the merge as current user.
+    fn comment_merge(&self, repo: &str, number: u64, body: &str) -> Result<()>;
+
+    // List the unread notifications of current user.
+    fn list_inbox(&self) -> Result<Vec<InboxItem>>;
+    // Mark the notification as read, the id comes from `list_inbox`.
+    fn mark_inbox_read(&self, id: &str) -> Result<()>;
+
+    // List the open issues of the repo.
+    fn list_issues(&self, _repo: &str) -> Result<Vec<IssueItem>> {
+        bail!("sorry, the provider does not support issues")
+    }
+    // Get the web url of the issue.
+    fn get_issue(&self, _repo: &str, _number: u64) -> Result<String> {
+        bail!("sorry, the provider does not support issues")
+    }
+    // Create an issue, and return its URL.
+    fn create_issue(&self, _repo: &str, _title: &str, _body: &str) -> Result<String> {
+        bail!("sorry, the provider does not support issues")
+    }
+
+    // Whether the title and body are required to create merge. Some
+    // providers (like Gerrit) take them from the commit message.
+    fn merge_require_input(&self) -> bool {
+        true
+    }
+
+    // Update the existing merge with the local commits, for the providers
+    // (like Gerrit) whose merge does not follow the source branch.
+    fn update_merge(&self, _opts: &MergeOption) -> Result<()> {
+        Ok(())
+    }
+
+    // Get the git ref holding the head of the merge, the ref can be fetched
+    // from the target repo even if the merge comes from a fork.
+    fn get_merge_ref(&self, repo: &str, number: u64) -> Result<String>;
+
+    // Get the source of the merge, `None` means the provider does not report
+    // it.
+    fn get_merge_source(&self, _repo: &str, _number: u64) -> Result<Option<MergeSource>> {
+        Ok(None)
+    }
+
+    // Get the size of repo in bytes, 0 means the provider does not report it.
+    fn get_size(&self, _repo: &str) -> Result<u64> {
+        Ok(0)
+    }
+
     // Get web url for repo.
     fn get_repo_url(&self, name: &str, branch: Option<String>, remote: &Remote) -> Result<String>;
+
+    // Get web url to compare head with base. The head can be in the form
+    // of "owner:branch" to compare across forks.
+    fn get_compare_url(
+        &self,
+        repo: &str,
+        base: &str,
+        head: &str,
+        remote: &Remote,
+    ) -> Result<String>;
 }
 
 pub fn create_provider(remote: &Remote) -> Result<Box<dyn Provider>> {
@@ -76,9 +231,159 @@ pub fn create_provider(remote: &Remote) -> Result<Box<dyn Provider>> {
         )
     }
     let api = remote.api.as_ref().unwrap();
+    let token = &remote.get_token()?;
     match api.provider {
-        config::Provider::Github => github::Github::new(&api.token),
-        config::Provider::Gitlab => gitlab::Gitlab::new(&api.url, &api.token),
+        config::Provider::Github => github::Github::new(&api.url, token, api.retry),
+        config::Provider::Gitlab => gitlab::Gitlab::new(&api.url, token, api.retry),
+        config::Provider::Gerrit => gerrit::Gerrit::new(&api.url, token, api.retry),
+        config::Provider::Codeberg => gitea::Gitea::codeberg(&api.url, token, api.retry),
+        config::Provider::Gitea => gitea::Gitea::new(&api.url, token, api.retry),
+        config::Provider::Sourcehut => sourcehut::Sourcehut::new(&api.url, token, api.retry),
+        config::Provider::Bitbucket => bitbucket::Bitbucket::new(&api.url, token, api.retry),
+        config::Provider::None => template::Template::new(remote, api),
+    }
+}
+
+/// Why a failed request is worth retrying.
+pub enum Retryable {
+    /// The rate limit is exceeded, the limit resets at the time if the
+    /// provider reports it.
+    RateLimited(Option<Epoch>),
+    /// The server error or network error, which may go away soon.
+    Transient,
+}
+
+impl Retryable {
+    /// Check the response for the reason to retry. Besides 429, Github and
+    /// Gitea reply 403 when the quota is used up.
+    pub fn from_response(status: StatusCode, headers: &HeaderMap) -> Option<Retryable> {
+        let header = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| headers.get(*name))
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<Epoch>().ok())
+        };
+        let exhausted = header(&["x-ratelimit-remaining", "ratelimit-remaining"]) == Some(0);
+        if status == StatusCode::TOO_MANY_REQUESTS || (status == StatusCode::FORBIDDEN && exhausted)
+        {
+            // The "Retry-After" is in seconds, the reset headers are epoch.
+            let reset = match header(&["retry-after"]) {
+                Some(after) => Some(util::current_time().unwrap_or_default() + after),
+                None => header(&["x-ratelimit-reset", "ratelimit-reset"]),
+            };
+            return Some(Retryable::RateLimited(reset));
+        }
+        if status.is_server_error() {
+            return Some(Retryable::Transient);
+        }
+        None
+    }
+}
+
+/// The retry policy shared by providers, the failed requests are retried with
+/// exponential backoff. The non-idempotent requests (such as creating a PR)
+/// are only retried when rate limited, since the rejected request is never
+/// applied, while a server error may be raised after applying it.
+#[derive(Clone, Copy)]
+pub struct Retry {
+    name: &'static str,
+    max: u32,
+}
+
+impl Retry {
+    /// The max delay between retries, in seconds. Not worth waiting for the
+    /// rate limit resetting later than this.
+    const MAX_DELAY: Epoch = 60;
+
+    pub fn new(name: &'static str, max: u32) -> Retry {
+        Retry { name, max }
+    }
+
+    /// Send the request until it succeeds or is not worth retrying. The `send`
+    /// returns the result of an attempt, with the reason to retry if failed.
+    pub fn run<T, F>(&self, method: &Method, mut send: F) -> Result<T>
+    where
+        F: FnMut() -> (Result<T>, Option<Retryable>),
+    {
+        let mut attempt = 0;
+        loop {
+            let (result, retryable) = send();
+            let (reason, delay) = match retryable {
+                Some(Retryable::RateLimited(reset)) => {
+                    let now = util::current_time()?;
+                    let delay = match reset {
+                        Some(reset) => reset.saturating_sub(now).max(1),
+                        None => Self::backoff(attempt),
+                    };
+                    if attempt >= self.max || delay > Self::MAX_DELAY {
+                        match reset {
+                            Some(reset) => bail!(
+                                "{} rate limited until {}, please try again later",
+                                self.name,
+                                style(to_local(reset)).yellow()
+                            ),
+                            None => bail!("{} rate limited, please try again later", self.name),
+                        }
+                    }
+                    ("rate limited", delay)
+                }
+                Some(Retryable::Transient) if method.is_idempotent() && attempt < self.max => {
+                    ("request failed", Self::backoff(attempt))
+                }
+                _ => return result,
+            };
+            attempt += 1;
+            _ = writeln!(
+                io::stderr(),
+                "{} {} {}, retry in {}s ({}/{})",
+                util::Palette::get().warning.apply_to("warning"),
+                self.name,
+                reason,
+                delay,
+                attempt,
+                self.max
+            );
+            thread::sleep(Duration::from_secs(delay));
+        }
+    }
+
+    fn backoff(attempt: u32) -> Epoch {
+        (1 << attempt.min(16)).min(Self::MAX_DELAY)
+    }
+}
+
+/// Parse the RFC 3339 time returned by providers.
+fn parse_time(s: &str) -> Result<Epoch> {
+    let time = DateTime::parse_from_rfc3339(s)
+        .with_context(|| format!("invalid time {} returned by provider", style(s).yellow()))?;
+    Ok(time.timestamp().max(0) as Epoch)
+}
+
+/// Convert the epoch to UTC time, to pass to providers.
+fn to_datetime(time: Epoch) -> DateTime<Utc> {
+    Utc.timestamp_opt(time as i64, 0)
+        .single()
+        .unwrap_or_default()
+}
+
+/// Format the epoch in local time, to show to users.
+fn to_local(time: Epoch) -> String {
+    match Local.timestamp_opt(time as i64, 0).single() {
+        Some(time) => time.format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => time.to_string(),
+    }
+}
+
+/// Build the commit activity, only the first line of message is used as the
+/// title.
+fn commit_activity(sha: &str, message: &str, author: String, time: Epoch) -> ActivityEvent {
+    ActivityEvent {
+        kind: ActivityKind::Commit,
+        id: sha.chars().take(7).collect(),
+        title: message.lines().next().unwrap_or_default().to_string(),
+        author,
+        time,
     }
 }
 
@@ -0,0 +1,195 @@
+use anyhow::{anyhow, bail, Context, Result};
+use console::style;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::api::{Retry, Retryable};
+
+/// The authorization used by [`Rest`].
+pub enum Auth {
+    None,
+    Basic(String, String),
+    Header(String),
+}
+
+/// A thin blocking JSON client, shared by the providers whose API is not
+/// covered by a dedicated crate.
+pub struct Rest {
+    client: Client,
+
+    name: &'static str,
+    base: String,
+    auth: Auth,
+    retry: Retry,
+
+    strip_prefix: Option<&'static str>,
+}
+
+impl Rest {
+    pub fn new(name: &'static str, base: impl AsRef<str>, auth: Auth) -> Result<Rest> {
+        let client = Client::builder()
+            .user_agent(concat!("git-zoxide/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .with_context(|| format!("unable to init {} client", name))?;
+        Ok(Rest {
+            client,
+            name,
+            base: base.as_ref().trim_end_matches('/').to_string(),
+            auth,
+            retry: Retry::new(name, 0),
+            strip_prefix: None,
+        })
+    }
+
+    /// Retry the failed requests with the shared policy, see [`Retry`].
+    pub fn with_retry(mut self, max: u32) -> Rest {
+        self.retry = Retry::new(self.name, max);
+        self
+    }
+
+    /// Strip the prefix from every response body before parsing it.
+    pub fn with_strip_prefix(mut self, prefix: &'static str) -> Rest {
+        self.strip_prefix = Some(prefix);
+        self
+    }
+
+    pub fn get<T>(&self, path: &str, query: &[(&str, &str)]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let req = self.request(Method::GET, path).query(query);
+        self.parse(self.send(req)?)
+    }
+
+    /// Similar to [`Rest::get`], but returns `Ok(None)` if the resource is
+    /// not found.
+    pub fn get_opt<T>(&self, path: &str, query: &[(&str, &str)]) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let req = self.request(Method::GET, path).query(query);
+        let resp = self.send_raw(req)?;
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(self.parse(self.check(resp)?)?))
+    }
+
+    pub fn post<B, T>(&self, path: &str, body: &B) -> Result<T>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let req = self.request(Method::POST, path).json(body);
+        self.parse(self.send(req)?)
+    }
+
+    pub fn put<B, T>(&self, path: &str, body: &B) -> Result<T>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let req = self.request(Method::PUT, path).json(body);
+        self.parse(self.send(req)?)
+    }
+
+    /// Send a PATCH request with query, the response body is ignored.
+    pub fn patch(&self, path: &str, query: &[(&str, &str)]) -> Result<()> {
+        let req = self.request(Method::PATCH, path).query(query);
+        self.send(req)?;
+        Ok(())
+    }
+
+    /// Send a DELETE request, the response body is ignored.
+    pub fn delete(&self, path: &str) -> Result<()> {
+        let req = self.request(Method::DELETE, path);
+        self.send(req)?;
+        Ok(())
+    }
+
+    /// Download the content of an absolute url, such as a release asset,
+    /// the authorization is not sent.
+    pub fn download(&self, url: &str) -> Result<Vec<u8>> {
+        let resp = self.send(self.client.get(url))?;
+        let bytes = resp
+            .bytes()
+            .with_context(|| format!("unable to download {}", style(url).yellow()))?;
+        Ok(bytes.to_vec())
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let url = format!("{}/{}", self.base, path.trim_start_matches('/'));
+        let req = self.client.request(method, url);
+        match &self.auth {
+            Auth::None => req,
+            Auth::Basic(user, password) => req.basic_auth(user, Some(password)),
+            Auth::Header(value) => req.header(reqwest::header::AUTHORIZATION, value),
+        }
+    }
+
+    fn send(&self, req: RequestBuilder) -> Result<Response> {
+        let resp = self.send_raw(req)?;
+        self.check(resp)
+    }
+
+    fn send_raw(&self, req: RequestBuilder) -> Result<Response> {
+        let req = req
+            .build()
+            .with_context(|| format!("unable to build request to {}", self.name))?;
+        self.retry.run(req.method(), || {
+            // Only the streaming body can not be cloned, which is never used.
+            let req = match req.try_clone() {
+                Some(req) => req,
+                None => {
+                    return (
+                        Err(anyhow!("unable to clone request to {}", self.name)),
+                        None,
+                    )
+                }
+            };
+            match self.client.execute(req) {
+                Ok(resp) => {
+                    let retryable = Retryable::from_response(resp.status(), resp.headers());
+                    (Ok(resp), retryable)
+                }
+                Err(err) => (
+                    Err(err).with_context(|| format!("unable to send request to {}", self.name)),
+                    Some(Retryable::Transient),
+                ),
+            }
+        })
+    }
+
+    fn check(&self, resp: Response) -> Result<Response> {
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(resp);
+        }
+        let url = resp.url().to_string();
+        let text = resp.text().unwrap_or_default();
+        bail!(
+            "{} returned {} for {}: {}",
+            self.name,
+            status,
+            style(url).yellow(),
+            text.trim()
+        )
+    }
+
+    fn parse<T>(&self, resp: Response) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let text = resp
+            .text()
+            .with_context(|| format!("unable to read {} response", self.name))?;
+        let text = match self.strip_prefix {
+            Some(prefix) => text.trim_start().trim_start_matches(prefix),
+            None => text.as_str(),
+        };
+        serde_json::from_str(text)
+            .with_context(|| format!("unable to parse {} response", self.name))
+    }
+}
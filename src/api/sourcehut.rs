@@ -0,0 +1,292 @@
+use anyhow::{bail, Context, Result};
+use console::style;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::api::rest::{Auth, Rest};
+use crate::api::{InboxItem, MergeItem, MergeOption, Provider};
+use crate::config::Remote;
+use crate::db::{ActivityEvent, Epoch, RepoMeta};
+use crate::util;
+
+/// Sourcehut provider, it is read-only since sourcehut uses mailing lists
+/// rather than merge requests.
+pub struct Sourcehut {
+    rest: Rest,
+
+    url: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GraphqlResponse<T> {
+    data: Option<T>,
+
+    #[serde(default)]
+    errors: Vec<GraphqlError>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GraphqlError {
+    message: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct UserData<T> {
+    user: Option<T>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Repositories {
+    repositories: RepositoryCursor,
+}
+
+#[derive(Deserialize, Debug)]
+struct RepositoryCursor {
+    results: Vec<Repository>,
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct UserRepository {
+    repository: Option<Repository>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RepositoryId {
+    repository: Option<Id>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Id {
+    id: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct Repository {
+    name: String,
+
+    #[serde(rename = "HEAD")]
+    head: Option<Reference>,
+
+    description: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Reference {
+    name: String,
+}
+
+impl Sourcehut {
+    const DEFAULT_URL: &str = "https://git.sr.ht";
+
+    pub fn new<U, T>(url: U, token: T, retry: u32) -> Result<Box<dyn Provider>>
+    where
+        U: AsRef<str>,
+        T: AsRef<str>,
+    {
+        let url = match url.as_ref() {
+            "" => Self::DEFAULT_URL,
+            url => url.trim_end_matches('/'),
+        };
+        if token.as_ref().is_empty() {
+            bail!("sourcehut api requires a personal access token, please check your config")
+        }
+        let auth = Auth::Header(format!("Bearer {}", token.as_ref()));
+        let rest = Rest::new("sourcehut", url, auth)?.with_retry(retry);
+        Ok(Box::new(Sourcehut {
+            rest,
+            url: url.to_string(),
+        }))
+    }
+
+    fn query<T>(&self, query: &str, variables: Value) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let body = json!({ "query": query, "variables": variables });
+        let resp: GraphqlResponse<T> = self.rest.post("query", &body)?;
+        if !resp.errors.is_empty() {
+            let msgs: Vec<&str> = resp.errors.iter().map(|e| e.message.as_str()).collect();
+            bail!("sourcehut graphql error: {}", msgs.join("; "))
+        }
+        resp.data.context("sourcehut graphql did not return data")
+    }
+
+    fn parse_repo_name(repo: &str) -> Result<(String, String)> {
+        let (owner, name) = util::split_name(repo);
+        if owner.is_empty() || name.is_empty() {
+            bail!("invalid sourcehut repository name {}", style(repo).yellow())
+        }
+        Ok((owner.trim_start_matches('~').to_string(), name))
+    }
+}
+
+impl Provider for Sourcehut {
+    fn list(&self, group: &str) -> Result<Vec<String>> {
+        let query = r#"query($user: String!, $cursor: Cursor) {
+            user(username: $user) {
+                repositories(cursor: $cursor) { results { name } cursor }
+            }
+        }"#;
+        let user = group.trim_start_matches('~');
+        let mut names = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let data: UserData<Repositories> =
+                self.query(query, json!({ "user": user, "cursor": cursor }))?;
+            let repos = match data.user {
+                Some(user) => user.repositories,
+                None => bail!("could not find sourcehut user {}", style(group).yellow()),
+            };
+            for repo in repos.results {
+                names.push(format!("~{}/{}", user, repo.name));
+            }
+            match repos.cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(names),
+            }
+        }
+    }
+
+    fn create_repo(&self, repo: &str, private: bool) -> Result<()> {
+        // The repository is always created under the owner of the token.
+        let query = r#"mutation($name: String!, $visibility: Visibility!) {
+            createRepository(name: $name, visibility: $visibility) { name }
+        }"#;
+        let (_, name) = Self::parse_repo_name(repo)?;
+        let visibility = if private { "PRIVATE" } else { "PUBLIC" };
+        let _: Value = self.query(query, json!({ "name": name, "visibility": visibility }))?;
+        Ok(())
+    }
+
+    fn delete_repo(&self, repo: &str) -> Result<()> {
+        // The repository is deleted by id, query it first.
+        let query = r#"query($user: String!, $name: String!) {
+            user(username: $user) { repository(name: $name) { id } }
+        }"#;
+        let (user, name) = Self::parse_repo_name(repo)?;
+        let data: UserData<RepositoryId> =
+            self.query(query, json!({ "user": user, "name": name }))?;
+        let id = match data.user.and_then(|user| user.repository) {
+            Some(repo) => repo.id,
+            None => bail!(
+                "could not find sourcehut repository {}",
+                style(repo).yellow()
+            ),
+        };
+
+        let query = r#"mutation($id: Int!) { deleteRepository(id: $id) { id } }"#;
+        let _: Value = self.query(query, json!({ "id": id }))?;
+        Ok(())
+    }
+
+    fn get_default_branch(&self, repo: &str) -> Result<String> {
+        let query = r#"query($user: String!, $name: String!) {
+            user(username: $user) { repository(name: $name) { name HEAD { name } } }
+        }"#;
+        let (user, name) = Self::parse_repo_name(repo)?;
+        let data: UserData<UserRepository> =
+            self.query(query, json!({ "user": user, "name": name }))?;
+        let head = data
+            .user
+            .and_then(|user| user.repository)
+            .and_then(|repo| repo.head);
+        match head {
+            Some(head) => match head.name.strip_prefix("refs/heads/") {
+                Some(branch) => Ok(branch.to_string()),
+                None => Ok(head.name),
+            },
+            None => bail!("sourcehut did not return default branch"),
+        }
+    }
+
+    fn get_upstream(&self, _repo: &str) -> Result<String> {
+        bail!("sorry, sourcehut does not support upstream features")
+    }
+
+    fn get_meta(&self, repo: &str) -> Result<RepoMeta> {
+        // Sourcehut has no stars or topics.
+        let query = r#"query($user: String!, $name: String!) {
+            user(username: $user) { repository(name: $name) { name description } }
+        }"#;
+        let (user, name) = Self::parse_repo_name(repo)?;
+        let data: UserData<UserRepository> =
+            self.query(query, json!({ "user": user, "name": name }))?;
+        match data.user.and_then(|user| user.repository) {
+            Some(repo) => Ok(RepoMeta {
+                description: repo.description.unwrap_or_default(),
+                ..Default::default()
+            }),
+            None => bail!(
+                "could not find sourcehut repository {}",
+                style(repo).yellow()
+            ),
+        }
+    }
+
+    fn list_activity(&self, _repo: &str, _since: Epoch) -> Result<Vec<ActivityEvent>> {
+        bail!("sorry, sourcehut does not support activity")
+    }
+
+    fn get_merge(&self, _opts: &MergeOption) -> Result<Option<String>> {
+        bail!("sorry, sourcehut does not support merge, please use git send-email")
+    }
+
+    fn get_merge_number(&self, _opts: &MergeOption) -> Result<Option<u64>> {
+        bail!("sorry, sourcehut does not support merge, please use git send-email")
+    }
+
+    fn create_merge(&self, _opts: &MergeOption) -> Result<String> {
+        bail!("sorry, sourcehut does not support merge, please use git send-email")
+    }
+
+    fn list_my_merges(&self) -> Result<Vec<MergeItem>> {
+        bail!("sorry, sourcehut does not support merge, please use git send-email")
+    }
+
+    fn list_review_merges(&self) -> Result<Vec<MergeItem>> {
+        bail!("sorry, sourcehut does not support merge, please use git send-email")
+    }
+
+    fn list_merges(&self, _repo: &str) -> Result<Vec<MergeItem>> {
+        bail!("sorry, sourcehut does not support merge, please use git send-email")
+    }
+
+    fn approve_merge(&self, _repo: &str, _number: u64) -> Result<()> {
+        bail!("sorry, sourcehut does not support merge, please use git send-email")
+    }
+
+    fn comment_merge(&self, _repo: &str, _number: u64, _body: &str) -> Result<()> {
+        bail!("sorry, sourcehut does not support merge, please use git send-email")
+    }
+
+    fn list_inbox(&self) -> Result<Vec<InboxItem>> {
+        bail!("sorry, sourcehut does not support notifications")
+    }
+
+    fn mark_inbox_read(&self, _id: &str) -> Result<()> {
+        bail!("sorry, sourcehut does not support notifications")
+    }
+
+    fn get_merge_ref(&self, _repo: &str, _number: u64) -> Result<String> {
+        bail!("sorry, sourcehut does not support merge, please use git send-email")
+    }
+
+    fn get_repo_url(&self, name: &str, branch: Option<String>, _remote: &Remote) -> Result<String> {
+        match branch {
+            Some(branch) => Ok(format!("{}/{}/tree/{}", self.url, name, branch)),
+            None => Ok(format!("{}/{}", self.url, name)),
+        }
+    }
+
+    fn get_compare_url(
+        &self,
+        _repo: &str,
+        _base: &str,
+        _head: &str,
+        _remote: &Remote,
+    ) -> Result<String> {
+        bail!("sorry, sourcehut does not support compare url")
+    }
+}
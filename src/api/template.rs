@@ -0,0 +1,171 @@
+use anyhow::{bail, Result};
+use console::style;
+
+use crate::api::{InboxItem, MergeItem, MergeOption, Provider};
+use crate::config::{Remote, API};
+use crate::db::{ActivityEvent, Epoch, RepoMeta};
+use crate::util;
+
+/// Provider for "dumb" remotes (cgit, Gitiles, etc.), the urls are rendered
+/// from templates in config, no API call is made.
+///
+/// The templates support placeholders `{domain}`, `{name}`, `{group}` and
+/// `{base}`. The branch template also supports `{branch}`, the merge
+/// template supports `{source}` and `{target}`, and the compare template
+/// supports `{base}` and `{head}` (here `{base}` is the base branch rather
+/// than the base name of repo).
+pub struct Template {
+    domain: String,
+
+    web_url: String,
+    branch_url: String,
+    merge_url: String,
+    compare_url: String,
+}
+
+impl Template {
+    pub fn new(remote: &Remote, api: &API) -> Result<Box<dyn Provider>> {
+        if api.web_url_template.is_empty() {
+            bail!(
+                "remote {} without provider requires web_url_template, please check your config",
+                style(&remote.name).yellow()
+            )
+        }
+        let domain = match &remote.clone {
+            Some(clone) => clone.domain.clone(),
+            None => String::new(),
+        };
+        Ok(Box::new(Template {
+            domain,
+            web_url: api.web_url_template.clone(),
+            branch_url: api.branch_url_template.clone(),
+            merge_url: api.merge_url_template.clone(),
+            compare_url: api.compare_url_template.clone(),
+        }))
+    }
+
+    /// Render the template for repo, the placeholders are replaced in order,
+    /// so the `extra` values come first to take precedence over the repo
+    /// ones with the same key.
+    fn render(&self, template: &str, name: &str, extra: &[(&str, &str)]) -> String {
+        let (group, base) = util::split_name(name);
+        let mut values = extra.to_vec();
+        values.extend_from_slice(&[
+            ("domain", self.domain.as_str()),
+            ("name", name),
+            ("group", group.as_str()),
+            ("base", base.as_str()),
+        ]);
+        util::render_template(template, &values)
+    }
+
+    fn unsupported<T>(&self, op: &str) -> Result<T> {
+        bail!("sorry, remote without provider does not support {}", op)
+    }
+}
+
+impl Provider for Template {
+    fn list(&self, _group: &str) -> Result<Vec<String>> {
+        self.unsupported("list")
+    }
+
+    fn create_repo(&self, _repo: &str, _private: bool) -> Result<()> {
+        self.unsupported("creating repo")
+    }
+
+    fn delete_repo(&self, _repo: &str) -> Result<()> {
+        self.unsupported("deleting repo")
+    }
+
+    fn get_default_branch(&self, _repo: &str) -> Result<String> {
+        self.unsupported("getting default branch")
+    }
+
+    fn get_upstream(&self, _repo: &str) -> Result<String> {
+        self.unsupported("upstream features")
+    }
+
+    fn get_meta(&self, _repo: &str) -> Result<RepoMeta> {
+        self.unsupported("repo metadata")
+    }
+
+    fn list_activity(&self, _repo: &str, _since: Epoch) -> Result<Vec<ActivityEvent>> {
+        self.unsupported("listing activity")
+    }
+
+    fn get_merge(&self, opts: &MergeOption) -> Result<Option<String>> {
+        if self.merge_url.is_empty() {
+            bail!("merge_url_template is not configured, please check your config")
+        }
+        let extra = [
+            ("source", opts.source.as_str()),
+            ("target", opts.target.as_str()),
+        ];
+        // There is no way to check whether the merge exists, always open
+        // the rendered url.
+        Ok(Some(self.render(&self.merge_url, &opts.repo, &extra)))
+    }
+
+    fn get_merge_number(&self, _opts: &MergeOption) -> Result<Option<u64>> {
+        self.unsupported("querying merge")
+    }
+
+    fn create_merge(&self, _opts: &MergeOption) -> Result<String> {
+        self.unsupported("creating merge")
+    }
+
+    fn list_my_merges(&self) -> Result<Vec<MergeItem>> {
+        self.unsupported("listing merges")
+    }
+
+    fn list_review_merges(&self) -> Result<Vec<MergeItem>> {
+        self.unsupported("listing merges")
+    }
+
+    fn list_merges(&self, _repo: &str) -> Result<Vec<MergeItem>> {
+        self.unsupported("listing merges")
+    }
+
+    fn approve_merge(&self, _repo: &str, _number: u64) -> Result<()> {
+        self.unsupported("approving merge")
+    }
+
+    fn comment_merge(&self, _repo: &str, _number: u64, _body: &str) -> Result<()> {
+        self.unsupported("commenting merge")
+    }
+
+    fn list_inbox(&self) -> Result<Vec<InboxItem>> {
+        self.unsupported("listing inbox")
+    }
+
+    fn mark_inbox_read(&self, _id: &str) -> Result<()> {
+        self.unsupported("marking inbox")
+    }
+
+    fn get_merge_ref(&self, _repo: &str, _number: u64) -> Result<String> {
+        self.unsupported("fetching merge")
+    }
+
+    fn get_repo_url(&self, name: &str, branch: Option<String>, _remote: &Remote) -> Result<String> {
+        match branch {
+            Some(branch) if !self.branch_url.is_empty() => {
+                Ok(self.render(&self.branch_url, name, &[("branch", branch.as_str())]))
+            }
+            _ => Ok(self.render(&self.web_url, name, &[])),
+        }
+    }
+
+    fn get_compare_url(
+        &self,
+        repo: &str,
+        base: &str,
+        head: &str,
+        _remote: &Remote,
+    ) -> Result<String> {
+        if self.compare_url.is_empty() {
+            bail!("compare_url_template is not configured, please check your config")
+        }
+        let extra = [("base", base), ("head", head)];
+        Ok(self.render(&self.compare_url, repo, &extra))
+    }
+}
@@ -0,0 +1,129 @@
+use std::cmp::Reverse;
+use std::io;
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use console::style;
+
+use crate::api;
+use crate::cmd::Activity;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::{ActivityCache, ActivityEvent, ActivityKind, Database, Epoch, Repo};
+use crate::util;
+
+impl Run for Activity {
+    fn run(&self) -> Result<()> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+        let now = util::current_time()?;
+
+        let since = match &self.since {
+            Some(since) => util::parse_duration(since)?,
+            None => Self::DEFAULT_SINCE,
+        };
+        let since = now.saturating_sub(since);
+
+        let remote = self.args.first().map(String::as_str).unwrap_or_default();
+        let group = self.args.get(1).map(String::as_str).unwrap_or_default();
+        if !remote.is_empty() {
+            let remote = cfg.must_get_remote(remote)?;
+            if remote.api.is_none() {
+                bail!(
+                    "remote {} does not enable api provider, please config it first",
+                    style(&remote.name).yellow()
+                )
+            }
+        }
+        // Only the repos whose remote has api provider have activities.
+        let repos: Vec<&Repo> = db
+            .filter(remote, group)
+            .into_iter()
+            .filter(|repo| match cfg.get_remote(&repo.remote) {
+                Some(remote) => remote.api.is_some(),
+                None => false,
+            })
+            .collect();
+        if repos.is_empty() {
+            _ = writeln!(io::stderr(), "nothing to do");
+            return Ok(());
+        }
+
+        let mut cache = ActivityCache::open()?;
+        let expired: Vec<&Repo> = repos
+            .iter()
+            .filter(|repo| self.force || cache.get(repo, since, now).is_none())
+            .copied()
+            .collect();
+        let results = util::parallel(&expired, |repo| fetch_activity(&cfg, repo, since));
+        for (repo, result) in expired.iter().zip(results) {
+            match result {
+                Ok(events) => cache.put(repo, since, events, now),
+                Err(err) => {
+                    _ = writeln!(
+                        io::stderr(),
+                        "{} fetch activity of {}:{} failed: {:#}",
+                        util::Palette::get().error.apply_to("error"),
+                        style(&repo.remote).yellow(),
+                        style(&repo.name).yellow(),
+                        err
+                    );
+                }
+            }
+        }
+        cache.save()?;
+
+        let mut empty = true;
+        for repo in repos {
+            let mut events = match cache.get(repo, since, now) {
+                Some(events) if !events.is_empty() => events,
+                _ => continue,
+            };
+            events.sort_by_key(|event| Reverse(event.time));
+            println!(
+                "{}:{}",
+                style(&repo.remote).yellow(),
+                style(&repo.name).yellow()
+            );
+            for event in events {
+                Self::show(event, now);
+            }
+            empty = false;
+        }
+        if empty {
+            _ = writeln!(
+                io::stderr(),
+                "no activity since {}",
+                util::format_since(now, since)
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl Activity {
+    const DEFAULT_SINCE: Epoch = 7 * util::DAY;
+
+    /// Print the event in format `<id> <title> <author> <time>`, the release
+    /// is prefixed with "release".
+    fn show(event: &ActivityEvent, now: Epoch) {
+        let id = match event.kind {
+            ActivityKind::Commit => style(event.id.clone()).yellow().to_string(),
+            ActivityKind::Release => format!("{} {}", style("release").green(), event.id),
+        };
+        println!(
+            "  {} {} {} {}",
+            id,
+            event.title,
+            style(&event.author).magenta(),
+            style(util::format_since(now, event.time)).dim()
+        );
+    }
+}
+
+fn fetch_activity(cfg: &Config, repo: &Repo, since: Epoch) -> Result<Vec<ActivityEvent>> {
+    let remote = cfg.must_get_remote(&repo.remote)?;
+    let provider = api::create_provider(remote)?;
+    provider.list_activity(&repo.name, since)
+}
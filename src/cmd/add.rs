@@ -0,0 +1,51 @@
+use anyhow::{bail, Result};
+use console::style;
+
+use crate::cmd::jump;
+use crate::cmd::Add;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+
+impl Run for Add {
+    fn run(&self) -> Result<()> {
+        let cfg = Config::parse()?;
+        let mut db = Database::open()?;
+        let now = util::current_time()?;
+
+        let (domain, name) = match util::parse_repo_url(&self.url) {
+            Some(parsed) => parsed,
+            None => bail!(
+                "could not parse repository url {}",
+                style(&self.url).yellow()
+            ),
+        };
+        let remote = match db.remotes_by_domain(&cfg, &domain, &name)?[..] {
+            [remote] => remote,
+            [] => bail!(
+                "no remote matches the domain {}, please add clone.domain to config",
+                style(&domain).yellow()
+            ),
+            _ => bail!(
+                "multiple remotes match the domain {}, please use home command with the remote",
+                style(&domain).yellow()
+            ),
+        };
+
+        let idx = match db.get(&remote.name, &name) {
+            Some(idx) => idx,
+            None => {
+                util::print_operation(format!(
+                    "add {}:{}",
+                    style(&remote.name).yellow(),
+                    style(&name).yellow()
+                ));
+                let idx = db.add(&remote.name, &name, "");
+                db.resolve_collision(idx, &cfg)?;
+                idx
+            }
+        };
+        jump::enter(&mut db, &cfg, idx, now, |_, _| Ok(()))
+    }
+}
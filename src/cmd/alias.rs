@@ -0,0 +1,107 @@
+use std::io::{self, Write};
+
+use anyhow::{bail, Result};
+use clap::CommandFactory;
+use console::style;
+
+use crate::cmd::Alias;
+use crate::cmd::Cmd;
+use crate::cmd::Run;
+use crate::config::Config;
+
+impl Run for Alias {
+    fn run(&self) -> Result<()> {
+        let cfg = Config::parse()?;
+        let mut names: Vec<&String> = cfg.aliases.keys().collect();
+        names.sort();
+        for name in names {
+            _ = writeln!(io::stdout(), "{} = {}", name, cfg.aliases[name]);
+        }
+        Ok(())
+    }
+}
+
+/// Expand the alias in the first argument with the `aliases` in config,
+/// the rest arguments are appended to the expanded ones. The builtin
+/// commands cannot be overridden, and the config is only parsed when the
+/// first argument is not a builtin command, so that a broken config will
+/// not block commands like `gz config`.
+pub fn expand_alias(mut args: Vec<String>) -> Result<Vec<String>> {
+    let name = match args.get(1) {
+        Some(name) if !name.starts_with('-') => name,
+        _ => return Ok(args),
+    };
+    let is_builtin = name == "help"
+        || Cmd::command()
+            .get_subcommands()
+            .any(|cmd| cmd.get_name() == name);
+    if is_builtin {
+        return Ok(args);
+    }
+
+    let cfg = Config::parse()?;
+    let template = match cfg.aliases.get(name) {
+        Some(template) => template,
+        None => return Ok(args),
+    };
+    let expanded = split_args(template)?;
+    if expanded.is_empty() {
+        bail!(
+            "alias {} is empty, please check your config",
+            style(name).yellow()
+        )
+    }
+    args.splice(1..2, expanded);
+    Ok(args)
+}
+
+/// Split the alias template into arguments by whitespace, the single or
+/// double quotes can be used to include whitespace in an argument.
+fn split_args(s: &str) -> Result<Vec<String>> {
+    let mut args = vec![];
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in s.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            (None, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        bail!("unclosed quote in alias {}", style(s).yellow())
+    }
+    if let Some(arg) = current {
+        args.push(arg);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_quotes() {
+        for (s, expect) in [
+            ("", vec![]),
+            ("home  gh {0}", vec!["home", "gh", "{0}"]),
+            (" list -g 'my group' ", vec!["list", "-g", "my group"]),
+            (r#"exec "echo 'hi'" ''"#, vec!["exec", "echo 'hi'", ""]),
+            ("a'b c'd", vec!["ab cd"]),
+        ] {
+            assert_eq!(split_args(s).unwrap(), expect, "{}", s);
+        }
+        assert!(split_args("exec 'echo").is_err());
+        assert!(split_args(r#"exec "echo'"#).is_err());
+    }
+}
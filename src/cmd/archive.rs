@@ -0,0 +1,167 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use console::style;
+
+use crate::cmd::Archive;
+use crate::cmd::Run;
+use crate::config::{Config, Remote};
+use crate::db::{Database, HistoryEntry, Repo};
+use crate::util;
+use crate::util::Shell;
+
+impl Run for Archive {
+    fn run(&self) -> Result<()> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+
+        let idx = db.must_get(&self.remote, &self.name)?;
+        archive_repo(&cfg, &db, &db.repos[idx], self.force)
+    }
+}
+
+/// Move the repo from workspace to cold storage. With `force`, the confirm
+/// is skipped.
+pub fn archive_repo(cfg: &Config, db: &Database, repo: &Repo, force: bool) -> Result<()> {
+    if cfg.cold_storage.is_empty() {
+        bail!("cold_storage is not configured, please config it first")
+    }
+    let cold_path = match repo.cold_path(&cfg.cold_storage) {
+        Some(path) => path,
+        None => bail!(
+            "repo {} is attached to {}, cannot archive it",
+            style(&repo.name).yellow(),
+            repo.path
+        ),
+    };
+    let path = repo.path(&cfg.workspace)?;
+    if !path.exists() {
+        bail!(
+            "repo {} is not in workspace, nothing to archive",
+            style(&repo.name).yellow()
+        )
+    }
+    if cold_path.exists() {
+        bail!(
+            "{} already exists in cold storage, please check it",
+            cold_path.display()
+        )
+    }
+    util::check_processes(cfg, &path, force)?;
+    if !force {
+        util::confirm(format!(
+            "do you want to archive {} to {}",
+            style(&repo.name).yellow(),
+            cold_path.display()
+        ))?;
+    }
+
+    // The maintenance of git is registered by path, it would fail after
+    // moving.
+    let path_str = util::path_to_str(&path)?;
+    if util::maintenance_repos()?
+        .iter()
+        .any(|repo| repo == path_str)
+    {
+        Shell::git()
+            .with_git_path(path_str)
+            .args(["maintenance", "unregister"])
+            .exec()?;
+    }
+    util::print_operation(format!(
+        "archive {} to {}",
+        style(&repo.name).yellow(),
+        cold_path.display()
+    ));
+    util::move_dir(&path, &cold_path)?;
+    HistoryEntry::new("archive")
+        .repo(&repo.remote, &repo.name)
+        .path(path_str)
+        .detail(cold_path.display().to_string())
+        .record();
+
+    let paths = db.list_paths(&cfg.workspace)?;
+    let empty_dir = util::EmptyDir::scan(&cfg.workspace, &paths)?;
+    empty_dir.clean()?;
+
+    Ok(())
+}
+
+/// Restore the repo to jump if it is archived in cold storage, by moving it
+/// back or cloning it again. The copy in cold storage is removed after
+/// restoring.
+pub fn restore_on_jump(cfg: &Config, repo: &Repo, remote: &Remote) -> Result<()> {
+    let cold_path = match repo.cold_path(&cfg.cold_storage) {
+        Some(path) if path.exists() => path,
+        _ => return Ok(()),
+    };
+    let path = repo.path(&cfg.workspace)?;
+    if path.exists() {
+        return Ok(());
+    }
+
+    let msg = format!(
+        "{} is archived in {}, restore it by",
+        style(&repo.name).yellow(),
+        cold_path.display()
+    );
+    // Only the cloned repo can be cloned again.
+    let reclone = match remote.clone {
+        Some(_) => util::select(msg, &["move back", "clone again"])? == 1,
+        None => {
+            util::confirm(format!("{} moving back", msg))?;
+            false
+        }
+    };
+    if reclone {
+        repo.ensure_path(&cfg.workspace, remote)?;
+        util::print_operation(format!("remove {}", cold_path.display()));
+        fs::remove_dir_all(&cold_path)
+            .with_context(|| format!("could not remove {}", cold_path.display()))?;
+    } else {
+        util::print_operation(format!(
+            "restore {} to {}",
+            style(&repo.name).yellow(),
+            path.display()
+        ));
+        util::move_dir(&cold_path, &path)?;
+        HistoryEntry::new("restore")
+            .repo(&repo.remote, &repo.name)
+            .path(path.display().to_string())
+            .detail(cold_path.display().to_string())
+            .record();
+    }
+    remove_empty_parents(&cold_path, Path::new(&cfg.cold_storage));
+    Ok(())
+}
+
+/// Remove the copy of repo in cold storage, used when removing the repo.
+pub fn remove_archived(cfg: &Config, repo: &Repo, force: bool) -> Result<()> {
+    let cold_path = match repo.cold_path(&cfg.cold_storage) {
+        Some(path) if path.exists() => path,
+        _ => return Ok(()),
+    };
+    if !force {
+        util::confirm(format!(
+            "do you want to remove the archived {}",
+            cold_path.display()
+        ))?;
+    }
+    fs::remove_dir_all(&cold_path)
+        .with_context(|| format!("could not remove {}", cold_path.display()))?;
+    remove_empty_parents(&cold_path, Path::new(&cfg.cold_storage));
+    Ok(())
+}
+
+/// Remove the empty parent directories of the restored repo, up to the root
+/// of cold storage. This is best-effort.
+fn remove_empty_parents(path: &Path, root: &Path) {
+    let mut dir = path.parent();
+    while let Some(parent) = dir {
+        if parent == root || fs::remove_dir(parent).is_err() {
+            return;
+        }
+        dir = parent.parent();
+    }
+}
@@ -31,7 +31,7 @@ impl Run for Attach {
         }
 
         let path_str = util::osstr_to_str(path.as_os_str())?;
-        if let Some(_) = db.get_by_path(path_str) {
+        if let Some(_) = db.get_by_path(path_str, cfg.resolve_symlinks) {
             bail!(
                 "path {} has already bound to anthor repository, please consider detach first",
                 style(path_str).yellow()
@@ -39,6 +39,22 @@ impl Run for Attach {
         }
 
         let idx = db.add(&self.remote, &self.name, path_str);
+        // The origin will be reset with `--remote-config`, no need to check.
+        if !self.remote_config {
+            db.resolve_collision(idx, &cfg)?;
+            if db.repos[idx].path != path_str {
+                // Relocated, the directory is left to its owner, the repo
+                // will be cloned into the new path on the next jump.
+                db.save()?;
+                _ = writeln!(
+                    io::stderr(),
+                    "{} relocated to {}",
+                    style(&self.name).yellow(),
+                    style(&db.repos[idx].path).yellow()
+                );
+                return Ok(());
+            }
+        }
         if self.remote_config {
             if let Some(clone) = &remote.clone {
                 let url = db.repos[idx].clone_url(clone);
@@ -64,6 +80,12 @@ impl Run for Attach {
             }
         }
 
+        if let Some(clone) = &remote.clone {
+            db.repos[idx].install_ssh_key(clone, path_str)?;
+        }
+        db.repos[idx].install_commit_template(remote, path_str)?;
+        db.repos[idx].install_signing(remote, path_str)?;
+
         db.save()?;
 
         _ = writeln!(io::stderr(), "{} attached", style(path_str).yellow());
@@ -0,0 +1,142 @@
+use std::io::{self, IsTerminal, Read, Write};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+
+use crate::api::DeviceFlow;
+use crate::cmd::Run;
+use crate::cmd::{Auth, AuthAction};
+use crate::config::{self, Config, Remote, TokenSource, API};
+use crate::util::{self, Keyring};
+
+impl Run for Auth {
+    fn run(&self) -> Result<()> {
+        let cfg = Config::parse()?;
+        match &self.action {
+            AuthAction::Set { remote } => {
+                let remote = cfg.must_get_remote(remote)?;
+                let api = Self::must_get_api(remote)?;
+                let token = Self::read_token(&remote.name)?;
+                if token.is_empty() {
+                    bail!("token could not be empty")
+                }
+                Keyring::set(&remote.name, &token)?;
+                _ = writeln!(
+                    io::stderr(),
+                    "token of {} stored to keyring",
+                    style(&remote.name).yellow()
+                );
+                if api.token_from != TokenSource::Keyring {
+                    _ = writeln!(
+                        io::stderr(),
+                        "{} the token is not used until token_from of {} is set to keyring",
+                        util::Palette::get().warning.apply_to("warning"),
+                        style(&remote.name).yellow()
+                    );
+                }
+                Ok(())
+            }
+            AuthAction::Remove { remote } => {
+                let remote = cfg.must_get_remote(remote)?;
+                if !Keyring::remove(&remote.name)? {
+                    bail!(
+                        "token of {} is not stored in keyring",
+                        style(&remote.name).yellow()
+                    )
+                }
+                _ = writeln!(
+                    io::stderr(),
+                    "token of {} removed from keyring",
+                    style(&remote.name).yellow()
+                );
+                Ok(())
+            }
+            AuthAction::Login { remote } => Self::login(&cfg, cfg.must_get_remote(remote)?),
+        }
+    }
+}
+
+impl Auth {
+    fn login(cfg: &Config, remote: &Remote) -> Result<()> {
+        let api = Self::must_get_api(remote)?;
+        if !matches!(api.provider, config::Provider::Github) {
+            bail!("login only supports github provider, please use `gz auth set` instead")
+        }
+        let client_id = match api.client_id.as_str() {
+            "" => match DeviceFlow::default_client_id(&api.url) {
+                Some(id) => id,
+                None => bail!(
+                    "login requires an OAuth app with device flow enabled, please set its client id to client_id of remote {}",
+                    style(&remote.name).yellow()
+                ),
+            },
+            id => id,
+        };
+
+        let mut flow = DeviceFlow::start(&api.url, client_id)?;
+        _ = writeln!(
+            io::stderr(),
+            "please enter code {} in {}",
+            style(&flow.user_code).yellow().bold(),
+            style(&flow.verification_uri).yellow()
+        );
+        // The code is shown above, the user can open the page by hand.
+        if let Err(err) = cfg.open_url(&flow.verification_uri) {
+            _ = writeln!(
+                io::stderr(),
+                "{} {}",
+                util::Palette::get().warning.apply_to("warning"),
+                err
+            );
+        }
+        util::print_operation("waiting for authorization ...");
+        let token = flow.wait()?;
+
+        Keyring::set(&remote.name, &token)?;
+        _ = writeln!(
+            io::stderr(),
+            "logged in, token of {} stored to keyring",
+            style(&remote.name).yellow()
+        );
+        if api.token_from != TokenSource::Keyring {
+            Config::set_keyring_token(&Config::get_path()?, &remote.name)?;
+            _ = writeln!(
+                io::stderr(),
+                "token_from of {} is set to keyring in config",
+                style(&remote.name).yellow()
+            );
+        }
+        if !api.token.is_empty() {
+            _ = writeln!(
+                io::stderr(),
+                "{} the token in config is not used anymore, please remove it",
+                util::Palette::get().warning.apply_to("warning")
+            );
+        }
+        Ok(())
+    }
+
+    fn must_get_api(remote: &Remote) -> Result<&API> {
+        match &remote.api {
+            Some(api) => Ok(api),
+            None => bail!(
+                "remote {} does not enable api provider, please config it first",
+                style(&remote.name).yellow()
+            ),
+        }
+    }
+
+    /// Read the token from stdin if it is piped, so that the token can be
+    /// set by scripts, otherwise prompt for it.
+    fn read_token(remote: &str) -> Result<String> {
+        if !io::stdin().is_terminal() {
+            let mut token = String::new();
+            io::stdin()
+                .read_to_string(&mut token)
+                .context("unable to read token from stdin")?;
+            return Ok(token.trim().to_string());
+        }
+        let token = util::password(format!("token for {}", style(remote).yellow()))?;
+        Ok(token.trim().to_string())
+    }
+}
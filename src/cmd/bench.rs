@@ -0,0 +1,255 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use pad::PadStr;
+
+use crate::cmd::Bench;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+
+/// The environment of the benchmarked commands, they run against a copy or
+/// a synthesized database, so the ranks of repos are not affected.
+struct BenchEnv {
+    dir: PathBuf,
+    envs: Vec<(&'static str, PathBuf)>,
+
+    repos: usize,
+    size: u64,
+
+    remote: String,
+    name: String,
+}
+
+impl Run for Bench {
+    fn run(&self) -> Result<()> {
+        let count = self.count.unwrap_or(Self::DEFAULT_COUNT);
+        if count < 2 {
+            bail!("the count should be at least 2, one cold run and one warm run")
+        }
+        let dir = Config::get_data_dir()?.join("bench");
+        if dir.exists() {
+            fs::remove_dir_all(&dir).context("unable to clean bench directory")?;
+        }
+        fs::create_dir_all(&dir).context("unable to create bench directory")?;
+
+        let result = match self.synth {
+            Some(size) => Self::synth(dir.clone(), size),
+            None => Self::copy(dir.clone()),
+        }
+        .and_then(|env| Self::bench(&env, count));
+        _ = fs::remove_dir_all(&dir);
+        result
+    }
+}
+
+impl Bench {
+    const DEFAULT_COUNT: usize = 10;
+    const SYNTH_REMOTE: &str = "bench";
+    const SYNTH_GROUPS: usize = 20;
+
+    /// Use a copy of current database and data files, the target repo is the
+    /// top ranked one that exists in workspace. The hooks and zoxide are
+    /// disabled in the copy of config, they are not part of the benchmark
+    /// and should not be triggered by it.
+    fn copy(dir: PathBuf) -> Result<BenchEnv> {
+        let cfg = Config::parse()?;
+        let mut db = Database::open()?;
+        let repo = db
+            .repos
+            .iter()
+            .find(|repo| match repo.path(&cfg.workspace) {
+                Ok(path) => path.exists(),
+                Err(_) => false,
+            });
+        let (remote, name) = match repo {
+            Some(repo) => (repo.remote.clone(), repo.name.clone()),
+            None => bail!("no repository in workspace to benchmark, please try --synth"),
+        };
+
+        let data_dir = dir.join("data");
+        fs::create_dir_all(&data_dir).context("unable to create bench data directory")?;
+        let entries =
+            fs::read_dir(Config::get_data_dir()?).context("unable to read data directory")?;
+        for entry in entries {
+            let entry = entry.context("unable to read data directory")?;
+            // The database is copied below, it might be out of data directory.
+            if !entry.path().is_file() || entry.file_name() == "database" {
+                continue;
+            }
+            fs::copy(entry.path(), data_dir.join(entry.file_name()))
+                .context("unable to copy data file")?;
+        }
+        let db_path = data_dir.join("database");
+        let mut copy = Database::open_at(db_path.clone())?;
+        copy.repos = mem::take(&mut db.repos);
+        copy.save()?;
+
+        let mut envs = vec![
+            ("_GZ_DATA_PATH", data_dir),
+            ("_GZ_DATABASE_PATH", db_path.clone()),
+        ];
+        if let Some(config_path) = Self::copy_config(&dir)? {
+            envs.push(("_GZ_CONFIG_PATH", config_path));
+        }
+        Ok(BenchEnv {
+            envs,
+            repos: copy.repos.len(),
+            size: util::dir_size(&db_path)?,
+            remote,
+            name,
+            dir,
+        })
+    }
+
+    /// Copy the config with hooks and zoxide disabled, `None` if there is no
+    /// config file, the default config enables neither.
+    fn copy_config(dir: &Path) -> Result<Option<PathBuf>> {
+        let content = match fs::read_to_string(Config::get_path()?) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).context("could not read config file"),
+        };
+        let mut config: serde_yaml::Value =
+            serde_yaml::from_str(&content).context("could not parse config file")?;
+        if let Some(config) = config.as_mapping_mut() {
+            config.remove("hooks");
+            config.insert("zoxide".into(), false.into());
+        }
+        let config = serde_yaml::to_string(&config).context("unable to encode bench config")?;
+        let path = dir.join("config.yaml");
+        fs::write(&path, config).context("unable to write bench config")?;
+        Ok(Some(path))
+    }
+
+    /// Synthesize a database with fake repos, with a config containing only
+    /// the workspace and a remote without clone, so nothing is cloned.
+    fn synth(dir: PathBuf, size: usize) -> Result<BenchEnv> {
+        if size == 0 {
+            bail!("the size of synthesized database should be positive")
+        }
+        let data_dir = dir.join("data");
+        let workspace = dir.join("workspace");
+        let config_path = dir.join("config.yaml");
+        let config = format!(
+            "workspace: {}\nremotes:\n  - name: {}\n",
+            workspace.display(),
+            Self::SYNTH_REMOTE
+        );
+        fs::write(&config_path, config).context("unable to write bench config")?;
+
+        let now = util::current_time()?;
+        let db_path = data_dir.join("database");
+        let mut db = Database::open_at(db_path.clone())?;
+        for i in 0..size {
+            let name = format!("group-{}/repo-{}", i % Self::SYNTH_GROUPS, i);
+            let idx = db.add(Self::SYNTH_REMOTE, name, "");
+            let repo = &mut db.repos[idx];
+            repo.accessed = fastrand::u32(1..1000) as f64;
+            repo.last_accessed = now - fastrand::u64(0..90 * util::DAY);
+        }
+        db.sort(now);
+        db.save()?;
+
+        // Only the target repo exists in workspace.
+        let repo = &db.repos[0];
+        fs::create_dir_all(repo.path(util::path_to_str(&workspace)?)?)
+            .context("unable to create bench repository")?;
+        Ok(BenchEnv {
+            envs: vec![
+                ("_GZ_CONFIG_PATH", config_path),
+                ("_GZ_DATA_PATH", data_dir),
+                ("_GZ_DATABASE_PATH", db_path.clone()),
+            ],
+            repos: size,
+            size: util::dir_size(&db_path)?,
+            remote: repo.remote.clone(),
+            name: repo.name.clone(),
+            dir,
+        })
+    }
+
+    fn bench(env: &BenchEnv, count: usize) -> Result<()> {
+        let (_, base) = util::split_name(&env.name);
+        let cases: [(&str, Vec<&str>); 3] = [
+            ("home", vec!["home", &env.remote, &env.name]),
+            ("jump", vec!["jump", &base]),
+            ("list", vec!["list", &env.remote]),
+        ];
+        _ = writeln!(
+            io::stderr(),
+            "Benchmark against {} repos (database {}), target {}:{}, {} runs each",
+            style(env.repos).yellow(),
+            style(util::human_bytes(env.size)).yellow(),
+            style(&env.remote).yellow(),
+            style(&env.name).yellow(),
+            count
+        );
+
+        println!(
+            "{} {} {} {} warm max",
+            "command".pad_to_width(8),
+            "cold".pad_to_width(10),
+            "warm avg".pad_to_width(10),
+            "warm min".pad_to_width(10),
+        );
+        for (name, args) in cases {
+            let mut costs = Vec::with_capacity(count);
+            for _ in 0..count {
+                costs.push(Self::run_once(env, &args)?);
+            }
+            let warm = &costs[1..];
+            let avg = warm.iter().sum::<Duration>() / warm.len() as u32;
+            println!(
+                "{} {} {} {} {}",
+                name.pad_to_width(8),
+                Self::format_cost(costs[0]).pad_to_width(10),
+                Self::format_cost(avg).pad_to_width(10),
+                Self::format_cost(*warm.iter().min().unwrap()).pad_to_width(10),
+                Self::format_cost(*warm.iter().max().unwrap())
+            );
+        }
+        Ok(())
+    }
+
+    fn run_once(env: &BenchEnv, args: &[&str]) -> Result<Duration> {
+        let exe = env::current_exe().context("unable to get current binary path")?;
+        let mut cmd = Command::new(exe);
+        cmd.args(args)
+            .current_dir(&env.dir)
+            .env("_GZ_RESULT_FILE", env.dir.join("result"))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        for (key, value) in &env.envs {
+            cmd.env(key, value);
+        }
+
+        let start = Instant::now();
+        let output = cmd
+            .output()
+            .with_context(|| format!("unable to run {}", style(args[0]).yellow()))?;
+        let cost = start.elapsed();
+        if !output.status.success() {
+            bail!(
+                "benchmark {} failed: {}",
+                style(args.join(" ")).yellow(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+        }
+        Ok(cost)
+    }
+
+    fn format_cost(cost: Duration) -> String {
+        format!("{:.2}ms", cost.as_secs_f64() * 1000.0)
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::Instant;
+
+use anyhow::Result;
+
+use crate::cmd::snapshot::{self, ManifestRepo};
+use crate::cmd::Bootstrap;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::Database;
+
+impl Run for Bootstrap {
+    fn run(&self) -> Result<()> {
+        let mut db = Database::open()?;
+        let cfg = Config::parse()?;
+
+        let mut repos = vec![];
+        for repo in &cfg.repos {
+            if !self.label.is_empty() && !repo.labels.iter().any(|l| self.label.contains(l)) {
+                continue;
+            }
+            repos.push(ManifestRepo {
+                remote: repo.remote.clone(),
+                name: repo.name.clone(),
+                path: repo.path.clone(),
+                branch: repo.pin.clone(),
+            });
+        }
+
+        if self.dry_run {
+            for repo in &repos {
+                let exists = match db.get(&repo.remote, &repo.name) {
+                    Some(idx) => db.repos[idx].path(&cfg.workspace)?.exists(),
+                    None => false,
+                };
+                if !exists {
+                    println!("{}:{}", repo.remote, repo.name);
+                }
+            }
+            return Ok(());
+        }
+
+        let start = Instant::now();
+        let result = snapshot::restore_repos(&mut db, &cfg, &repos);
+        cfg.notify_done(start, "bootstrap is done");
+        result
+    }
+}
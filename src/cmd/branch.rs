@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
@@ -6,6 +8,9 @@ use pad::PadStr;
 
 use crate::cmd::Branch;
 use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::{Database, HistoryEntry, StatusCache};
+use crate::output::Record;
 use crate::util;
 use crate::util::BranchStatus;
 use crate::util::GitBranch;
@@ -15,12 +20,23 @@ impl Run for Branch {
     fn run(&self) -> Result<()> {
         if self.sync {
             GitBranch::ensure_no_uncommitted()?;
+            let cfg = Config::parse()?;
+            let start = Instant::now();
             self.fetch()?;
+            let branches = GitBranch::list().context("unable to list branch")?;
+            self.sync(&branches)?;
+            if cfg.status_cache {
+                let db = Database::open()?;
+                if let Ok(idx) = db.current_index(&cfg) {
+                    let mut cache = StatusCache::open()?;
+                    cache.refresh(&db.repos[idx], &cfg.workspace, util::current_time()?)?;
+                    cache.save()?;
+                }
+            }
+            cfg.notify_done(start, "branch sync is done");
+            return Ok(());
         }
         let branches = GitBranch::list().context("unable to list branch")?;
-        if self.sync {
-            return self.sync(&branches);
-        }
         if self.delete {
             return self.delete(&branches);
         }
@@ -57,6 +73,17 @@ impl Branch {
         if branches.is_empty() {
             return;
         }
+        if self.porcelain {
+            for branch in branches {
+                Record::new()
+                    .push(&branch.name)
+                    .push_bool(branch.current)
+                    .push(branch.status.name())
+                    .push(&branch.worktree)
+                    .print();
+            }
+            return;
+        }
         if !self.all {
             for branch in branches {
                 if branch.current && self.cmp {
@@ -70,14 +97,20 @@ impl Branch {
         let pad = pad.name.len();
 
         for branch in branches {
-            println!(
-                "{} {}",
-                branch
-                    .name
-                    .as_str()
-                    .pad_to_width_with_alignment(pad, pad::Alignment::Left),
-                branch.status.display(),
-            );
+            let name = branch
+                .name
+                .as_str()
+                .pad_to_width_with_alignment(pad, pad::Alignment::Left);
+            if branch.checked_out_elsewhere() {
+                println!(
+                    "{} {} {}",
+                    name,
+                    branch.status.display(),
+                    style(format!("(worktree {})", branch.worktree)).dim()
+                );
+                continue;
+            }
+            println!("{} {}", name, branch.status.display());
         }
     }
 
@@ -87,7 +120,15 @@ impl Branch {
         let mut back = &default;
         let mut tasks: Vec<SyncBranchTask> = vec![];
         let mut current: &str = "";
+        let mut skipped: Vec<&GitBranch> = vec![];
         for branch in branches {
+            if branch.checked_out_elsewhere() {
+                // The branch can only be synced in its own worktree.
+                if !matches!(branch.status, BranchStatus::Sync | BranchStatus::Detached) {
+                    skipped.push(branch);
+                }
+                continue;
+            }
             if branch.current {
                 current = branch.name.as_str();
                 match branch.status {
@@ -113,6 +154,14 @@ impl Branch {
         }
 
         println!();
+        for branch in skipped {
+            println!(
+                "skip {} {}, it is checked out in worktree {}",
+                branch.status.name(),
+                style(&branch.name).magenta(),
+                style(&branch.worktree).yellow()
+            );
+        }
         if tasks.is_empty() {
             println!("nothing to do");
             return Ok(());
@@ -154,6 +203,10 @@ impl Branch {
                         current = default;
                     }
                     Shell::git().args(["branch", "-D", branch]).exec()?;
+                    HistoryEntry::new("delete-branch")
+                        .current_path()
+                        .detail(branch)
+                        .record();
                 }
             }
         }
@@ -165,14 +218,37 @@ impl Branch {
     }
 
     fn fetch(&self) -> Result<()> {
+        if self.no_fetch {
+            return Ok(());
+        }
         let mut git = Shell::git();
         git.args(["fetch", "origin", "--prune"]);
+        if self.fetch_current {
+            let current = GitBranch::current()?;
+            if current.is_empty() {
+                bail!("could not fetch current branch in detached HEAD")
+            }
+            // The pattern refspec does not fail when the branch is gone on
+            // remote, and the stale remote branch is pruned, so that it can
+            // be detected as gone.
+            git.arg(format!(
+                "+refs/heads/{}*:refs/remotes/origin/{}*",
+                current, current
+            ));
+        }
         git.exec()?;
         Ok(())
     }
 
     fn delete(&self, branches: &Vec<GitBranch>) -> Result<()> {
         let branch = self.get_branch_or_current(branches)?;
+        if branch.checked_out_elsewhere() {
+            bail!(
+                "branch {} is checked out in worktree {}, please remove the worktree first",
+                style(&branch.name).yellow(),
+                style(&branch.worktree).yellow()
+            )
+        }
 
         if branch.current {
             GitBranch::ensure_no_uncommitted()?;
@@ -184,6 +260,10 @@ impl Branch {
         }
 
         Shell::git().args(["branch", "-D", &branch.name]).exec()?;
+        HistoryEntry::new("delete-branch")
+            .current_path()
+            .detail(&branch.name)
+            .record();
         if self.push {
             Shell::git()
                 .args(["push", "origin", "--delete", &branch.name])
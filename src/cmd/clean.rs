@@ -1,6 +1,11 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use console::style;
 
 use crate::cmd::Clean;
 use crate::cmd::Run;
@@ -16,9 +21,12 @@ impl Run for Clean {
         let paths = db.list_paths(&cfg.workspace)?;
         let empty_dir = util::EmptyDir::scan(&cfg.workspace, &paths)?;
 
-        if self.dry_run {
+        if self.dry_run || self.report {
             let mut dirs = vec![];
             empty_dir.list(&mut dirs);
+            if self.report {
+                return self.report(&db, &cfg, &paths, dirs);
+            }
             for dir in dirs {
                 println!("{}", PathBuf::from(dir).display());
             }
@@ -27,3 +35,106 @@ impl Run for Clean {
         empty_dir.clean()
     }
 }
+
+impl Clean {
+    fn report(
+        &self,
+        db: &Database,
+        cfg: &Config,
+        paths: &Vec<PathBuf>,
+        empty_dirs: Vec<&OsStr>,
+    ) -> Result<()> {
+        let tracked: HashSet<&PathBuf> = paths.iter().collect();
+        let workspace = PathBuf::from(&cfg.workspace);
+        let mut untracked = vec![];
+        Self::scan_untracked(&workspace, &tracked, &mut untracked)?;
+        untracked.sort();
+
+        Self::print_title("Empty directories (candidates for clean)", empty_dirs.len());
+        for dir in empty_dirs {
+            println!("  {}", PathBuf::from(dir).display());
+        }
+
+        Self::print_title(
+            "Untracked repositories (candidates for attach)",
+            untracked.len(),
+        );
+        for path in untracked {
+            // Guess the remote and name from the path relative to workspace.
+            let hint = match path.strip_prefix(&workspace).ok().and_then(Path::to_str) {
+                Some(rel) => match rel.split_once('/') {
+                    Some((remote, name)) if cfg.get_remote(remote).is_some() => {
+                        format!("gz attach {} {}", remote, name)
+                    }
+                    _ => String::new(),
+                },
+                None => String::new(),
+            };
+            println!("  {} {}", path.display(), style(hint).dim());
+        }
+
+        let mut missing = vec![];
+        for (repo, path) in db.repos.iter().zip(paths) {
+            if !path.exists() {
+                missing.push((repo, path));
+            }
+        }
+        Self::print_title(
+            "Missing repositories (candidates for remove)",
+            missing.len(),
+        );
+        for (repo, path) in missing {
+            println!(
+                "  {}:{} {}",
+                repo.remote,
+                repo.name,
+                style(path.display()).dim()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn print_title(title: &str, count: usize) {
+        println!(
+            "{} {}",
+            style(title).bold(),
+            style(format!("({})", count)).dim()
+        );
+    }
+
+    /// Find directories containing `.git` under `dir` that are not tracked,
+    /// the git repositories and tracked directories are not walked into.
+    fn scan_untracked(
+        dir: &Path,
+        tracked: &HashSet<&PathBuf>,
+        out: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        let subs = match fs::read_dir(dir) {
+            Ok(subs) => subs,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("could not read dir {}", dir.display()))
+            }
+        };
+        for sub in subs {
+            let sub = sub.context("could not read sub directory")?;
+            let file_type = sub
+                .file_type()
+                .context("could not read file type for sub directory")?;
+            if !file_type.is_dir() {
+                continue;
+            }
+            let path = sub.path();
+            if tracked.contains(&path) {
+                continue;
+            }
+            if path.join(".git").exists() {
+                out.push(path);
+                continue;
+            }
+            Self::scan_untracked(&path, tracked, out)?;
+        }
+        Ok(())
+    }
+}
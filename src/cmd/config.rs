@@ -11,6 +11,13 @@ use crate::util;
 
 impl Run for Config {
     fn run(&self) -> Result<()> {
+        if self.schema {
+            let schema = schemars::schema_for!(config::Config);
+            let schema =
+                serde_json::to_string_pretty(&schema).context("could not encode json schema")?;
+            println!("{}", schema);
+            return Ok(());
+        }
         let path = config::Config::get_path()?;
         match fs::read(&path) {
             Ok(_) => util::Shell::edit_file(&self.editor, &path),
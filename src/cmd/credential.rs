@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, Write};
+
+use anyhow::{Context, Result};
+
+use crate::cmd::Credential;
+use crate::cmd::Run;
+use crate::config::{self, Config};
+use crate::db::Database;
+
+impl Run for Credential {
+    fn run(&self) -> Result<()> {
+        let attrs = Self::read_attrs()?;
+        match self.operation.as_str() {
+            "get" => {}
+            // The tokens are managed in config or keyring, there is nothing to
+            // store or erase, but git still calls us after authenticating.
+            "store" | "erase" => return Ok(()),
+            // The protocol requires ignoring unknown operations, they might
+            // be added by the newer git.
+            _ => return Ok(()),
+        }
+        // Only the https urls need credential.
+        if attrs.get("protocol").map(String::as_str) != Some("https") {
+            return Ok(());
+        }
+        let host = match attrs.get("host") {
+            Some(host) => host,
+            None => return Ok(()),
+        };
+
+        let cfg = Config::parse()?;
+        let db = Database::open()?;
+        // The path is sent only with `credential.useHttpPath`, it helps to
+        // choose among the remotes sharing the host.
+        let name = attrs
+            .get("path")
+            .map(|path| path.trim_matches('/').trim_end_matches(".git"))
+            .unwrap_or_default();
+        let remote = match db.remotes_by_domain(&cfg, host, name)?[..] {
+            [remote] => remote,
+            // Do not guess when ambiguous, giving the token of another
+            // account is worse than prompting.
+            _ => return Ok(()),
+        };
+        let (api, token) = match (&remote.api, remote.get_token()) {
+            (Some(api), Ok(token)) if !token.is_empty() => (api, token),
+            // Let git fall back to other helpers or prompt.
+            _ => return Ok(()),
+        };
+        let (username, password) = match api.provider {
+            // The token of Gerrit is "<username>:<http-password>".
+            config::Provider::Gerrit => match token.split_once(':') {
+                Some((username, password)) => (username, password),
+                None => return Ok(()),
+            },
+            // Gitlab requires this username for personal access token, the
+            // others accept any non-empty username.
+            config::Provider::Gitlab => ("oauth2", token.as_str()),
+            // The token of Bitbucket is "<username>:<app-password>" or an
+            // access token, which requires this username.
+            config::Provider::Bitbucket => match token.split_once(':') {
+                Some((username, password)) => (username, password),
+                None => ("x-token-auth", token.as_str()),
+            },
+            _ => (
+                attrs
+                    .get("username")
+                    .map_or("x-access-token", String::as_str),
+                token.as_str(),
+            ),
+        };
+
+        let mut stdout = io::stdout();
+        writeln!(stdout, "username={}", username)?;
+        writeln!(stdout, "password={}", password)?;
+        Ok(())
+    }
+}
+
+impl Credential {
+    /// Read the attributes in format `key=value` from stdin, until a blank
+    /// line or EOF.
+    fn read_attrs() -> Result<HashMap<String, String>> {
+        let mut attrs = HashMap::new();
+        for line in io::stdin().lock().lines() {
+            let line = line.context("unable to read credential from stdin")?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                attrs.insert(key.to_string(), value.to_string());
+            }
+        }
+        Ok(attrs)
+    }
+}
@@ -7,12 +7,14 @@ use console::style;
 
 use crate::cmd::Detach;
 use crate::cmd::Run;
+use crate::config::Config;
 use crate::db::Database;
 use crate::util;
 
 impl Run for Detach {
     fn run(&self) -> Result<()> {
         let mut db = Database::open()?;
+        let cfg = Config::parse()?;
 
         let path = match &self.dir {
             Some(dir) => util::str_to_path(dir)?,
@@ -20,7 +22,7 @@ impl Run for Detach {
         };
 
         let path_str = util::osstr_to_str(path.as_os_str())?;
-        let idx = match db.get_by_path(path_str) {
+        let idx = match db.get_by_path(path_str, cfg.resolve_symlinks) {
             Some(idx) => idx,
             None => bail!(
                 "path {} did not bound to any repository",
@@ -0,0 +1,48 @@
+use anyhow::Result;
+
+use crate::api;
+use crate::cmd::open;
+use crate::cmd::Diff;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+use crate::util::GitRemote;
+use crate::util::Shell;
+
+impl Run for Diff {
+    fn run(&self) -> Result<()> {
+        if self.web {
+            return self.open_compare();
+        }
+
+        let remote = GitRemote::build(self.upstream)?;
+        let target = remote.target(util::option_arg(&self.args))?;
+        let range = format!("{}...HEAD", target);
+
+        let mut args = vec!["diff"];
+        if self.stat {
+            args.push("--stat");
+        }
+        args.push(range.as_str());
+        Shell::git().args(args).inherit().exec()?;
+
+        Ok(())
+    }
+}
+
+impl Diff {
+    fn open_compare(&self) -> Result<()> {
+        let mut db = Database::open()?;
+        let config = Config::parse()?;
+        let repo = db.current_or_detect(&config)?;
+        let remote = config.must_get_remote(&repo.remote)?;
+        let provider = api::create_provider(remote)?;
+
+        let target = util::option_arg(&self.args);
+        let url = open::compare_url(repo, remote, provider.as_ref(), target, self.upstream)?;
+        config.open_url(url)?;
+
+        Ok(())
+    }
+}
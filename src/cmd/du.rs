@@ -0,0 +1,166 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::Result;
+use console::style;
+use pad::PadStr;
+
+use crate::cmd::Du;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::{Database, Repo};
+use crate::output::Record;
+use crate::util;
+use crate::util::Shell;
+
+struct Usage<'a> {
+    repo: &'a Repo,
+    path: PathBuf,
+
+    worktree: u64,
+    git: u64,
+}
+
+impl Run for Du {
+    fn run(&self) -> Result<()> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+
+        let remote = self.args.first().map(String::as_str).unwrap_or_default();
+        let group = self.args.get(1).map(String::as_str).unwrap_or_default();
+        if !remote.is_empty() {
+            cfg.must_get_remote(remote)?;
+        }
+        let repos = db.filter(remote, group);
+
+        let results = util::parallel(&repos, |repo| Self::usage(repo, &cfg.workspace));
+        let mut usages = Vec::with_capacity(results.len());
+        for result in results {
+            if let Some(usage) = result? {
+                usages.push(usage);
+            }
+        }
+        if usages.is_empty() {
+            return Ok(());
+        }
+        usages.sort_by_key(|usage| Reverse(usage.worktree + usage.git));
+
+        if self.gc {
+            return Self::gc(&cfg, &usages);
+        }
+        if self.porcelain {
+            for usage in usages {
+                Record::new()
+                    .push(&usage.repo.remote)
+                    .push(&usage.repo.name)
+                    .push(usage.worktree)
+                    .push(usage.git)
+                    .print();
+            }
+            return Ok(());
+        }
+        Self::show(&usages);
+
+        Ok(())
+    }
+}
+
+impl Du {
+    /// The `.git` bigger than this will be suggested to run gc.
+    const SUGGEST_SIZE: u64 = 512 << 20;
+    const SUGGEST_COUNT: usize = 3;
+
+    fn usage<'a>(repo: &'a Repo, workspace: &str) -> Result<Option<Usage<'a>>> {
+        let path = repo.path(workspace)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let git_path = path.join(".git");
+        let total = util::dir_size(&path)?;
+        let mut git = util::dir_size(&git_path)?;
+        let worktree = total.saturating_sub(git);
+        // In mirror clone mode, ".git" is a file pointing to the mirror.
+        if git_path.is_file() {
+            git = util::dir_size(&repo.mirror_path()?)?;
+        }
+        Ok(Some(Usage {
+            repo,
+            path,
+            worktree,
+            git,
+        }))
+    }
+
+    fn show(usages: &Vec<Usage>) {
+        let pad = usages
+            .iter()
+            .map(|usage| usage.repo.remote.len() + usage.repo.name.len() + 1)
+            .max()
+            .unwrap_or_default();
+        let mut remotes: HashMap<&str, u64> = HashMap::new();
+        for usage in usages {
+            let name = format!("{}:{}", usage.repo.remote, usage.repo.name);
+            println!(
+                "{} {} (worktree {}, git {})",
+                name.pad_to_width_with_alignment(pad, pad::Alignment::Left),
+                style(util::human_bytes(usage.worktree + usage.git)).yellow(),
+                util::human_bytes(usage.worktree),
+                util::human_bytes(usage.git),
+            );
+            *remotes.entry(&usage.repo.remote).or_default() += usage.worktree + usage.git;
+        }
+
+        println!();
+        let mut remotes: Vec<_> = remotes.into_iter().collect();
+        remotes.sort_by_key(|(_, size)| Reverse(*size));
+        for (remote, size) in remotes {
+            println!(
+                "Remote {}: {}",
+                style(remote).cyan(),
+                style(util::human_bytes(size)).yellow()
+            );
+        }
+
+        let mut largest: Vec<&Usage> = usages
+            .iter()
+            .filter(|usage| usage.git >= Self::SUGGEST_SIZE)
+            .collect();
+        if largest.is_empty() {
+            return;
+        }
+        largest.sort_by_key(|usage| Reverse(usage.git));
+        println!();
+        println!("The following repos have large git data:");
+        for usage in largest.iter().take(Self::SUGGEST_COUNT) {
+            println!(
+                "  * {} ({})",
+                usage.repo.name,
+                style(util::human_bytes(usage.git)).yellow()
+            );
+        }
+        println!(
+            "Consider running {} for them, or re-clone them as partial clone with {}",
+            style("gz du --gc").cyan(),
+            style("git clone --filter=blob:none").cyan()
+        );
+    }
+
+    fn gc(cfg: &Config, usages: &Vec<Usage>) -> Result<()> {
+        util::confirm(format!(
+            "do you want to run gc for {} repos",
+            style(usages.len()).yellow()
+        ))?;
+        let start = Instant::now();
+        for usage in usages {
+            let path = util::path_to_str(&usage.path)?;
+            Shell::git()
+                .with_git_path(path)
+                .args(["gc", "--aggressive", "--prune=now"])
+                .exec()?;
+        }
+        cfg.notify_done(start, "gc is done");
+        Ok(())
+    }
+}
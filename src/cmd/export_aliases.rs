@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::cmd::ExportAliases;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+use crate::util::Shell;
+
+impl Run for ExportAliases {
+    fn run(&self) -> Result<()> {
+        let mut db = Database::open()?;
+        let cfg = Config::parse()?;
+        let now = util::current_time()?;
+        db.sort_by_rank(now, self.rank.unwrap_or(cfg.rank));
+
+        let top = self.top.unwrap_or(Self::DEFAULT_TOP);
+        let prefix = self.prefix.as_deref().unwrap_or_default();
+        let mut names: HashSet<String> = HashSet::new();
+        let mut count = 0;
+        for repo in db.repos.iter() {
+            if count >= top {
+                break;
+            }
+            // The alias of a repo not cloned yet would be broken.
+            let path = repo.path(&cfg.workspace)?;
+            if !path.exists() {
+                continue;
+            }
+            let (_, base) = util::split_name(&repo.name);
+            let full_name = format!("{}/{}", repo.remote, repo.name);
+            let name = [base.as_str(), repo.name.as_str(), full_name.as_str()]
+                .into_iter()
+                .map(|name| format!("{}{}", prefix, Self::sanitize(name)))
+                .find(|name| !names.contains(name) && Self::usable(name));
+            let name = match name {
+                Some(name) => name,
+                None => continue,
+            };
+            let cd = format!("cd {}", util::shell_quote(util::path_to_str(&path)?));
+            println!("alias {}={}", name, util::shell_quote(&cd));
+            names.insert(name);
+            count += 1;
+        }
+        Ok(())
+    }
+}
+
+impl ExportAliases {
+    const DEFAULT_TOP: usize = 20;
+
+    /// The alias cannot start with "-" (parsed as an option of `alias`), and
+    /// should not shadow an existing command, such as a repo named "git".
+    fn usable(name: &str) -> bool {
+        !name.starts_with('-') && !Shell::cmd_exists(name)
+    }
+
+    /// The shells only accept limited characters in alias names, replace
+    /// the others, such as "/" in the full name, with "-".
+    fn sanitize(name: &str) -> String {
+        name.chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' => c,
+                _ => '-',
+            })
+            .collect()
+    }
+}
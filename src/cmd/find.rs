@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+
+use crate::cmd::Find;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+use crate::util::Shell;
+
+impl Run for Find {
+    fn run(&self) -> Result<()> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+
+        let remote = self.args.first().map(String::as_str).unwrap_or_default();
+        let group = self.args.get(1).map(String::as_str).unwrap_or_default();
+        if !remote.is_empty() {
+            cfg.must_get_remote(remote)?;
+        }
+        let repos = db.filter(remote, group);
+
+        let results = util::parallel(&repos, |repo| {
+            let path = repo.path(&cfg.workspace)?;
+            if !path.exists() {
+                return Ok(vec![]);
+            }
+            let output = Shell::git()
+                .mute()
+                .arg("ls-files")
+                .with_path(&path)
+                .exec()?;
+            let files: Vec<PathBuf> = output
+                .lines()
+                .filter(|file| self.is_match(file))
+                .map(|file| path.join(file))
+                .collect();
+            Ok::<_, anyhow::Error>(files)
+        });
+
+        let mut files: Vec<String> = vec![];
+        for result in results {
+            for file in result? {
+                files.push(format!("{}", file.display()));
+            }
+        }
+        if !self.select {
+            for file in files {
+                println!("{}", file);
+            }
+            return Ok(());
+        }
+        if files.is_empty() {
+            bail!("no file found")
+        }
+
+        let mut fzf = util::Fzf::build(&[])?;
+        let idx = fzf.query(&files)?;
+        println!("{}", files[idx]);
+
+        Ok(())
+    }
+}
+
+impl Find {
+    fn is_match(&self, file: &str) -> bool {
+        if self.glob.contains('/') {
+            return util::glob_match(&self.glob, file);
+        }
+        let name = match file.rsplit_once('/') {
+            Some((_, name)) => name,
+            None => file,
+        };
+        util::glob_match(&self.glob, name)
+    }
+}
@@ -0,0 +1,95 @@
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+
+use crate::cmd::Grep;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+use crate::util::Shell;
+
+impl Run for Grep {
+    fn run(&self) -> Result<()> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+
+        let remote = self.args.first().map(String::as_str).unwrap_or_default();
+        let group = self.args.get(1).map(String::as_str).unwrap_or_default();
+        if !remote.is_empty() {
+            cfg.must_get_remote(remote)?;
+        }
+        let repos = db.filter(remote, group);
+
+        let use_rg = Shell::cmd_exists("rg");
+        let results = util::parallel(&repos, |repo| {
+            let path = repo.path(&cfg.workspace)?;
+            if !path.exists() {
+                return Ok(vec![]);
+            }
+            let mut cmd = match use_rg {
+                true => {
+                    let mut rg = Command::new("rg");
+                    rg.args(["--line-number", "--no-heading", "--color", "never"]);
+                    rg
+                }
+                false => {
+                    let mut git = Command::new("git");
+                    git.args(["grep", "--line-number", "-I"]);
+                    git
+                }
+            };
+            let output = cmd
+                .arg("-e")
+                .arg(&self.pattern)
+                .current_dir(&path)
+                .stdin(Stdio::null())
+                .output()
+                .context("could not launch grep command")?;
+            // Both rg and git grep exit with 1 if nothing matched, other
+            // codes mean that the search failed.
+            match output.status.code() {
+                Some(0) => {}
+                Some(1) => return Ok(vec![]),
+                _ => bail!(
+                    "could not grep {}: {}",
+                    style(&repo.name).yellow(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            }
+            let output = String::from_utf8_lossy(&output.stdout);
+            let hits: Vec<String> = output
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(|line| format!("{}:{}", repo.name, line))
+                .collect();
+            Ok::<_, anyhow::Error>(hits)
+        });
+
+        let mut hits: Vec<(usize, String)> = vec![];
+        for (idx, result) in results.into_iter().enumerate() {
+            for hit in result? {
+                if !self.open {
+                    println!("{}", hit);
+                    continue;
+                }
+                hits.push((idx, hit));
+            }
+        }
+        if !self.open {
+            return Ok(());
+        }
+        if hits.is_empty() {
+            bail!("no result found")
+        }
+
+        let keys: Vec<&str> = hits.iter().map(|(_, hit)| hit.as_str()).collect();
+        let mut fzf = util::Fzf::build(&[])?;
+        let (idx, _) = hits[fzf.query(&keys)?];
+        let path = repos[idx].path(&cfg.workspace)?;
+        println!("{}", path.display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use std::io;
+use std::io::Write;
+
+use anyhow::Result;
+use console::style;
+
+use crate::cmd::History;
+use crate::cmd::Run;
+use crate::db::{self, Epoch, HistoryEntry};
+use crate::output::Record;
+use crate::util;
+
+impl Run for History {
+    fn run(&self) -> Result<()> {
+        let now = util::current_time()?;
+        let since = match &self.since {
+            Some(since) => now.saturating_sub(util::parse_duration(since)?),
+            None => 0,
+        };
+        let remote = self.args.first().map(String::as_str).unwrap_or_default();
+        let keyword = self.args.get(1).map(String::as_str).unwrap_or_default();
+
+        let mut entries: Vec<HistoryEntry> = db::History::read()?
+            .into_iter()
+            .filter(|entry| entry.time >= since)
+            .filter(|entry| match &self.action {
+                Some(action) => &entry.action == action,
+                None => true,
+            })
+            .filter(|entry| remote.is_empty() || entry.remote == remote)
+            .filter(|entry| keyword.is_empty() || entry.name.contains(keyword))
+            .collect();
+        // The log is appended in time order, show the newest first.
+        entries.reverse();
+        let limit = self.limit.unwrap_or(Self::DEFAULT_LIMIT);
+        if limit > 0 {
+            entries.truncate(limit);
+        }
+
+        if self.porcelain {
+            for entry in entries {
+                Record::new()
+                    .push(entry.time)
+                    .push(&entry.action)
+                    .push(&entry.remote)
+                    .push(&entry.name)
+                    .push(&entry.path)
+                    .push(&entry.detail)
+                    .print();
+            }
+            return Ok(());
+        }
+        if entries.is_empty() {
+            _ = writeln!(io::stderr(), "no history");
+            return Ok(());
+        }
+        for entry in entries {
+            Self::show(&entry, now);
+        }
+        Ok(())
+    }
+}
+
+impl History {
+    const DEFAULT_LIMIT: usize = 50;
+
+    /// Print the entry in format `<time> <action> <target> <detail>`, the
+    /// target is the repo, or the path if the operation is not bound to a
+    /// repo.
+    fn show(entry: &HistoryEntry, now: Epoch) {
+        let target = if entry.name.is_empty() {
+            entry.path.clone()
+        } else {
+            format!("{}:{}", entry.remote, entry.name)
+        };
+        let mut line = format!(
+            "{} {} {}",
+            style(util::format_since(now, entry.time)).dim(),
+            style(&entry.action).green(),
+            style(target).yellow()
+        );
+        if !entry.detail.is_empty() {
+            line.push(' ');
+            line.push_str(&entry.detail);
+        }
+        println!("{}", line);
+    }
+}
@@ -1,38 +1,54 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
 use anyhow::bail;
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::offset::Local;
+use chrono::Datelike;
 use console::style;
 
 use crate::api;
+use crate::cmd::jump;
 use crate::cmd::Home;
 use crate::cmd::Run;
-use crate::config::{Config, Remote};
+use crate::config::{Config, RankMode, Remote};
 use crate::db::Database;
-use crate::db::Epoch;
 use crate::db::Keywords;
+use crate::db::{Epoch, Repo};
 use crate::util;
+use crate::util::Shell;
 
 impl Run for Home {
     fn run(&self) -> Result<()> {
         let mut db = Database::open()?;
         let cfg = Config::parse()?;
         let now = util::current_time()?;
+        let rank = self.rank.unwrap_or(cfg.rank);
+        if rank != RankMode::Alltime {
+            db.sort_by_rank(now, rank);
+        }
+        if cfg.session_boost {
+            db.boost_session();
+        }
 
-        let (remote, repo_idx) = self.query(&mut db, &cfg, now)?;
-        let repo = &db.repos[repo_idx];
-
-        let path = repo.ensure_path(&cfg.workspace, remote)?;
-        db.update(repo_idx, now);
-
-        println!("{}", path.display());
-
-        db.sort(now);
-        db.save()?;
+        let count = db.repos.len();
+        let (_, repo_idx) = self.query(&mut db, &cfg, now)?;
+        if repo_idx >= count {
+            db.resolve_collision(repo_idx, &cfg)?;
+        }
+        jump::enter(&mut db, &cfg, repo_idx, now, |repo, path| {
+            self.generate_files(&cfg, repo, path)
+        })?;
 
         Ok(())
     }
 }
 
 impl Home {
+    const MIT_LICENSE: &str = include_str!("../../files/license_mit.txt");
+    const README: &str = "# {name}\n";
+
     fn query<'a>(
         &self,
         db: &mut Database,
@@ -58,10 +74,17 @@ impl Home {
         if self.args.len() == 1 {
             let arg = &self.args[0];
             match cfg.get_remote(arg.as_str()) {
-                Some(remote) => return Ok((remote, self.search_repo(db, arg, "")?)),
+                Some(remote) => return Ok((remote, self.search_repo(db, cfg, arg, "")?)),
                 None => {
-                    let idx = db.match_keyword("", arg, &cfg.keyword_map)?;
-                    if let None = cfg.keyword_map.get(arg) {
+                    let idx = db.match_keyword_bound(
+                        "",
+                        arg,
+                        &cfg.keyword_map,
+                        self.interactive,
+                        self.deep,
+                    )?;
+                    // The deep keyword is a free text, not worth completing.
+                    if !cfg.keyword_map.contains_key(arg) && !self.deep {
                         // Store keyword in database to make completion next time
                         let mut keyword_db = Keywords::open(now)?;
                         keyword_db.add(&arg, now);
@@ -75,21 +98,30 @@ impl Home {
 
         let remote_name = &self.args[0];
         let remote = cfg.must_get_remote(remote_name)?;
-        let name = &self.args[1];
+        let name = &cfg.map_group(&self.args[1]);
 
         if name.ends_with("/") {
             let name = name.trim_end_matches("/");
+            if self.all {
+                return Ok((remote, self.search_repo_all(db, remote, name)?));
+            }
             if self.search {
                 return Ok((remote, self.search_repo_remote(db, remote, name)?));
             }
-            return Ok((remote, self.search_repo(db, remote_name, name)?));
+            return Ok((remote, self.search_repo(db, cfg, remote_name, name)?));
         }
 
         if let Some(idx) = db.get(&remote.name, name) {
             return Ok((remote, idx));
         }
         if !self.create {
-            if let Ok(idx) = db.match_keyword(remote_name, name, &cfg.keyword_map) {
+            if let Ok(idx) = db.match_keyword_bound(
+                remote_name,
+                name,
+                &cfg.keyword_map,
+                self.interactive,
+                self.deep,
+            ) {
                 return Ok((remote, idx));
             }
         }
@@ -97,18 +129,33 @@ impl Home {
         Ok((remote, self.create_repo(db, remote_name, name)?))
     }
 
-    fn search_repo<R, Q>(&self, db: &Database, remote: R, query: Q) -> Result<usize>
+    fn search_repo<R, Q>(&self, db: &Database, cfg: &Config, remote: R, query: Q) -> Result<usize>
     where
         R: AsRef<str>,
         Q: AsRef<str>,
     {
+        // The query may end with a partial base name, such as "owner/rep",
+        // in this case, search the group and use the partial base name as the
+        // initial query of fzf.
+        let query = query.as_ref();
+        let group_prefix = format!("{}/", query);
+        let is_group = query.is_empty()
+            || db
+                .repos
+                .iter()
+                .any(|repo| repo.remote == remote.as_ref() && repo.name.starts_with(&group_prefix));
+        let (prefix, partial) = match is_group {
+            true => (query.to_string(), String::new()),
+            false => util::split_name(query),
+        };
+
         let mut items: Vec<usize> = Vec::with_capacity(db.repos.len());
         let mut keys: Vec<&str> = Vec::with_capacity(db.repos.len());
         for (idx, repo) in db.repos.iter().enumerate() {
             if repo.remote != remote.as_ref() {
                 continue;
             }
-            let key = match repo.name.strip_prefix(query.as_ref()) {
+            let key = match repo.name.strip_prefix(&prefix) {
                 Some(s) => s.trim_matches('/'),
                 None => continue,
             };
@@ -120,13 +167,34 @@ impl Home {
         }
 
         if items.is_empty() {
-            bail!(
-                "no matches repository with query {}",
-                style(query.as_ref()).yellow()
-            )
+            bail!("no matches repository with query {}", style(query).yellow())
+        }
+        if items.len() == 1 {
+            return Ok(items[0]);
         }
 
-        let mut fzf = util::Fzf::build()?;
+        let mut args = vec![];
+        if !partial.is_empty() {
+            args.extend(["--query", partial.as_str(), "--select-1"]);
+        }
+        // Preview the cached status and metadata with `list --long`, the fzf replaces
+        // "{}" with the quoted key.
+        let preview;
+        if cfg.status_cache || cfg.repo_meta {
+            let exe = env::current_exe().context("could not get current executable")?;
+            let prefix = match prefix.is_empty() {
+                true => String::new(),
+                false => format!("{}/", prefix),
+            };
+            preview = format!(
+                "'{}' list '{}' {}{{}} --long",
+                exe.display(),
+                remote.as_ref(),
+                prefix
+            );
+            args.extend(["--preview", preview.as_str(), "--preview-window", "down,3"]);
+        }
+        let mut fzf = util::Fzf::build(&args)?;
         Ok(items[fzf.query(&keys)?])
     }
 
@@ -152,7 +220,7 @@ impl Home {
             keys.push(key);
         }
 
-        let mut fzf = util::Fzf::build()?;
+        let mut fzf = util::Fzf::build(&[])?;
         let idx = fzf.query(&keys)?;
 
         let repo_name = &repo_names[idx];
@@ -162,6 +230,136 @@ impl Home {
         self.create_repo(db, &remote.name, repo_name)
     }
 
+    /// Search the local repos and the provider together, the local repos are
+    /// listed first, followed by the provider repos not in database.
+    fn search_repo_all(&self, db: &mut Database, remote: &Remote, query: &str) -> Result<usize> {
+        let provider = api::create_provider(remote)?;
+        util::print_operation(format!("provider: list repo for {}", style(query).yellow()));
+        let remote_names = provider.list(query)?;
+
+        let prefix = format!("{}/", query);
+        let mut local: Vec<(usize, String)> = vec![];
+        for (idx, repo) in db.repos.iter().enumerate() {
+            if repo.remote != remote.name {
+                continue;
+            }
+            if let Some(key) = repo.name.strip_prefix(&prefix) {
+                local.push((idx, key.to_string()));
+            }
+        }
+        let remote_names: Vec<&String> = remote_names
+            .iter()
+            .filter(|name| db.get(&remote.name, name).is_none())
+            .collect();
+
+        let mut keys = Vec::with_capacity(local.len() + remote_names.len());
+        for (_, key) in &local {
+            keys.push(key.clone());
+        }
+        for name in &remote_names {
+            let key = name.strip_prefix(&prefix).unwrap_or(name);
+            keys.push(format!("{} (remote)", key));
+        }
+        if keys.is_empty() {
+            bail!("no matches repository with query {}", style(query).yellow())
+        }
+
+        let mut fzf = util::Fzf::build(&[])?;
+        let idx = fzf.query(&keys)?;
+        match local.get(idx) {
+            Some((idx, _)) => Ok(*idx),
+            None => self.create_repo(db, &remote.name, remote_names[idx - local.len()]),
+        }
+    }
+
+    /// Generate LICENSE and README.md for the created repo and make the
+    /// initial commit.
+    fn generate_files(&self, cfg: &Config, repo: &Repo, path: &PathBuf) -> Result<()> {
+        if self.license.is_none() && !self.readme {
+            return Ok(());
+        }
+        let path_str = util::path_to_str(path)?;
+        let has_commit = Shell::git()
+            .mute()
+            .with_git_path(path_str)
+            .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+            .exec()
+            .is_ok();
+        if has_commit {
+            bail!(
+                "repository {} already has commits, skip generating files",
+                style(&repo.name).yellow()
+            )
+        }
+
+        let (_, base) = util::split_name(&repo.name);
+        let year = Local::now().year().to_string();
+        // The ident is in format "<name> <<email>> <time> <tz>".
+        let ident = Shell::git()
+            .mute()
+            .with_git_path(path_str)
+            .args(["var", "GIT_AUTHOR_IDENT"])
+            .exec()
+            .unwrap_or_default();
+        let author = match ident.split_once(" <") {
+            Some((name, _)) => name.to_string(),
+            None => String::new(),
+        };
+        let values = [
+            ("name", base.as_str()),
+            ("full_name", repo.name.as_str()),
+            ("year", year.as_str()),
+            ("author", author.as_str()),
+        ];
+
+        let mut files = vec![];
+        if let Some(license) = &self.license {
+            let file = cfg
+                .templates
+                .as_ref()
+                .and_then(|templates| templates.licenses.get(license));
+            let template = match file {
+                Some(file) => Self::read_template(file)?,
+                None if license == "mit" => String::from(Self::MIT_LICENSE),
+                None => bail!(
+                    "could not find license {}, please add it to templates.licenses in config",
+                    style(license).yellow()
+                ),
+            };
+            files.push(("LICENSE", template));
+        }
+        if self.readme {
+            let template = match cfg.templates.as_ref() {
+                Some(templates) if !templates.readme.is_empty() => {
+                    Self::read_template(&templates.readme)?
+                }
+                _ => String::from(Self::README),
+            };
+            files.push(("README.md", template));
+        }
+
+        for (name, template) in &files {
+            util::print_operation(format!("create {} ...", style(name).yellow()));
+            let content = util::render_template(template, &values);
+            util::write(path.join(name), content)?;
+        }
+        Shell::git()
+            .with_git_path(path_str)
+            .arg("add")
+            .args(files.iter().map(|(name, _)| *name))
+            .exec()?;
+        Shell::git()
+            .with_git_path(path_str)
+            .args(["commit", "-m", "Initial commit"])
+            .exec()?;
+        Ok(())
+    }
+
+    fn read_template(path: &str) -> Result<String> {
+        fs::read_to_string(path)
+            .with_context(|| format!("could not read template {}", style(path).yellow()))
+    }
+
     fn create_repo<R, N>(&self, db: &mut Database, remote: R, name: N) -> Result<usize>
     where
         R: AsRef<str>,
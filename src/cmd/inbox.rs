@@ -0,0 +1,106 @@
+use std::io;
+use std::io::Write;
+
+use anyhow::Result;
+use console::style;
+
+use crate::api::{self, InboxItem, Provider};
+use crate::cmd::mine;
+use crate::cmd::Inbox;
+use crate::cmd::Run;
+use crate::config::{Config, Remote};
+use crate::db::Epoch;
+use crate::util;
+
+impl Run for Inbox {
+    fn run(&self) -> Result<()> {
+        let cfg = Config::parse()?;
+        let now = util::current_time()?;
+
+        let mut items = mine::query_remotes(&cfg, &self.args, "notifications", |provider| {
+            provider.list_inbox()
+        })?;
+        if items.is_empty() {
+            _ = writeln!(io::stderr(), "inbox is empty");
+            return Ok(());
+        }
+        // Group by repo, the latest first.
+        items.sort_by(|(remote1, item1), (remote2, item2)| {
+            (&remote1.name, &item1.repo, item2.updated).cmp(&(
+                &remote2.name,
+                &item2.repo,
+                item1.updated,
+            ))
+        });
+        if !self.open && !self.read {
+            Self::show(&items, now);
+            return Ok(());
+        }
+
+        if self.all {
+            util::confirm(format!(
+                "do you want to mark {} notifications as read",
+                items.len()
+            ))?;
+            // The items are sorted by remote, so that the provider can be
+            // reused.
+            let mut last: Option<(&str, Box<dyn Provider>)> = None;
+            for (remote, item) in items.iter() {
+                let provider = match last {
+                    Some((name, provider)) if name == remote.name => provider,
+                    _ => api::create_provider(remote)?,
+                };
+                Self::mark_read(provider.as_ref(), item)?;
+                last = Some((remote.name.as_str(), provider));
+            }
+            return Ok(());
+        }
+
+        let keys: Vec<String> = items
+            .iter()
+            .map(|(remote, item)| {
+                format!(
+                    "{}:{} [{}] {}",
+                    remote.name, item.repo, item.kind, item.title
+                )
+            })
+            .collect();
+        let mut fzf = util::Fzf::build(&[])?;
+        let (remote, item) = &items[fzf.query(&keys)?];
+        if self.open {
+            cfg.open_url(&item.url)?;
+        }
+        let provider = api::create_provider(remote)?;
+        Self::mark_read(provider.as_ref(), item)
+    }
+}
+
+impl Inbox {
+    /// Print notifications grouped by repo, in format
+    /// `<kind> <title> <reason> <updated>`.
+    fn show(items: &[(&Remote, InboxItem)], now: Epoch) {
+        let mut last: Option<(&str, &str)> = None;
+        for (remote, item) in items {
+            let key = (remote.name.as_str(), item.repo.as_str());
+            if last != Some(key) {
+                println!("{}:{}", style(key.0).yellow(), style(key.1).yellow());
+                last = Some(key);
+            }
+            println!(
+                "  {} {} {} {}",
+                style(&item.kind).cyan(),
+                item.title,
+                style(&item.reason).magenta(),
+                style(util::format_since(now, item.updated)).dim()
+            );
+        }
+    }
+
+    fn mark_read(provider: &dyn Provider, item: &InboxItem) -> Result<()> {
+        util::print_operation(format!(
+            "provider: mark {} as read",
+            style(&item.title).yellow()
+        ));
+        provider.mark_inbox_read(&item.id)
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use console::style;
+
+use crate::cmd::Info;
+use crate::cmd::Run;
+use crate::config::{Config, Remote};
+use crate::db::Database;
+use crate::util::{self, Shell};
+
+impl Run for Info {
+    fn run(&self) -> Result<()> {
+        let mut db = Database::open()?;
+        let cfg = Config::parse()?;
+        let now = util::current_time()?;
+
+        // Rank among all repos in the same way as jumping.
+        db.sort_by_rank(now, cfg.rank);
+        let idx = db.current_index(&cfg)?;
+        let repo = &db.repos[idx];
+        let remote = cfg.must_get_remote(&repo.remote)?;
+        let path = repo.path(&cfg.workspace)?;
+        let path_str = util::path_to_str(&path)?;
+
+        let clone_url = match &remote.clone {
+            Some(clone) => repo.clone_url(clone),
+            None => String::new(),
+        };
+        let git_config = |key: &str| {
+            Shell::git()
+                .mute()
+                .with_git_path(path_str)
+                .args(["config", "--get", key])
+                .exec()
+                .unwrap_or_default()
+        };
+        let default_branch = Shell::git()
+            .mute()
+            .with_git_path(path_str)
+            .args(["symbolic-ref", "--short", "refs/remotes/origin/HEAD"])
+            .exec()
+            .map(|head| head.trim_start_matches("origin/").to_string())
+            .unwrap_or_default();
+        let labels = cfg
+            .repos
+            .iter()
+            .find(|declared| declared.remote == repo.remote && declared.name == repo.name)
+            .map(|declared| declared.labels.join(", "))
+            .unwrap_or_default();
+        let score = format!(
+            "{:.2} (rank {} of {}, accessed {} times, {})",
+            repo.rank(now, cfg.rank),
+            idx + 1,
+            db.repos.len(),
+            repo.accessed,
+            util::format_since(now, repo.last_accessed)
+        );
+
+        Self::show("remote", &repo.remote);
+        Self::show("name", &repo.name);
+        Self::show("path", path_str);
+        Self::show("clone", &clone_url);
+        Self::show(
+            "user",
+            &Self::user(remote, &git_config("user.name"), &git_config("user.email")),
+        );
+        Self::show("default", &default_branch);
+        Self::show("upstream", &git_config("remote.upstream.url"));
+        Self::show("score", &score);
+        Self::show("labels", &labels);
+        if let Some(meta) = &repo.meta {
+            Self::show("meta", &meta.summary());
+        }
+        Ok(())
+    }
+}
+
+impl Info {
+    /// The width of the field names, for aligning the values.
+    const NAME_WIDTH: usize = 9;
+
+    fn show(name: &str, value: &str) {
+        let value = match value.is_empty() {
+            true => style("<none>").dim().to_string(),
+            false => value.to_string(),
+        };
+        println!(
+            "{} {}",
+            style(format!("{:width$}", name, width = Self::NAME_WIDTH)).green(),
+            value
+        );
+    }
+
+    /// The identity used by git in the repo, the one in remote config is
+    /// shown too if they differ, which means the config was changed after
+    /// the repo was cloned or the repo config was overridden.
+    fn user(remote: &Remote, name: &str, email: &str) -> String {
+        if name.is_empty() && email.is_empty() {
+            return String::new();
+        }
+        let user = format!("{} <{}>", name, email);
+        match &remote.user {
+            Some(expect) if expect.name != name || expect.email != email => format!(
+                "{} {}",
+                user,
+                style(format!(
+                    "(remote config: {} <{}>)",
+                    expect.name, expect.email
+                ))
+                .yellow()
+            ),
+            _ => user,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::io;
+use std::io::Write;
+
+use anyhow::Result;
+use console::style;
+
+use crate::api::{self, Provider};
+use crate::cmd::Issue;
+use crate::cmd::Merge;
+use crate::cmd::Pr;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+
+impl Run for Issue {
+    fn run(&self) -> Result<()> {
+        let mut db = Database::open()?;
+        let cfg = Config::parse()?;
+        let repo = db.current_or_detect(&cfg)?;
+        let remote = cfg.must_get_remote(&repo.remote)?;
+        let provider = api::create_provider(remote)?;
+        let target = Pr::target(provider.as_ref(), &repo.name, self.upstream)?;
+
+        if let Some(number) = self.open {
+            util::print_operation(format!(
+                "provider: get issue {}#{}",
+                style(&target).yellow(),
+                number
+            ));
+            let url = provider.get_issue(&target, number)?;
+            return cfg.open_url(&url);
+        }
+        if self.create {
+            let url = Self::create(provider.as_ref(), &target)?;
+            return cfg.open_url(&url);
+        }
+        Self::list(provider.as_ref(), &target)
+    }
+}
+
+impl Issue {
+    fn list(provider: &dyn Provider, target: &str) -> Result<()> {
+        let now = util::current_time()?;
+        util::print_operation(format!(
+            "provider: list issues for {}",
+            style(target).yellow()
+        ));
+        let issues = provider.list_issues(target)?;
+        if issues.is_empty() {
+            _ = writeln!(io::stderr(), "no open issue");
+            return Ok(());
+        }
+        for issue in issues.iter() {
+            let author = match issue.author.is_empty() {
+                true => String::new(),
+                false => format!(" {}", style(format!("@{}", issue.author)).cyan()),
+            };
+            let labels = match issue.labels.is_empty() {
+                true => String::new(),
+                false => format!(" {}", style(issue.labels.join(",")).magenta()),
+            };
+            println!(
+                "#{} {}{}{} {}",
+                issue.number,
+                issue.title,
+                author,
+                labels,
+                style(util::format_since(now, issue.created)).dim()
+            );
+        }
+        Ok(())
+    }
+
+    fn create(provider: &dyn Provider, target: &str) -> Result<String> {
+        let template = include_bytes!("../../files/issue.md");
+        let template = String::from_utf8_lossy(template);
+        let (title, body) = Merge::input(template.as_ref(), "issue")?;
+
+        println!();
+        println!("Ready to create issue in {}", style(target).yellow());
+        println!("Title: {}", style(&title).yellow());
+        if !body.is_empty() {
+            println!("Body:");
+            println!("{}", style(&body).dim());
+        }
+        println!();
+
+        util::confirm("continue")?;
+        println!();
+
+        util::print_operation(format!("provider: create issue {}", style(&title).yellow()));
+        provider.create_issue(target, &title, &body)
+    }
+}
@@ -1,10 +1,17 @@
+use std::path::PathBuf;
+use std::time::Instant;
+
 use anyhow::Result;
 
+use crate::cmd::archive;
+use crate::cmd::refresh_meta;
+use crate::cmd::wip;
 use crate::cmd::Jump;
 use crate::cmd::Run;
-use crate::config::Config;
-use crate::db::Database;
+use crate::config::{Config, RankMode, RepoConfig};
 use crate::db::Keywords;
+use crate::db::StatusCache;
+use crate::db::{Database, Epoch, Repo};
 use crate::util;
 
 impl Run for Jump {
@@ -13,25 +20,64 @@ impl Run for Jump {
         let mut db = Database::open()?;
         let mut keywords = Keywords::open(now)?;
         let config = Config::parse()?;
+        let rank = self.rank.unwrap_or(config.rank);
+        if rank != RankMode::Alltime {
+            db.sort_by_rank(now, rank);
+        }
+        if config.session_boost {
+            db.boost_session();
+        }
 
-        let idx = db.match_keyword("", &self.keyword, &config.keyword_map)?;
-        let repo = &db.repos[idx];
-
-        let remote = config.must_get_remote(&repo.remote)?;
-        let path = repo.ensure_path(&config.workspace, &remote)?;
-        println!("{}", path.display());
+        let idx = db.match_keyword_bound(
+            "",
+            &self.keyword,
+            &config.keyword_map,
+            self.interactive,
+            self.deep,
+        )?;
+        let (_, name) = util::split_name(&db.repos[idx].name);
+        let save_keyword = !name.eq(&self.keyword)
+            && !self.deep
+            && !config.keyword_map.contains_key(&self.keyword);
+        enter(&mut db, &config, idx, now, |_, _| Ok(()))?;
 
-        let (_, name) = util::split_name(&repo.name);
-        if !name.eq(&self.keyword) {
-            if let None = config.keyword_map.get(&self.keyword) {
-                keywords.add(&self.keyword, now);
-                keywords.save()?;
-            }
+        if save_keyword {
+            keywords.add(&self.keyword, now);
+            keywords.save()?;
         }
 
-        db.sort(now);
-        db.save()?;
-
         Ok(())
     }
 }
+
+/// Jump to the repo `idx`, shared by the commands entering a repo. The wip
+/// changes are saved and restored, the repo is restored from archive or
+/// cloned if needed, then the path is output and the database is saved.
+/// The `prepare` runs in the ready repo before the `on_enter` script, such
+/// as generating files or checking out a branch.
+pub fn enter<F>(db: &mut Database, cfg: &Config, idx: usize, now: Epoch, prepare: F) -> Result<()>
+where
+    F: FnOnce(&Repo, &PathBuf) -> Result<()>,
+{
+    wip::save_on_jump(db, cfg, idx)?;
+    let repo = &db.repos[idx];
+
+    let remote = cfg.must_get_remote(&repo.remote)?;
+    archive::restore_on_jump(cfg, repo, remote)?;
+    let start = Instant::now();
+    let path = repo.ensure_path(&cfg.workspace, remote)?;
+    cfg.notify_done(start, format!("{} is ready", repo.name));
+    prepare(repo, &path)?;
+    wip::restore_on_jump(cfg, &path)?;
+    RepoConfig::run_on_enter(remote, &repo.name, &path)?;
+    StatusCache::refresh_on_jump(db, cfg, idx, now)?;
+    util::output_repo_path(cfg, repo, &path)?;
+
+    db.update(idx, now);
+    let (remote, name) = (db.repos[idx].remote.clone(), db.repos[idx].name.clone());
+    db.sort(now);
+    db.save()?;
+    refresh_meta::refresh_on_jump(db, cfg, &remote, &name, now);
+
+    Ok(())
+}
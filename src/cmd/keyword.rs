@@ -0,0 +1,116 @@
+use std::cmp::Reverse;
+use std::io::{self, Write};
+
+use anyhow::{bail, Result};
+use console::style;
+
+use crate::cmd::Run;
+use crate::cmd::{Keyword, KeywordAction};
+use crate::config::Config;
+use crate::db::{Bindings, Database};
+use crate::util;
+
+impl Run for Keyword {
+    fn run(&self) -> Result<()> {
+        let mut bindings = Bindings::open()?;
+        match &self.action {
+            KeywordAction::List => {
+                let mut keywords: Vec<_> = bindings.data.repos.iter().collect();
+                keywords.sort_by_key(|(keyword, _)| *keyword);
+                for (keyword, (remote, name)) in keywords {
+                    println!("{} -> {}:{}", keyword, remote, name);
+                }
+                Ok(())
+            }
+            KeywordAction::Unbind { keyword } => {
+                if bindings.unbind(keyword).is_none() {
+                    bail!("keyword {} is not bound", style(keyword).yellow())
+                }
+                bindings.save()?;
+                _ = writeln!(io::stderr(), "{} unbound", style(keyword).yellow());
+                Ok(())
+            }
+            KeywordAction::Promote { min } => {
+                Self::promote(&mut bindings, min.unwrap_or(Self::DEFAULT_PROMOTE_MIN))
+            }
+        }
+    }
+}
+
+impl Keyword {
+    const DEFAULT_PROMOTE_MIN: u32 = 3;
+
+    fn promote(bindings: &mut Bindings, min: u32) -> Result<()> {
+        let cfg = Config::parse()?;
+        let db = Database::open()?;
+
+        let mut candidates: Vec<(&String, u32)> = bindings
+            .data
+            .hits
+            .iter()
+            .filter(|(keyword, hits)| **hits >= min && !cfg.keyword_map.contains_key(*keyword))
+            .map(|(keyword, hits)| (keyword, *hits))
+            .collect();
+        candidates.sort_by_key(|(keyword, hits)| (Reverse(*hits), *keyword));
+        if candidates.is_empty() {
+            _ = writeln!(
+                io::stderr(),
+                "no bound keyword is used at least {} times",
+                min
+            );
+            return Ok(());
+        }
+
+        let mut entries = vec![];
+        for (keyword, hits) in candidates {
+            let (remote, name) = match bindings.data.repos.get(keyword) {
+                Some(bound) => bound,
+                None => continue,
+            };
+            // The keyword map replaces the keyword with the repo name, which
+            // must match the bound repo first, otherwise the jump changes.
+            let matched = db.match_keyword_all("", name).first().copied();
+            if matched.is_none() || matched != db.get(remote, name) {
+                _ = writeln!(
+                    io::stderr(),
+                    "{} skip {}, the name of {}:{} matches other repos first",
+                    util::Palette::get().warning.apply_to("warning"),
+                    style(keyword).yellow(),
+                    remote,
+                    name
+                );
+                continue;
+            }
+            println!(
+                "{} -> {}:{} ({} uses)",
+                style(keyword).yellow(),
+                remote,
+                name,
+                hits
+            );
+            if util::ask(format!(
+                "promote {} to keyword_map",
+                style(keyword).yellow()
+            ))? {
+                entries.push((keyword.clone(), name.clone()));
+            }
+        }
+        if entries.is_empty() {
+            _ = writeln!(io::stderr(), "nothing to promote");
+            return Ok(());
+        }
+
+        Config::add_keyword_map(&Config::get_path()?, &entries)?;
+        // The config takes over, the bindings are no longer needed.
+        for (keyword, _) in entries.iter() {
+            bindings.unbind(keyword);
+        }
+        bindings.save()?;
+        _ = writeln!(
+            io::stderr(),
+            "{} keywords promoted to config",
+            style(entries.len()).yellow()
+        );
+        Ok(())
+    }
+}
@@ -1,16 +1,23 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 use anyhow::Result;
+use console::style;
+use pad::PadStr;
 
 use crate::cmd::List;
 use crate::cmd::Run;
-use crate::config::Config;
-use crate::db::{Database, Keywords};
+use crate::config::{Config, RankMode};
+use crate::db::{Database, Epoch, Keywords, Repo, StatusCache};
+use crate::git;
+use crate::output::Record;
 use crate::util;
 
 impl Run for List {
     fn run(&self) -> Result<()> {
         let cfg = Config::parse()?;
+        if self.tree {
+            return self.show_tree(&cfg);
+        }
 
         if self.args.is_empty() {
             let keyword = self.keyword;
@@ -19,6 +26,20 @@ impl Run for List {
             } else {
                 self.remote
             };
+            if self.porcelain {
+                let db = Database::open()?;
+                for remote in &cfg.remotes {
+                    Record::new()
+                        .push(&remote.name)
+                        .push(db.filter(&remote.name, "").len())
+                        .print();
+                }
+                return Ok(());
+            }
+            let db = match self.porcelain_v2 {
+                true => Some(Database::open()?),
+                false => None,
+            };
             if keyword {
                 let now = util::current_time()?;
                 let keywords = Keywords::open(now)?;
@@ -30,7 +51,16 @@ impl Run for List {
                         continue;
                     }
                     name_set.insert(keyword.to_string());
-                    println!("{}", keyword);
+                    self.show(keyword, || match &db {
+                        Some(db) => match db.match_keyword("", keyword, &cfg.keyword_map) {
+                            Ok(idx) => {
+                                let repo = &db.repos[idx];
+                                format!("jump to {}:{}", repo.remote, repo.name)
+                            }
+                            Err(_) => String::from("no match"),
+                        },
+                        None => String::new(),
+                    });
                 }
 
                 let mut keys: Vec<_> = cfg
@@ -41,13 +71,19 @@ impl Run for List {
                 keys.sort_by(|s1, s2| s1.cmp(&s2));
                 for key in keys {
                     if let None = name_set.get(&key) {
-                        println!("{}", key);
+                        self.show(&key, || format!("maps to {}", cfg.keyword_map[&key]));
                     }
                 }
             }
             if remote {
                 for remote in &cfg.remotes {
-                    println!("{}", remote.name);
+                    self.show(&remote.name, || match &db {
+                        Some(db) => {
+                            let count = db.filter(&remote.name, "").len();
+                            format!("{} repos", count)
+                        }
+                        None => String::new(),
+                    });
                 }
             }
             return Ok(());
@@ -68,13 +104,198 @@ impl Run for List {
             return Ok(());
         }
 
-        for repo in &db.repos {
-            if repo.remote.as_str() != &self.args[0] {
+        let now = util::current_time()?;
+        let mut repos = db.filter(&self.args[0], "");
+        if let Some(name) = self.args.get(1) {
+            repos.retain(|repo| &repo.name == name);
+        }
+        let rank = self.rank.unwrap_or(cfg.rank);
+        if rank != RankMode::Alltime {
+            repos.sort_by(|repo1, repo2| repo2.rank(now, rank).total_cmp(&repo1.rank(now, rank)));
+        }
+        if self.long {
+            return self.show_long(&repos, now);
+        }
+        if self.porcelain {
+            return Self::show_porcelain(&repos, &cfg.workspace);
+        }
+        if !self.porcelain_v2 {
+            for repo in repos {
+                println!("{}", repo.name);
+            }
+            return Ok(());
+        }
+        let descs = util::parallel(&repos, |repo| Self::describe(repo, &cfg.workspace, now));
+        for (repo, desc) in repos.iter().zip(descs) {
+            self.show(&repo.name, || desc);
+        }
+
+        Ok(())
+    }
+}
+
+impl List {
+    /// Print the item, in porcelain v2 mode, the item is printed as
+    /// [`Record::describe`].
+    fn show<F>(&self, name: &str, desc: F)
+    where
+        F: FnOnce() -> String,
+    {
+        if !self.porcelain_v2 {
+            println!("{}", name);
+            return;
+        }
+        Record::describe(name, &desc()).print();
+    }
+
+    /// Print repos as a tree of remote -> group -> repo, each remote and
+    /// group node shows its repo count and cumulative score.
+    fn show_tree(&self, cfg: &Config) -> Result<()> {
+        let db = Database::open()?;
+        let now = util::current_time()?;
+        let remotes: Vec<&str> = match self.args.first() {
+            Some(remote) => vec![cfg.must_get_remote(remote)?.name.as_str()],
+            None => cfg
+                .remotes
+                .iter()
+                .map(|remote| remote.name.as_str())
+                .collect(),
+        };
+
+        for remote in remotes {
+            let repos = db.filter(remote, "");
+            if repos.is_empty() {
                 continue;
             }
-            println!("{}", repo.name);
+            let mut groups: BTreeMap<String, Vec<(String, f64)>> = BTreeMap::new();
+            for repo in &repos {
+                let (group, base) = util::split_name(&repo.name);
+                groups
+                    .entry(group)
+                    .or_default()
+                    .push((base, repo.score(now)));
+            }
+
+            let total: f64 = repos.iter().map(|repo| repo.score(now)).sum();
+            println!(
+                "{} {}",
+                style(remote).cyan().bold(),
+                Self::tree_stat(repos.len(), total)
+            );
+            let groups_len = groups.len();
+            for (group_idx, (group, mut items)) in groups.into_iter().enumerate() {
+                let last_group = group_idx == groups_len - 1;
+                let (branch, indent) = match last_group {
+                    true => ("└── ", "    "),
+                    false => ("├── ", "│   "),
+                };
+                let group_score: f64 = items.iter().map(|(_, score)| score).sum();
+                let group = match group.is_empty() {
+                    true => String::from("."),
+                    false => group,
+                };
+                println!(
+                    "{}{} {}",
+                    branch,
+                    style(group).yellow(),
+                    Self::tree_stat(items.len(), group_score)
+                );
+
+                items.sort_by(|(name1, _), (name2, _)| name1.cmp(name2));
+                let items_len = items.len();
+                for (idx, (name, score)) in items.into_iter().enumerate() {
+                    let branch = match idx == items_len - 1 {
+                        true => "└── ",
+                        false => "├── ",
+                    };
+                    println!(
+                        "{}{}{} {}",
+                        indent,
+                        branch,
+                        name,
+                        style(format!("{:.2}", score)).dim()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Print repos with their cached status, in format
+    /// `<name> <branch>[*] <updated> [meta]`, the "*" means the repo is
+    /// dirty, the meta is shown if it was fetched.
+    fn show_long(&self, repos: &[&Repo], now: Epoch) -> Result<()> {
+        let cache = StatusCache::open()?;
+        let pad = match repos.iter().map(|repo| repo.name.len()).max() {
+            Some(pad) => pad,
+            None => return Ok(()),
+        };
+        for repo in repos {
+            let name = repo
+                .name
+                .as_str()
+                .pad_to_width_with_alignment(pad, pad::Alignment::Left);
+            let status = match cache.get(repo) {
+                Some(status) => {
+                    let dirty = match status.dirty {
+                        true => style("*").red().to_string(),
+                        false => String::new(),
+                    };
+                    format!(
+                        "{}{} {}",
+                        style(&status.branch).magenta(),
+                        dirty,
+                        style(util::format_since(now, status.updated)).dim()
+                    )
+                }
+                None => style("<no status>").dim().to_string(),
+            };
+            match &repo.meta {
+                Some(meta) => println!("{} {} {}", name, status, style(meta.summary()).cyan()),
+                None => println!("{} {}", name, status),
+            }
         }
+        Ok(())
+    }
 
+    fn show_porcelain(repos: &[&Repo], workspace: &str) -> Result<()> {
+        let cache = StatusCache::open()?;
+        for repo in repos {
+            let path = repo.path(workspace)?;
+            let record = Record::new()
+                .push(&repo.remote)
+                .push(&repo.name)
+                .push(path.display())
+                .push(repo.last_accessed);
+            match cache.get(repo) {
+                Some(status) => record.push(&status.branch).push_bool(status.dirty),
+                None => record.push("").push(""),
+            }
+            .print();
+        }
         Ok(())
     }
+
+    fn tree_stat(count: usize, score: f64) -> String {
+        let stat = format!("({} repos, score {:.2})", count, score);
+        style(stat).dim().to_string()
+    }
+
+    fn describe(repo: &Repo, workspace: &str, now: Epoch) -> String {
+        let since = util::format_since(now, repo.last_accessed);
+        let path = match repo.path(workspace) {
+            Ok(path) if path.exists() => path,
+            _ => return format!("{}, not cloned", since),
+        };
+        let branch = match util::path_to_str(&path) {
+            Ok(path) => git::reader(Some(path), true)
+                .current_branch()
+                .unwrap_or_default(),
+            Err(_) => String::new(),
+        };
+        match branch.is_empty() {
+            true => since,
+            false => format!("{}, on {}", since, branch),
+        }
+    }
 }
@@ -0,0 +1,63 @@
+use std::io;
+use std::io::Write;
+
+use anyhow::Result;
+use console::style;
+
+use crate::cmd::Run;
+use crate::cmd::{Maintenance, MaintenanceAction};
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+use crate::util::Shell;
+
+impl Run for Maintenance {
+    fn run(&self) -> Result<()> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+        let registered = util::maintenance_repos()?;
+
+        let mut paths = Vec::with_capacity(db.repos.len());
+        for path in db.list_paths(&cfg.workspace)? {
+            if !path.exists() {
+                continue;
+            }
+            let path = util::path_to_str(&path)?.to_string();
+            let is_registered = registered.contains(&path);
+            match self.action {
+                MaintenanceAction::EnableAll if !is_registered => paths.push(path),
+                MaintenanceAction::DisableAll if is_registered => paths.push(path),
+                _ => {}
+            }
+        }
+        if paths.is_empty() {
+            _ = writeln!(io::stderr(), "nothing to do");
+            return Ok(());
+        }
+
+        let (action, word) = match self.action {
+            MaintenanceAction::EnableAll => ("register", "enable"),
+            MaintenanceAction::DisableAll => ("unregister", "disable"),
+        };
+        util::confirm(format!(
+            "do you want to {} maintenance for {} repos",
+            word,
+            style(paths.len()).yellow()
+        ))?;
+        for path in &paths {
+            Shell::git()
+                .with_git_path(path)
+                .args(["maintenance", action])
+                .exec()?;
+        }
+        if let MaintenanceAction::EnableAll = self.action {
+            // Make sure the background schedule is running.
+            Shell::git()
+                .with_git_path(&paths[0])
+                .args(["maintenance", "start"])
+                .exec()?;
+        }
+
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@ use crate::api::MergeOption;
 use crate::api::Provider;
 use crate::cmd::Merge;
 use crate::cmd::Run;
-use crate::config::Config;
+use crate::config::{Config, RepoConfig, Ticket};
 use crate::db::Database;
 use crate::db::Repo;
 use crate::util;
@@ -15,13 +15,18 @@ use crate::util::GitBranch;
 
 impl Run for Merge {
     fn run(&self) -> Result<()> {
-        GitBranch::ensure_no_uncommitted()?;
-        let db = Database::open()?;
+        let review = self.approve || self.comment.is_some();
+        if !review {
+            GitBranch::ensure_no_uncommitted()?;
+        }
+        let mut db = Database::open()?;
         let config = Config::parse()?;
-        let repo = db.current(&config.workspace)?;
+        let repo = db.current_or_detect(&config)?;
         let remote = config.must_get_remote(&repo.remote)?;
         let provider = api::create_provider(&remote)?;
 
+        let repo_cfg = RepoConfig::current()?.unwrap_or_default();
+
         let mut upstream = None;
         if self.upstream {
             util::print_operation(format!(
@@ -29,15 +34,25 @@ impl Run for Merge {
                 style(&repo.name).yellow()
             ));
             upstream = Some(provider.get_upstream(&repo.name)?);
+        } else if !repo_cfg.merge_target.is_empty() {
+            let target = repo_cfg.merge_target.clone();
+            util::print_operation(format!("use merge target {}", style(&target).yellow()));
+            upstream = Some(target);
+        } else if let Some(target) = remote.get_merge_target(&repo.name) {
+            util::print_operation(format!("use merge target {}", style(&target).yellow()));
+            upstream = Some(target);
         }
 
-        let mut opts = self.options(repo, &provider, &upstream)?;
+        let mut opts = self.options(repo, &provider, &upstream, &repo_cfg)?;
         opts.upstream = upstream;
         if let None = opts.upstream {
             if opts.source.eq(&opts.target) {
                 bail!("could not merge myself")
             }
         }
+        if review {
+            return self.review(&opts, provider.as_ref());
+        }
 
         util::print_operation(format!(
             "provider: query merge for {}",
@@ -46,27 +61,30 @@ impl Run for Merge {
         let merge = provider.get_merge(&opts)?;
 
         let url = match merge {
-            Some(url) => url,
-            None => self.create(&mut opts, &provider)?,
+            Some(url) => {
+                provider.update_merge(&opts)?;
+                url
+            }
+            None => self.create(&mut opts, &provider, remote.ticket.as_ref())?,
         };
 
-        util::open_url(url.as_str())?;
+        config.open_url(url.as_str())?;
 
         Ok(())
     }
 }
 
 impl Merge {
-    const TITLE_EMPTY: &str = "merge title cannot be empty";
-
     fn options(
         &self,
         repo: &Repo,
         provider: &Box<dyn Provider>,
         upstream: &Option<String>,
+        repo_cfg: &RepoConfig,
     ) -> Result<MergeOption> {
         let target = match &self.target {
             Some(t) => t.to_string(),
+            None if !repo_cfg.merge_branch.is_empty() => repo_cfg.merge_branch.clone(),
             None => match upstream {
                 Some(upstream) => {
                     util::print_operation(format!(
@@ -91,18 +109,75 @@ impl Merge {
             body: String::new(),
             source,
             target,
+            milestone: self.milestone.clone(),
+            project: self.project.clone(),
         })
     }
 
-    fn create(&self, opts: &mut MergeOption, provider: &Box<dyn Provider>) -> Result<String> {
+    fn review(&self, opts: &MergeOption, provider: &dyn Provider) -> Result<()> {
+        if let Some(body) = &self.comment {
+            if body.trim().is_empty() {
+                bail!("merge comment cannot be empty")
+            }
+        }
+        util::print_operation(format!(
+            "provider: query merge {}",
+            style(opts.display()).yellow()
+        ));
+        let number = match provider.get_merge_number(opts)? {
+            Some(number) => number,
+            None => bail!(
+                "could not find open merge {}",
+                style(opts.display()).yellow()
+            ),
+        };
+        // In upstream mode, the merge belongs to the upstream repo.
+        let repo = opts.upstream.as_ref().unwrap_or(&opts.repo);
+
+        if let Some(body) = &self.comment {
+            util::print_operation(format!("provider: comment merge #{}", number));
+            provider.comment_merge(repo, number, body)?;
+        }
+        if self.approve {
+            util::print_operation(format!("provider: approve merge #{}", number));
+            provider.approve_merge(repo, number)?;
+        }
+        Ok(())
+    }
+
+    fn create(
+        &self,
+        opts: &mut MergeOption,
+        provider: &Box<dyn Provider>,
+        ticket: Option<&Ticket>,
+    ) -> Result<String> {
         println!();
         util::confirm(format!("do you want to create merge {}", opts.display()))?;
-        (opts.title, opts.body) = self.input()?;
+        if !provider.merge_require_input() {
+            println!();
+            util::print_operation(format!(
+                "provider: create merge {}",
+                style(opts.display()).yellow()
+            ));
+            return provider.create_merge(opts);
+        }
+        let template = include_bytes!("../../files/merge_request.md");
+        let template = String::from_utf8_lossy(template);
+        (opts.title, opts.body) = Self::input(template.as_ref(), "merge")?;
+        if let Some(ticket) = ticket {
+            Self::apply_ticket(opts, ticket)?;
+        }
 
         println!();
         println!("Ready to create merge: {}", opts.display());
         println!("Title: {}", style(&opts.title).yellow());
         println!("Body: {}", style(opts.body_display()).yellow());
+        if let Some(milestone) = &opts.milestone {
+            println!("Milestone: {}", style(milestone).yellow());
+        }
+        if let Some(project) = &opts.project {
+            println!("Project: {}", style(project).yellow());
+        }
         println!();
 
         util::confirm("continue")?;
@@ -115,11 +190,28 @@ impl Merge {
         provider.create_merge(opts)
     }
 
-    fn input(&self) -> Result<(String, String)> {
-        let template = include_bytes!("../../files/merge_request.md");
-        let template = String::from_utf8_lossy(template);
+    fn apply_ticket(opts: &mut MergeOption, ticket: &Ticket) -> Result<()> {
+        let id = match ticket.extract(&opts.source)? {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+        opts.title = Ticket::prefix(&id, &opts.title);
+        if let Some(url) = ticket.url(&id) {
+            if !opts.body.contains(&url) {
+                let line = format!("Ticket: [{}]({})", id, url);
+                opts.body = match opts.body.is_empty() {
+                    true => line,
+                    false => format!("{}\n\n{}", opts.body, line),
+                };
+            }
+        }
+        Ok(())
+    }
 
-        let edited = util::edit(template.as_ref(), ".md", true)?;
+    /// Edit the template in editor, return the title and body. The `kind`
+    /// is used in the error message, such as "merge" or "issue".
+    pub fn input(template: &str, kind: &str) -> Result<(String, String)> {
+        let edited = util::edit(template, ".md", true)?;
 
         let lines: Vec<&str> = edited.split("\n").collect();
         let mut title = None;
@@ -135,11 +227,11 @@ impl Merge {
             }
         }
         if let None = title {
-            bail!(Self::TITLE_EMPTY)
+            bail!("{} title cannot be empty", kind)
         }
         let title = title.unwrap();
         if title.is_empty() {
-            bail!(Self::TITLE_EMPTY)
+            bail!("{} title cannot be empty", kind)
         }
         let body = body_lines.join("\n");
 
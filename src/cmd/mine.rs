@@ -0,0 +1,193 @@
+use std::env;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use console::style;
+
+use crate::api::{self, MergeItem, Provider};
+use crate::cmd::jump;
+use crate::cmd::Mine;
+use crate::cmd::Review;
+use crate::cmd::Run;
+use crate::config::{Config, Remote};
+use crate::db::{Database, Epoch};
+use crate::util;
+use crate::util::GitBranch;
+
+impl Run for Mine {
+    fn run(&self) -> Result<()> {
+        let cfg = Config::parse()?;
+        let now = util::current_time()?;
+
+        let merges = list_merges(&cfg, &self.args, |provider| provider.list_my_merges())?;
+        if merges.is_empty() {
+            _ = writeln!(io::stderr(), "no open merge");
+            return Ok(());
+        }
+        if !self.checkout && !self.open {
+            show_merges(&merges, now);
+            return Ok(());
+        }
+
+        let (remote, merge) = select_merge(&merges)?;
+        if self.open {
+            return cfg.open_url(&merge.url);
+        }
+        let provider = api::create_provider(remote)?;
+
+        let mut db = Database::open()?;
+        let idx = match db.get(&remote.name, &merge.repo) {
+            Some(idx) => idx,
+            None => {
+                let idx = db.add(&remote.name, &merge.repo, "");
+                db.resolve_collision(idx, &cfg)?;
+                idx
+            }
+        };
+        jump::enter(&mut db, &cfg, idx, now, |_, path| {
+            checkout_merge(provider.as_ref(), remote, &merge.repo, merge.number, path)
+        })
+    }
+}
+
+/// Checkout the source branch of the merge in the repo at `path`, the fork
+/// is fetched if needed, see [`Review::checkout_source`].
+pub fn checkout_merge(
+    provider: &dyn Provider,
+    remote: &Remote,
+    repo: &str,
+    number: u64,
+    path: &PathBuf,
+) -> Result<()> {
+    // The git commands run in current directory, enter the repo.
+    env::set_current_dir(path).with_context(|| format!("could not enter {}", path.display()))?;
+    GitBranch::ensure_no_uncommitted()?;
+    Review::checkout_source(provider, remote, repo, "origin", number)
+}
+
+/// List merges from the providers of remotes, see [`query_remotes`]. The
+/// result is sorted by remote and repo, so that the merges of a repo are
+/// grouped together.
+pub fn list_merges<'a, F>(
+    cfg: &'a Config,
+    args: &[String],
+    list: F,
+) -> Result<Vec<(&'a Remote, MergeItem)>>
+where
+    F: Fn(&dyn Provider) -> Result<Vec<MergeItem>> + Sync,
+{
+    let mut merges = query_remotes(cfg, args, "merges", list)?;
+    merges.sort_by(|(remote1, merge1), (remote2, merge2)| {
+        (&remote1.name, &merge1.repo, merge1.number).cmp(&(
+            &remote2.name,
+            &merge2.repo,
+            merge2.number,
+        ))
+    });
+    Ok(merges)
+}
+
+/// Query items from the providers of remotes in parallel, the remote can
+/// be specified by `args`, default is all the remotes enabling api. The
+/// failed remotes are reported and skipped, `what` names the items in the
+/// report.
+pub fn query_remotes<'a, T, F>(
+    cfg: &'a Config,
+    args: &[String],
+    what: &str,
+    query: F,
+) -> Result<Vec<(&'a Remote, T)>>
+where
+    T: Send,
+    F: Fn(&dyn Provider) -> Result<Vec<T>> + Sync,
+{
+    let remotes: Vec<&Remote> = match args.first() {
+        Some(name) => vec![cfg.must_get_remote(name)?],
+        None => cfg
+            .remotes
+            .iter()
+            .filter(|remote| remote.api.is_some())
+            .collect(),
+    };
+    if remotes.is_empty() {
+        bail!("no remote enables api provider, please config it first")
+    }
+
+    let results = util::parallel(&remotes, |remote| {
+        let provider = api::create_provider(remote)?;
+        query(provider.as_ref())
+    });
+    let mut items = vec![];
+    let mut failed = 0;
+    for (remote, result) in remotes.iter().zip(results) {
+        match result {
+            Ok(result) => items.extend(result.into_iter().map(|item| (*remote, item))),
+            Err(err) => {
+                _ = writeln!(
+                    io::stderr(),
+                    "{} list {} of {} failed: {:#}",
+                    util::Palette::get().error.apply_to("error"),
+                    what,
+                    style(&remote.name).yellow(),
+                    err
+                );
+                failed += 1;
+            }
+        }
+    }
+    if failed == remotes.len() {
+        bail!("all remotes failed to list {}", what)
+    }
+    Ok(items)
+}
+
+/// Print merges grouped by repo, in format
+/// `#<number> <title> <source> [ci] <created>`.
+pub fn show_merges(merges: &[(&Remote, MergeItem)], now: Epoch) {
+    let mut last: Option<(&str, &str)> = None;
+    for (remote, merge) in merges {
+        let key = (remote.name.as_str(), merge.repo.as_str());
+        if last != Some(key) {
+            println!("{}:{}", style(key.0).yellow(), style(key.1).yellow());
+            last = Some(key);
+        }
+        println!(
+            "  #{} {} {}{} {}",
+            merge.number,
+            merge.title,
+            style(&merge.source).magenta(),
+            format_ci(&merge.ci),
+            style(util::format_since(now, merge.created)).dim()
+        );
+    }
+}
+
+/// Format the CI status with a leading space, empty if the status is
+/// unknown.
+pub fn format_ci(ci: &str) -> String {
+    match ci {
+        "" => String::new(),
+        "success" => format!(" {}", style(ci).green()),
+        "failure" => format!(" {}", style(ci).red()),
+        _ => format!(" {}", style(ci).yellow()),
+    }
+}
+
+/// Select a merge with fzf.
+pub fn select_merge<'a, 'b>(
+    merges: &'b [(&'a Remote, MergeItem)],
+) -> Result<&'b (&'a Remote, MergeItem)> {
+    let keys: Vec<String> = merges
+        .iter()
+        .map(|(remote, merge)| {
+            format!(
+                "{}:{}#{} {}",
+                remote.name, merge.repo, merge.number, merge.title
+            )
+        })
+        .collect();
+    let mut fzf = util::Fzf::build(&[])?;
+    Ok(&merges[fzf.query(&keys)?])
+}
@@ -1,28 +1,68 @@
+mod activity;
+mod add;
+mod alias;
+mod archive;
 mod attach;
+mod auth;
+mod bench;
+mod bootstrap;
 mod branch;
 mod clean;
 mod config;
+mod credential;
 mod detach;
+mod diff;
+mod du;
+mod export_aliases;
+mod find;
+mod grep;
+mod history;
 mod home;
+mod inbox;
+mod info;
 mod init;
+mod issue;
 mod jump;
+mod keyword;
 mod list;
+mod maintenance;
 mod merge;
+mod mine;
 mod open;
+mod operation;
+mod outdated;
+mod pr;
+mod publish;
+mod push;
 mod rebase;
+mod refresh_meta;
 mod remove;
 mod reset;
+mod review;
+mod reviews;
+mod scan;
+mod set;
+mod snapshot;
 mod squash;
+mod suggest_archive;
 mod tag;
+mod ui;
+mod version;
+mod wip;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+pub use crate::cmd::alias::expand_alias;
+use crate::config::RankMode;
 
 #[derive(Debug, Parser)]
 #[clap(about, author, version)]
 pub enum Cmd {
     Home(Home),
     Remove(Remove),
+    Archive(Archive),
+    SuggestArchive(SuggestArchive),
     Clean(Clean),
     Attach(Attach),
     Detach(Detach),
@@ -31,12 +71,46 @@ pub enum Cmd {
     Config(Config),
     Branch(Branch),
     Merge(Merge),
+    Mine(Mine),
     Open(Open),
     Rebase(Rebase),
     Squash(Squash),
     Reset(Reset),
     Jump(Jump),
     Tag(Tag),
+    Set(Set),
+    Diff(Diff),
+    Review(Review),
+    Reviews(Reviews),
+    Inbox(Inbox),
+    Activity(Activity),
+    Push(Push),
+    Publish(Publish),
+    Wip(Wip),
+    Grep(Grep),
+    Find(Find),
+    Du(Du),
+    Outdated(Outdated),
+    RefreshMeta(RefreshMeta),
+    Maintenance(Maintenance),
+    Ui(Ui),
+    Alias(Alias),
+    Keyword(Keyword),
+    Snapshot(Snapshot),
+    Bootstrap(Bootstrap),
+    Continue(Continue),
+    Abort(Abort),
+    Version(Version),
+    Bench(Bench),
+    Credential(Credential),
+    Add(Add),
+    History(History),
+    Scan(Scan),
+    Auth(Auth),
+    Info(Info),
+    ExportAliases(ExportAliases),
+    Pr(Pr),
+    Issue(Issue),
 }
 
 /// Print the home path for a repository, recommanded to use `zz` instead
@@ -53,6 +127,34 @@ pub struct Home {
     /// Use remote provider to search the repo
     #[clap(long, short)]
     pub search: bool,
+
+    /// Search both the local repos and the remote provider, the repos not
+    /// in database are marked
+    #[clap(long, short)]
+    pub all: bool,
+
+    /// The rank mode used to match repo, default is `rank` in config
+    #[clap(long, value_enum)]
+    pub rank: Option<RankMode>,
+
+    /// Select repo with fzf when the keyword matches multiple repos, the
+    /// selection will be remembered for the keyword
+    #[clap(long, short)]
+    pub interactive: bool,
+
+    /// Also match the keyword against the cached description and topics of
+    /// repos, see `repo_meta` in config
+    #[clap(long, short)]
+    pub deep: bool,
+
+    /// Generate LICENSE for the created repo, builtin: mit, others can be
+    /// added to `templates.licenses` in config
+    #[clap(long, requires = "create")]
+    pub license: Option<String>,
+
+    /// Generate README.md for the created repo
+    #[clap(long, requires = "create")]
+    pub readme: bool,
 }
 
 /// Remove a repository
@@ -66,6 +168,41 @@ pub struct Remove {
     /// Direct remove, skip confirm
     #[clap(long, short)]
     pub force: bool,
+
+    /// Also delete the repository in remote provider, this cannot be undone
+    /// and always requires confirmation
+    #[clap(long)]
+    pub remote_too: bool,
+}
+
+/// Move a repository to cold storage, it will be restored when jumping
+#[derive(Debug, Parser)]
+pub struct Archive {
+    /// The remote of the repo
+    pub remote: String,
+    /// The name of the repo
+    pub name: String,
+
+    /// Direct archive, skip confirm
+    #[clap(long, short)]
+    pub force: bool,
+}
+
+/// List the repositories not accessed for a long time, to archive or remove
+#[derive(Debug, Parser)]
+pub struct SuggestArchive {
+    /// Remote and group (optional) to filter repositories
+    #[clap(num_args = 0..=2)]
+    pub args: Vec<String>,
+
+    /// The repositories not accessed in this many days are listed, default
+    /// will use `archive_after` in config
+    #[clap(long, short)]
+    pub days: Option<u64>,
+
+    /// Ask to archive or remove each repository with one key
+    #[clap(long, short)]
+    pub interactive: bool,
 }
 
 /// Clean unused directory in workspace
@@ -74,6 +211,125 @@ pub struct Clean {
     /// Show repo to clean, do not execute
     #[clap(long)]
     pub dry_run: bool,
+
+    /// Report the untracked repositories in workspace and the repositories
+    /// whose directories are missing, do not execute
+    #[clap(long, short)]
+    pub report: bool,
+}
+
+/// Show the layout of workspace versus the database as a tree, with the
+/// counts of tracked, untracked and missing repositories
+#[derive(Debug, Parser)]
+pub struct Scan {
+    /// The max depth of directories to walk and show, default is no limit
+    #[clap(long, short)]
+    pub depth: Option<usize>,
+}
+
+/// Manage the api tokens of remotes stored in the system keyring
+#[derive(Debug, Parser)]
+pub struct Auth {
+    #[clap(subcommand)]
+    pub action: AuthAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuthAction {
+    /// Store the token of a remote to keyring, the token is read from stdin
+    /// if it is not a terminal, otherwise prompted
+    Set {
+        /// The remote to store token for
+        remote: String,
+    },
+
+    /// Remove the token of a remote from keyring
+    Remove {
+        /// The remote to remove token for
+        remote: String,
+    },
+
+    /// Login to Github by OAuth device flow, the token is stored to keyring
+    /// and `token_from` of remote is set to keyring
+    Login {
+        /// The remote to login, its provider must be github
+        remote: String,
+    },
+}
+
+/// Show what git-zoxide knows about current repository, such as the clone
+/// url, user, upstream, score and labels
+#[derive(Debug, Parser)]
+pub struct Info {}
+
+/// Print shell aliases to cd into the most used repos, such as
+/// `source <(gz export-aliases)`, the alias is named by the base name of
+/// repo, or the full name if the base name is taken or shadows a command
+#[derive(Debug, Parser)]
+pub struct ExportAliases {
+    /// The number of repos to export, default is 20
+    #[clap(long)]
+    pub top: Option<usize>,
+
+    /// The prefix of alias names, to avoid shadowing commands
+    #[clap(long)]
+    pub prefix: Option<String>,
+
+    /// The rank mode used to choose repos, default is `rank` in config
+    #[clap(long, value_enum)]
+    pub rank: Option<RankMode>,
+}
+
+/// Manage the merges (PR/MR) of current repository
+#[derive(Debug, Parser)]
+pub struct Pr {
+    #[clap(subcommand)]
+    pub action: PrAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PrAction {
+    /// List the open merges of current repository
+    List {
+        /// Upstream mode, list the merges of the upstream of forked repo
+        #[clap(long, short)]
+        upstream: bool,
+
+        /// Select a merge with fzf and open it in default browser
+        #[clap(long, short)]
+        open: bool,
+    },
+
+    /// Fetch a merge and check it out into a local branch tracking its
+    /// source branch, checkout again to get the new commits
+    Checkout {
+        /// The merge number
+        number: u64,
+
+        /// Upstream mode, the merge is in the upstream of forked repo
+        #[clap(long, short)]
+        upstream: bool,
+    },
+}
+
+/// List, create or open the issues of current repository
+#[derive(Debug, Parser)]
+pub struct Issue {
+    /// List the open issues, this is the default
+    #[clap(long, short, conflicts_with_all = ["create", "open"])]
+    pub list: bool,
+
+    /// Create an issue, the title and body are edited in editor
+    #[clap(long, short, conflicts_with = "open")]
+    pub create: bool,
+
+    /// Open the issue in default browser
+    #[clap(long, short)]
+    pub open: Option<u64>,
+
+    /// Upstream mode, the issues are in the upstream of forked repo
+    #[clap(long, short)]
+    pub upstream: bool,
 }
 
 /// Attach current path to a repository
@@ -111,8 +367,9 @@ pub struct Detach {
 /// List remotes or repositories
 #[derive(Debug, Parser)]
 pub struct List {
-    /// With remote or not
-    #[clap(num_args = 0..=1)]
+    /// With remote or not, the name of repo can follow the remote to show
+    /// only one repo
+    #[clap(num_args = 0..=2)]
     pub args: Vec<String>,
 
     /// Show only group (for completion)
@@ -126,6 +383,27 @@ pub struct List {
     /// Show only remote (for completion)
     #[clap(long)]
     pub remote: bool,
+
+    /// Show with description, in format `<name>:<description>` (for completion)
+    #[clap(long)]
+    pub porcelain_v2: bool,
+
+    /// Show repositories as a tree grouped by remote and group
+    #[clap(long, short)]
+    pub tree: bool,
+
+    /// Show the cached branch and dirty state of repositories, requires
+    /// `status_cache` in config, and the metadata fetched from provider
+    #[clap(long, short)]
+    pub long: bool,
+
+    /// The rank mode used to sort repos, default is `rank` in config
+    #[clap(long, value_enum)]
+    pub rank: Option<RankMode>,
+
+    /// Show in the stable format for scripts
+    #[clap(long, conflicts_with_all = ["group", "keyword", "porcelain_v2", "tree", "long"])]
+    pub porcelain: bool,
 }
 
 /// Print the init script, please add `source <(git-zoxide init)` to your profile
@@ -150,6 +428,11 @@ pub struct Config {
     /// The editor to use, default will auto choose one from your machine
     #[clap(long, short)]
     pub editor: Option<String>,
+
+    /// Print the JSON schema of config file, can be used by YAML language
+    /// server to validate and complete the config
+    #[clap(long, short)]
+    pub schema: bool,
 }
 
 /// Git branch operations
@@ -167,6 +450,17 @@ pub struct Branch {
     #[clap(long, short)]
     pub sync: bool,
 
+    /// Do not fetch before syncing, the status is computed from the remote
+    /// branches fetched last time
+    #[clap(long, requires = "sync")]
+    pub no_fetch: bool,
+
+    /// Only fetch current branch before syncing, which is much faster for
+    /// repos with many branches, the other branches use the remote branches
+    /// fetched last time
+    #[clap(long, requires = "sync", conflicts_with = "no_fetch")]
+    pub fetch_current: bool,
+
     /// Create a new branch
     #[clap(long, short)]
     pub create: bool,
@@ -182,8 +476,43 @@ pub struct Branch {
     /// Show branch (for completion)
     #[clap(long)]
     pub cmp: bool,
+
+    /// Show branches with status in the stable format for scripts
+    #[clap(long)]
+    pub porcelain: bool,
+}
+
+/// Push current branch to remote
+#[derive(Debug, Parser)]
+pub struct Push {
+    /// Force push with lease, the protected branches will be refused
+    #[clap(long, short)]
+    pub force: bool,
 }
 
+/// Publish current repository (created locally) to remote, set origin and
+/// push the default branch
+#[derive(Debug, Parser)]
+pub struct Publish {
+    /// Create the repository in remote provider before pushing
+    #[clap(long, short)]
+    pub create: bool,
+
+    /// Create the repository as private, only used with `--create`
+    #[clap(long, short, requires = "create")]
+    pub private: bool,
+}
+
+/// Continue the git operation in progress (rebase, merge, cherry-pick, am,
+/// revert) after resolving conflicts
+#[derive(Debug, Parser)]
+pub struct Continue {}
+
+/// Abort the git operation in progress (rebase, merge, cherry-pick, am,
+/// revert)
+#[derive(Debug, Parser)]
+pub struct Abort {}
+
 /// Create or open MergeRequest or PullRequest
 #[derive(Debug, Parser)]
 pub struct Merge {
@@ -198,14 +527,59 @@ pub struct Merge {
     /// Target branch, default will use HEAD branch
     #[clap(long, short)]
     pub target: Option<String>,
+
+    /// Milestone title to assign to the created merge
+    #[clap(long, short)]
+    pub milestone: Option<String>,
+
+    /// Project number to add the created merge to (only for Github)
+    #[clap(long, short)]
+    pub project: Option<String>,
+
+    /// Approve the open merge of current branch rather than opening it
+    #[clap(long)]
+    pub approve: bool,
+
+    /// Comment on the open merge of current branch rather than opening it
+    #[clap(long)]
+    pub comment: Option<String>,
+}
+
+/// List the open merges (PR/MR) authored by me across remotes
+#[derive(Debug, Parser)]
+pub struct Mine {
+    /// The remote to query, default will query all remotes enabling api
+    #[clap(num_args = 0..=1)]
+    pub args: Vec<String>,
+
+    /// Select a merge with fzf, jump to its repository and checkout its
+    /// source branch into a local tracking branch
+    #[clap(long, short, conflicts_with = "open")]
+    pub checkout: bool,
+
+    /// Select a merge with fzf and open it in default browser
+    #[clap(long, short)]
+    pub open: bool,
 }
 
 /// Open current repository in default browser
 #[derive(Debug, Parser)]
 pub struct Open {
+    /// Compare target (optional), only used with `--compare`
+    #[clap(num_args = 0..=1)]
+    pub args: Vec<String>,
+
     /// Open current branch
     #[clap(long, short)]
     pub branch: bool,
+
+    /// Open compare page between current branch and target branch
+    #[clap(long, short)]
+    pub compare: bool,
+
+    /// Print the url rather than opening it in browser
+    #[clap(long, short)]
+    pub print: bool,
 }
 
 /// Rebase current branch
@@ -220,6 +594,102 @@ pub struct Rebase {
     pub upstream: bool,
 }
 
+/// Show diff between current branch and target branch
+#[derive(Debug, Parser)]
+pub struct Diff {
+    /// Diff target (optional), default will use HEAD branch
+    #[clap(num_args = 0..=1)]
+    pub args: Vec<String>,
+
+    /// Upstream mode, only used for forked repo
+    #[clap(long, short)]
+    pub upstream: bool,
+
+    /// Only show diffstat
+    #[clap(long, short)]
+    pub stat: bool,
+
+    /// Open compare page in default browser
+    #[clap(long, short)]
+    pub web: bool,
+}
+
+/// Checkout a merge (PR/MR) into a local review branch
+#[derive(Debug, Parser)]
+pub struct Review {
+    /// The merge number, optional when using `--done`
+    #[clap(num_args = 0..=1)]
+    pub args: Vec<String>,
+
+    /// Upstream mode, only used for forked repo
+    #[clap(long, short)]
+    pub upstream: bool,
+
+    /// Finish reviewing, delete the review branch
+    #[clap(long, short)]
+    pub done: bool,
+}
+
+/// List the open merges (PR/MR) requesting my review across remotes
+#[derive(Debug, Parser)]
+pub struct Reviews {
+    /// The remote to query, default will query all remotes enabling api
+    #[clap(num_args = 0..=1)]
+    pub args: Vec<String>,
+
+    /// Select a merge with fzf, jump to its repository and checkout its
+    /// source branch into a local tracking branch
+    #[clap(long, short, conflicts_with_all = ["open", "approve"])]
+    pub checkout: bool,
+
+    /// Select a merge with fzf and open it in default browser
+    #[clap(long, short, conflicts_with = "approve")]
+    pub open: bool,
+
+    /// Select a merge with fzf and approve it
+    #[clap(long, short)]
+    pub approve: bool,
+}
+
+/// List unread notifications (Github notifications, Gitlab todos) across
+/// remotes
+#[derive(Debug, Parser)]
+pub struct Inbox {
+    /// The remote to query, default will query all remotes enabling api
+    #[clap(num_args = 0..=1)]
+    pub args: Vec<String>,
+
+    /// Select a notification with fzf, open it in default browser and mark
+    /// it as read
+    #[clap(long, short, conflicts_with = "read")]
+    pub open: bool,
+
+    /// Select a notification with fzf and mark it as read
+    #[clap(long, short)]
+    pub read: bool,
+
+    /// With `--read`, mark all the listed notifications as read
+    #[clap(long, short, requires = "read")]
+    pub all: bool,
+}
+
+/// Show recent commits on default branch and releases of repositories
+#[derive(Debug, Parser)]
+pub struct Activity {
+    /// Remote and group (optional) to filter repositories
+    #[clap(num_args = 0..=2)]
+    pub args: Vec<String>,
+
+    /// Show the activities in this duration, like "12h", "7d", "2w", default
+    /// is 7d
+    #[clap(long, short)]
+    pub since: Option<String>,
+
+    /// Ignore the cache, fetch activities from provider
+    #[clap(long, short)]
+    pub force: bool,
+}
+
 /// Squash multiple commits into one
 #[derive(Debug, Parser)]
 pub struct Squash {
@@ -234,6 +704,18 @@ pub struct Squash {
     /// Commit message
     #[clap(long, short)]
     pub message: Option<String>,
+
+    /// Do not append the trailers configured for the remote
+    #[clap(long)]
+    pub no_trailers: bool,
+
+    /// Create a fixup commit for a commit selected from the squash range
+    #[clap(long, short)]
+    pub fixup: bool,
+
+    /// Apply the fixup commits by rebasing with autosquash
+    #[clap(long, short)]
+    pub autosquash: bool,
 }
 
 /// Reset git to remote
@@ -252,11 +734,180 @@ pub struct Reset {
     pub upstream: bool,
 }
 
+/// Save or restore work in progress changes for current branch
+#[derive(Debug, Parser)]
+pub struct Wip {
+    /// Restore the saved changes
+    #[clap(long, short)]
+    pub restore: bool,
+}
+
+/// Search content across repositories
+#[derive(Debug, Parser)]
+pub struct Grep {
+    /// The pattern to search
+    pub pattern: String,
+
+    /// Remote and group (optional) to filter repositories
+    #[clap(num_args = 0..=2)]
+    pub args: Vec<String>,
+
+    /// Select a result and jump to its repository
+    #[clap(long, short)]
+    pub open: bool,
+}
+
+/// Find tracked files across repositories
+#[derive(Debug, Parser)]
+pub struct Find {
+    /// The glob to match file path, if it does not contain `/`, only the
+    /// file name will be matched
+    pub glob: String,
+
+    /// Remote and group (optional) to filter repositories
+    #[clap(num_args = 0..=2)]
+    pub args: Vec<String>,
+
+    /// Select a file using fzf
+    #[clap(long, short)]
+    pub select: bool,
+}
+
+/// List outdated dependencies (Cargo, npm, Go) across repositories
+#[derive(Debug, Parser)]
+pub struct Outdated {
+    /// Remote and group (optional) to filter repositories
+    #[clap(num_args = 0..=2)]
+    pub args: Vec<String>,
+}
+
+/// Show disk usage of repositories
+#[derive(Debug, Parser)]
+pub struct Du {
+    /// Remote and group (optional) to filter repositories
+    #[clap(num_args = 0..=2)]
+    pub args: Vec<String>,
+
+    /// Run `git gc --aggressive` for the repositories
+    #[clap(long)]
+    pub gc: bool,
+
+    /// Show in the stable format for scripts
+    #[clap(long, conflicts_with = "gc")]
+    pub porcelain: bool,
+}
+
+/// Fetch the metadata (description, stars, etc.) of repositories from provider
+#[derive(Debug, Parser)]
+pub struct RefreshMeta {
+    /// Remote and group or repo name (optional) to filter repositories
+    #[clap(num_args = 0..=2)]
+    pub args: Vec<String>,
+}
+
+/// Manage git maintenance for repositories
+#[derive(Debug, Parser)]
+pub struct Maintenance {
+    #[clap(subcommand)]
+    pub action: MaintenanceAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MaintenanceAction {
+    /// Register all cloned repositories to git maintenance
+    EnableAll,
+
+    /// Unregister all repositories in workspace from git maintenance
+    DisableAll,
+}
+
+/// Manage the keywords bound to repositories
+#[derive(Debug, Parser)]
+pub struct Keyword {
+    #[clap(subcommand)]
+    pub action: KeywordAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum KeywordAction {
+    /// List the keyword bindings
+    List,
+
+    /// Forget the binding of a keyword
+    Unbind {
+        /// The keyword to unbind
+        keyword: String,
+    },
+
+    /// Show the bound keywords used repeatedly, and write the selected ones
+    /// into `keyword_map` of config permanently
+    Promote {
+        /// The min number of uses for a keyword to be promoted, default is 3
+        #[clap(long, short)]
+        min: Option<u32>,
+    },
+}
+
+/// Save or restore the repositories in a manifest, to reproduce the
+/// workspace on another machine
+#[derive(Debug, Parser)]
+pub struct Snapshot {
+    #[clap(subcommand)]
+    pub action: SnapshotAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SnapshotAction {
+    /// Create a manifest with repositories, checked out branches and attached
+    /// paths
+    Create {
+        /// The manifest file to write, default will print to stdout
+        file: Option<String>,
+    },
+
+    /// Clone the repositories in manifest, check out the recorded branches
+    /// and attach the paths
+    Restore {
+        /// The manifest file to read
+        file: String,
+    },
+}
+
+/// Ensure the repositories declared in config exist locally
+#[derive(Debug, Parser)]
+pub struct Bootstrap {
+    /// Only bootstrap the repositories with any of the labels
+    #[clap(long, short)]
+    pub label: Vec<String>,
+
+    /// Show the repositories to bootstrap, do not execute
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+/// Interactive dashboard to manage repositories
+#[derive(Debug, Parser)]
+pub struct Ui {}
+
 /// Quick jump to a repository (please use `gz` instead)
 #[derive(Debug, Parser)]
 pub struct Jump {
     /// Jump keyword
     pub keyword: String,
+
+    /// The rank mode used to match repo, default is `rank` in config
+    #[clap(long, value_enum)]
+    pub rank: Option<RankMode>,
+
+    /// Select repo with fzf when the keyword matches multiple repos, the
+    /// selection will be remembered for the keyword
+    #[clap(long, short)]
+    pub interactive: bool,
+
+    /// Also match the keyword against the cached description and topics of
+    /// repos, see `repo_meta` in config
+    #[clap(long, short)]
+    pub deep: bool,
 }
 
 /// Git tag operations
@@ -282,11 +933,102 @@ pub struct Tag {
     #[clap(long, short)]
     pub push: bool,
 
+    /// Show the tag that would be created by the rule, without creating it
+    #[clap(long, requires = "rule")]
+    pub dry_run: bool,
+
     /// Show rules (for completion)
-    #[clap(long)]
+    #[clap(long, alias = "rules")]
     pub show_rules: bool,
+
+    /// With show rules, show each rule applied to the latest tag
+    #[clap(long, requires = "show_rules")]
+    pub explain: bool,
+}
+
+/// Show the version, and check the newer releases
+#[derive(Debug, Parser)]
+pub struct Version {
+    /// Check the newer releases in GitHub, show their changelogs
+    #[clap(long, short)]
+    pub check_update: bool,
+
+    /// Download the latest release, verify its checksum and replace current
+    /// binary
+    #[clap(long, short, requires = "check_update")]
+    pub install: bool,
+}
+
+/// Measure the latencies of home, jump and list against the database
+#[derive(Debug, Parser)]
+pub struct Bench {
+    /// The times to run each command, the first run is taken as cold, default
+    /// is 10
+    #[clap(long, short)]
+    pub count: Option<usize>,
+
+    /// Synthesize a database with this many fake repositories, rather than
+    /// using a copy of current database. Current config is not used either
+    #[clap(long, short)]
+    pub synth: Option<usize>,
+}
+
+/// Git credential helper using the api tokens in config, enable it by
+/// `git config --global credential.helper '!git-zoxide credential'`
+#[derive(Debug, Parser)]
+pub struct Credential {
+    /// The operation passed by git: get, store or erase
+    pub operation: String,
+}
+
+/// Clone a repository by its url and add it to the database, the remote is
+/// matched by the domain in url
+#[derive(Debug, Parser)]
+pub struct Add {
+    /// The url of repository, can be https, ssh or the web page url copied
+    /// from browser
+    pub url: String,
 }
 
+/// Show the log of mutating operations, such as clones, removals and
+/// branch deletions
+#[derive(Debug, Parser)]
+pub struct History {
+    /// Remote and name keyword (optional) to filter operations
+    #[clap(num_args = 0..=2)]
+    pub args: Vec<String>,
+
+    /// Only show the operations of this action, like "clone", "remove"
+    #[clap(long, short)]
+    pub action: Option<String>,
+
+    /// Only show the operations in this duration, like "12h", "7d", "2w"
+    #[clap(long, short)]
+    pub since: Option<String>,
+
+    /// The max number of operations to show, default is 50, 0 means no limit
+    #[clap(long, short = 'n')]
+    pub limit: Option<usize>,
+
+    /// Show in the stable format for scripts
+    #[clap(long)]
+    pub porcelain: bool,
+}
+
+/// Set option for current repository
+#[derive(Debug, Parser)]
+pub struct Set {
+    /// The option key, available: use-ssh
+    pub key: String,
+
+    /// The option value, leave empty to reset the option
+    pub value: Option<String>,
+}
+
+/// Show the command aliases defined in config
+#[derive(Debug, Parser)]
+pub struct Alias {}
+
 pub trait Run {
     fn run(&self) -> Result<()>;
 }
@@ -296,6 +1038,8 @@ impl Run for Cmd {
         match self {
             Cmd::Home(home) => home.run(),
             Cmd::Remove(remove) => remove.run(),
+            Cmd::Archive(archive) => archive.run(),
+            Cmd::SuggestArchive(suggest_archive) => suggest_archive.run(),
             Cmd::Clean(clean) => clean.run(),
             Cmd::Attach(attach) => attach.run(),
             Cmd::Detach(detach) => detach.run(),
@@ -304,12 +1048,46 @@ impl Run for Cmd {
             Cmd::Config(config) => config.run(),
             Cmd::Branch(branch) => branch.run(),
             Cmd::Merge(merge) => merge.run(),
+            Cmd::Mine(mine) => mine.run(),
             Cmd::Open(open) => open.run(),
             Cmd::Rebase(rebase) => rebase.run(),
             Cmd::Squash(squash) => squash.run(),
             Cmd::Reset(reset) => reset.run(),
             Cmd::Jump(jump) => jump.run(),
             Cmd::Tag(tag) => tag.run(),
+            Cmd::Set(set) => set.run(),
+            Cmd::Diff(diff) => diff.run(),
+            Cmd::Review(review) => review.run(),
+            Cmd::Reviews(reviews) => reviews.run(),
+            Cmd::Inbox(inbox) => inbox.run(),
+            Cmd::Activity(activity) => activity.run(),
+            Cmd::Push(push) => push.run(),
+            Cmd::Publish(publish) => publish.run(),
+            Cmd::Wip(wip) => wip.run(),
+            Cmd::Grep(grep) => grep.run(),
+            Cmd::Find(find) => find.run(),
+            Cmd::Du(du) => du.run(),
+            Cmd::Outdated(outdated) => outdated.run(),
+            Cmd::RefreshMeta(refresh_meta) => refresh_meta.run(),
+            Cmd::Maintenance(maintenance) => maintenance.run(),
+            Cmd::Ui(ui) => ui.run(),
+            Cmd::Alias(alias) => alias.run(),
+            Cmd::Keyword(keyword) => keyword.run(),
+            Cmd::Snapshot(snapshot) => snapshot.run(),
+            Cmd::Bootstrap(bootstrap) => bootstrap.run(),
+            Cmd::Continue(cont) => cont.run(),
+            Cmd::Abort(abort) => abort.run(),
+            Cmd::Version(version) => version.run(),
+            Cmd::Bench(bench) => bench.run(),
+            Cmd::Credential(credential) => credential.run(),
+            Cmd::Add(add) => add.run(),
+            Cmd::History(history) => history.run(),
+            Cmd::Scan(scan) => scan.run(),
+            Cmd::Auth(auth) => auth.run(),
+            Cmd::Info(info) => info.run(),
+            Cmd::ExportAliases(export_aliases) => export_aliases.run(),
+            Cmd::Pr(pr) => pr.run(),
+            Cmd::Issue(issue) => issue.run(),
         }
     }
 }
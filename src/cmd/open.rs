@@ -1,28 +1,86 @@
 use anyhow::Result;
+use console::style;
 
 use crate::api;
+use crate::api::Provider;
 use crate::cmd::Open;
 use crate::cmd::Run;
-use crate::config::Config;
+use crate::config::{Config, Remote};
 use crate::db::Database;
+use crate::db::Repo;
 use crate::util;
 use crate::util::GitBranch;
 
 impl Run for Open {
     fn run(&self) -> Result<()> {
-        let db = Database::open()?;
+        let mut db = Database::open()?;
         let config = Config::parse()?;
-        let repo = db.current(&config.workspace)?;
+        let repo = db.current_or_detect(&config)?;
         let remote = config.must_get_remote(&repo.remote)?;
         let provider = api::create_provider(&remote)?;
 
+        if self.compare {
+            let target = util::option_arg(&self.args);
+            let url = compare_url(repo, remote, provider.as_ref(), target, false)?;
+            return self.open(&config, url);
+        }
+
         let mut branch = None;
         if self.branch {
             branch = Some(GitBranch::current()?);
         }
         let url = provider.get_repo_url(&repo.name, branch, &remote)?;
-        util::open_url(url)?;
+        self.open(&config, url)
+    }
+}
 
-        Ok(())
+impl Open {
+    fn open(&self, cfg: &Config, url: String) -> Result<()> {
+        if self.print {
+            return util::print_url(url);
+        }
+        cfg.open_url(url)
     }
 }
+
+/// Build the url to compare current branch with target branch. If target is
+/// not provided, the default branch (of upstream in upstream mode) is used.
+pub fn compare_url(
+    repo: &Repo,
+    remote: &Remote,
+    provider: &dyn Provider,
+    target: Option<&str>,
+    upstream: bool,
+) -> Result<String> {
+    let current = GitBranch::current()?;
+    let (name, head) = match upstream {
+        true => {
+            util::print_operation(format!(
+                "provider: get upstream for {}",
+                style(&repo.name).yellow()
+            ));
+            let upstream = provider.get_upstream(&repo.name)?;
+            // Comparing across forks requires the owner of the head
+            // branch, in the form of "owner:branch".
+            let (owner, _) = util::split_name(&repo.name);
+            (upstream, format!("{}:{}", owner, current))
+        }
+        false => (repo.name.clone(), current),
+    };
+
+    let base = match target {
+        Some(target) => target.to_string(),
+        None => match upstream {
+            true => {
+                util::print_operation(format!(
+                    "provider: get default branch for upstream {}",
+                    style(&name).yellow()
+                ));
+                provider.get_default_branch(&name)?
+            }
+            false => GitBranch::default()?,
+        },
+    };
+
+    provider.get_compare_url(&name, &base, &head, remote)
+}
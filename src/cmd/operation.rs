@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+
+use crate::cmd::Run;
+use crate::cmd::{Abort, Continue};
+use crate::util::GitOperation;
+use crate::util::GitRebase;
+use crate::util::Shell;
+
+impl Run for Continue {
+    fn run(&self) -> Result<()> {
+        let (root, op) = detect()?;
+        let root = root.display().to_string();
+        let mut git = Shell::git();
+        git.with_git_path(&root)
+            .args([op.command(), "--continue"])
+            .inherit();
+        // The rebase might stop again at the next commit, help user to
+        // resolve the conflicts.
+        match op {
+            GitOperation::Rebase => GitRebase::run(&mut git),
+            _ => git.exec().map(|_| ()),
+        }
+    }
+}
+
+impl Run for Abort {
+    fn run(&self) -> Result<()> {
+        let (root, op) = detect()?;
+        Shell::git()
+            .with_git_path(root.display().to_string())
+            .args([op.command(), "--abort"])
+            .exec()?;
+        Ok(())
+    }
+}
+
+fn detect() -> Result<(PathBuf, GitOperation)> {
+    let root = Shell::git()
+        .mute()
+        .args(["rev-parse", "--show-toplevel"])
+        .exec()?;
+    let root = PathBuf::from(root);
+    match GitOperation::detect(&root)? {
+        Some(op) => Ok((root, op)),
+        None => bail!("there is no git operation in progress"),
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+
+use crate::cmd::Outdated;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::{Database, Repo};
+use crate::util;
+use crate::util::Shell;
+
+/// The package ecosystem of a repo, detected by the manifest file in the
+/// root of repo.
+#[derive(Clone, Copy, PartialEq)]
+enum Ecosystem {
+    Cargo,
+    Npm,
+    Go,
+}
+
+impl Ecosystem {
+    const ALL: [Ecosystem; 3] = [Ecosystem::Cargo, Ecosystem::Npm, Ecosystem::Go];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "cargo",
+            Ecosystem::Npm => "npm",
+            Ecosystem::Go => "go",
+        }
+    }
+
+    fn manifest(&self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "Cargo.toml",
+            Ecosystem::Npm => "package.json",
+            Ecosystem::Go => "go.mod",
+        }
+    }
+
+    /// The program required to list outdated dependencies, cargo needs the
+    /// `cargo-outdated` plugin.
+    fn program(&self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "cargo-outdated",
+            Ecosystem::Npm => "npm",
+            Ecosystem::Go => "go",
+        }
+    }
+
+    fn args(&self) -> &'static [&'static str] {
+        match self {
+            Ecosystem::Cargo => &["cargo", "outdated", "--root-deps-only"],
+            Ecosystem::Npm => &["npm", "outdated"],
+            Ecosystem::Go => &["go", "list", "-u", "-m", "all"],
+        }
+    }
+
+    /// Run the command in the repo and return the outdated dependencies, one
+    /// per line.
+    fn list(&self, path: &Path, env: &BTreeMap<&str, &str>) -> Result<Vec<String>> {
+        let args = self.args();
+        // The `npm outdated` exits with 1 if there are outdated dependencies,
+        // so the exit code cannot be handled by `Shell`. It also exits with 1
+        // on error, but without output.
+        let output = Command::new(args[0])
+            .args(&args[1..])
+            .envs(env)
+            .current_dir(path)
+            .stdin(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .with_context(|| format!("could not launch {}", args[0]))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let ok = match self {
+            Ecosystem::Npm => match output.status.code() {
+                Some(0) => true,
+                Some(1) => !stdout.trim().is_empty(),
+                _ => false,
+            },
+            _ => output.status.success(),
+        };
+        if !ok {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let msg = stderr.lines().last().unwrap_or_default();
+            bail!("{} failed: {}", args.join(" "), msg)
+        }
+
+        let lines = stdout
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty());
+        let deps: Vec<&str> = match self {
+            // The table of cargo-outdated and npm starts with a header, the
+            // cargo-outdated also has a separator line.
+            Ecosystem::Cargo => lines
+                .skip(1)
+                .filter(|line| !line.starts_with("----") && !line.starts_with("All dependencies"))
+                .collect(),
+            Ecosystem::Npm => lines.skip(1).collect(),
+            // Go shows the available update in brackets, like
+            // "golang.org/x/net v0.1.0 [v0.2.0]".
+            Ecosystem::Go => lines.filter(|line| line.ends_with(']')).collect(),
+        };
+        Ok(deps.into_iter().map(String::from).collect())
+    }
+}
+
+impl Run for Outdated {
+    fn run(&self) -> Result<()> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+
+        let remote = self.args.first().map(String::as_str).unwrap_or_default();
+        let group = self.args.get(1).map(String::as_str).unwrap_or_default();
+        if !remote.is_empty() {
+            cfg.must_get_remote(remote)?;
+        }
+
+        let mut targets: Vec<(&Repo, PathBuf, Ecosystem)> = vec![];
+        let mut missing: Vec<Ecosystem> = vec![];
+        for repo in db.filter(remote, group) {
+            let path = repo.path(&cfg.workspace)?;
+            if !path.exists() {
+                continue;
+            }
+            for ecosystem in Ecosystem::ALL {
+                if !path.join(ecosystem.manifest()).exists() {
+                    continue;
+                }
+                if missing.contains(&ecosystem) {
+                    continue;
+                }
+                if !Shell::cmd_exists(ecosystem.program()) {
+                    _ = writeln!(
+                        io::stderr(),
+                        "{} skip {} repos, {} is not installed",
+                        util::Palette::get().warning.apply_to("warning"),
+                        ecosystem.name(),
+                        style(ecosystem.program()).yellow()
+                    );
+                    missing.push(ecosystem);
+                    continue;
+                }
+                targets.push((repo, path.clone(), ecosystem));
+            }
+        }
+        if targets.is_empty() {
+            _ = writeln!(io::stderr(), "nothing to do");
+            return Ok(());
+        }
+
+        let results = util::parallel(&targets, |(repo, path, ecosystem)| {
+            let env = match cfg.get_remote(&repo.remote) {
+                Some(remote) => remote.get_env(&repo.name),
+                None => BTreeMap::new(),
+            };
+            ecosystem.list(path, &env)
+        });
+        let mut total = 0;
+        let mut repos = 0;
+        for ((repo, _, ecosystem), result) in targets.iter().zip(results) {
+            let deps = match result {
+                Ok(deps) => deps,
+                Err(err) => {
+                    _ = writeln!(
+                        io::stderr(),
+                        "{} check {}:{} failed: {:#}",
+                        util::Palette::get().error.apply_to("error"),
+                        style(&repo.remote).yellow(),
+                        style(&repo.name).yellow(),
+                        err
+                    );
+                    continue;
+                }
+            };
+            if deps.is_empty() {
+                continue;
+            }
+            println!(
+                "{}:{} ({})",
+                style(&repo.remote).yellow(),
+                style(&repo.name).yellow(),
+                style(ecosystem.name()).cyan()
+            );
+            for dep in deps.iter() {
+                println!("  {}", dep);
+            }
+            total += deps.len();
+            repos += 1;
+        }
+        _ = writeln!(
+            io::stderr(),
+            "{} outdated dependencies in {} repos",
+            style(total).yellow(),
+            style(repos).yellow()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use std::io;
+use std::io::Write;
+
+use anyhow::Result;
+use console::style;
+
+use crate::api::{self, Provider};
+use crate::cmd::mine;
+use crate::cmd::Review;
+use crate::cmd::Run;
+use crate::cmd::{Pr, PrAction};
+use crate::config::Config;
+use crate::db::Database;
+use crate::util::{self, GitBranch, GitRemote};
+
+impl Run for Pr {
+    fn run(&self) -> Result<()> {
+        match &self.action {
+            PrAction::List { upstream, open } => Self::list(*upstream, *open),
+            PrAction::Checkout { number, upstream } => Self::checkout(*number, *upstream),
+        }
+    }
+}
+
+impl Pr {
+    fn list(upstream: bool, open: bool) -> Result<()> {
+        let mut db = Database::open()?;
+        let cfg = Config::parse()?;
+        let now = util::current_time()?;
+        let repo = db.current_or_detect(&cfg)?;
+        let remote = cfg.must_get_remote(&repo.remote)?;
+        let provider = api::create_provider(remote)?;
+
+        let target = Self::target(provider.as_ref(), &repo.name, upstream)?;
+        util::print_operation(format!(
+            "provider: list merges for {}",
+            style(&target).yellow()
+        ));
+        let merges = provider.list_merges(&target)?;
+        if merges.is_empty() {
+            _ = writeln!(io::stderr(), "no open merge");
+            return Ok(());
+        }
+
+        if !open {
+            for merge in merges.iter() {
+                let author = match merge.author.is_empty() {
+                    true => String::new(),
+                    false => format!(" {}", style(format!("@{}", merge.author)).cyan()),
+                };
+                println!(
+                    "#{} {}{} {}{} {}",
+                    merge.number,
+                    merge.title,
+                    author,
+                    style(&merge.source).magenta(),
+                    mine::format_ci(&merge.ci),
+                    style(util::format_since(now, merge.created)).dim()
+                );
+            }
+            return Ok(());
+        }
+
+        let keys: Vec<String> = merges
+            .iter()
+            .map(|merge| match merge.author.is_empty() {
+                true => format!("#{} {}", merge.number, merge.title),
+                false => format!("#{} {} @{}", merge.number, merge.title, merge.author),
+            })
+            .collect();
+        let mut fzf = util::Fzf::build(&[])?;
+        let merge = &merges[fzf.query(&keys)?];
+        cfg.open_url(&merge.url)
+    }
+
+    fn checkout(number: u64, upstream: bool) -> Result<()> {
+        GitBranch::ensure_no_uncommitted()?;
+        let mut db = Database::open()?;
+        let cfg = Config::parse()?;
+        let repo = db.current_or_detect(&cfg)?;
+        let remote = cfg.must_get_remote(&repo.remote)?;
+        let provider = api::create_provider(remote)?;
+
+        // The merge ref lives in the target repo, for a forked repo, this
+        // is the upstream.
+        let git_remote = GitRemote::build(upstream)?;
+        let target = Self::target(provider.as_ref(), &repo.name, upstream)?;
+        util::print_operation(format!(
+            "provider: get merge {}#{}",
+            style(&target).yellow(),
+            number
+        ));
+        Review::checkout_source(
+            provider.as_ref(),
+            remote,
+            &target,
+            git_remote.as_str(),
+            number,
+        )
+    }
+
+    /// The repo holding the merges (or issues), the upstream in upstream
+    /// mode.
+    pub fn target(provider: &dyn Provider, name: &str, upstream: bool) -> Result<String> {
+        if !upstream {
+            return Ok(name.to_string());
+        }
+        util::print_operation(format!(
+            "provider: try to get upstream for {}",
+            style(name).yellow()
+        ));
+        provider.get_upstream(name)
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::{bail, Result};
+use console::style;
+
+use crate::api;
+use crate::cmd::Publish;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+use crate::util::Shell;
+
+impl Run for Publish {
+    fn run(&self) -> Result<()> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+        let repo = db.current(&cfg)?;
+        let remote = cfg.must_get_remote(&repo.remote)?;
+        let clone = match &remote.clone {
+            Some(clone) => clone,
+            None => bail!(
+                "remote {} does not have clone config, cannot publish repository to it",
+                style(&remote.name).yellow()
+            ),
+        };
+
+        let origin = Shell::git()
+            .mute()
+            .args(["config", "--get", "remote.origin.url"])
+            .exec()
+            .unwrap_or_default();
+        if !origin.is_empty() {
+            bail!(
+                "repository already has origin {}, no need to publish",
+                style(origin).yellow()
+            )
+        }
+        // Publish the initial branch the repo was created with, see
+        // `default_branch` in remote.
+        let branch = match remote.default_branch.as_str() {
+            "" => Shell::git()
+                .mute()
+                .args(["config", "--get", "init.defaultBranch"])
+                .exec()
+                .unwrap_or_else(|_| String::from("master")),
+            branch => branch.to_string(),
+        };
+        let exists = Shell::git()
+            .mute()
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("refs/heads/{}", branch))
+            .exec()
+            .is_ok();
+        if !exists {
+            bail!(
+                "branch {} does not exist, please commit to it first",
+                style(&branch).yellow()
+            )
+        }
+
+        let url = repo.clone_url(clone);
+        util::confirm(format!(
+            "do you want to publish {} to {}",
+            style(&repo.name).yellow(),
+            style(&url).yellow()
+        ))?;
+
+        if self.create {
+            let provider = api::create_provider(remote)?;
+            util::print_operation(format!("create {} ...", style(&repo.name).yellow()));
+            provider.create_repo(&repo.name, self.private)?;
+        }
+
+        Shell::git()
+            .args(["remote", "add", "origin", url.as_str()])
+            .exec()?;
+        Shell::git()
+            .args(["push", "--set-upstream", "origin", branch.as_str()])
+            .exec()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{bail, Result};
+use console::style;
+
+use crate::cmd::Push;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+use crate::util::GitBranch;
+use crate::util::Shell;
+
+impl Run for Push {
+    fn run(&self) -> Result<()> {
+        let branch = GitBranch::current()?;
+        if branch.is_empty() {
+            bail!("you are not on a branch, please checkout one first")
+        }
+        if !self.force {
+            Shell::git()
+                .args(["push", "--set-upstream", "origin", branch.as_str()])
+                .exec()?;
+            return Ok(());
+        }
+
+        if self.is_protected(&branch)? {
+            bail!(
+                "branch {} is protected, refuse to force push",
+                style(&branch).yellow()
+            )
+        }
+
+        let (remote_head, discard) = Self::discarded_commits(&branch)?;
+        // "--force-if-includes" makes sure that the remote commits we are
+        // going to overwrite were integrated locally. If user confirms to
+        // discard some remote commits, lease to exactly the remote head we
+        // showed instead.
+        let mut lease = String::from("--force-with-lease");
+        if !discard.is_empty() {
+            println!();
+            println!(
+                "Force push will discard {} remote commits:",
+                style(discard.len()).yellow()
+            );
+            for commit in &discard {
+                println!("  * {}", commit);
+            }
+            println!();
+            util::confirm("continue")?;
+            println!();
+            lease = format!("--force-with-lease={}:{}", branch, remote_head);
+        }
+
+        Shell::git()
+            .args([
+                "push",
+                lease.as_str(),
+                "--force-if-includes",
+                "--set-upstream",
+                "origin",
+                branch.as_str(),
+            ])
+            .exec()?;
+
+        Ok(())
+    }
+}
+
+impl Push {
+    fn is_protected(&self, branch: &str) -> Result<bool> {
+        if branch == GitBranch::default()? {
+            return Ok(true);
+        }
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+        // Push can be used in repo not managed by us, only the default branch
+        // is protected for it.
+        let repo = match db.current(&cfg) {
+            Ok(repo) => repo,
+            Err(_) => return Ok(false),
+        };
+        let remote = cfg.must_get_remote(&repo.remote)?;
+        Ok(remote.is_protected(branch))
+    }
+
+    /// Get the remote head of the branch and the remote commits that are
+    /// not included in local branch.
+    fn discarded_commits(branch: &str) -> Result<(String, Vec<String>)> {
+        let remote_ref = format!("refs/heads/{}", branch);
+        let output = Shell::git()
+            .args(["ls-remote", "origin", remote_ref.as_str()])
+            .exec()?;
+        // The branch was not pushed before, nothing to discard.
+        let remote_head = match output.split_whitespace().next() {
+            Some(head) => head.to_string(),
+            None => return Ok((String::new(), vec![])),
+        };
+        // Use an empty refmap so that the remote-tracking branch is not
+        // updated, otherwise the lease would be meaningless.
+        Shell::git()
+            .args(["fetch", "--refmap=", "origin", remote_ref.as_str()])
+            .exec()?;
+        let range = format!("HEAD..{}", remote_head);
+        let output = Shell::git()
+            .args(["log", "--oneline", range.as_str()])
+            .exec()?;
+        let commits = output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.trim().to_string())
+            .collect();
+        Ok((remote_head, commits))
+    }
+}
@@ -4,6 +4,7 @@ use crate::cmd::Rebase;
 use crate::cmd::Run;
 use crate::util;
 use crate::util::GitBranch;
+use crate::util::GitRebase;
 use crate::util::GitRemote;
 use crate::util::Shell;
 
@@ -12,8 +13,6 @@ impl Run for Rebase {
         GitBranch::ensure_no_uncommitted()?;
         let remote = GitRemote::build(self.upstream)?;
         let target = remote.target(util::option_arg(&self.args))?;
-        Shell::git().args(["rebase", target.as_str()]).exec()?;
-
-        Ok(())
+        GitRebase::run(Shell::git().args(["rebase", target.as_str()]))
     }
 }
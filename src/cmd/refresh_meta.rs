@@ -0,0 +1,132 @@
+use std::env;
+use std::io;
+use std::io::Write;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Result};
+use console::style;
+
+use crate::api;
+use crate::cmd::RefreshMeta;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::{Database, Epoch, Repo, RepoMeta};
+use crate::util;
+
+impl Run for RefreshMeta {
+    fn run(&self) -> Result<()> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+        let now = util::current_time()?;
+
+        let remote = self.args.first().map(String::as_str).unwrap_or_default();
+        let group = self.args.get(1).map(String::as_str).unwrap_or_default();
+        if !remote.is_empty() {
+            let remote = cfg.must_get_remote(remote)?;
+            if remote.api.is_none() {
+                bail!(
+                    "remote {} does not enable api provider, please config it first",
+                    style(&remote.name).yellow()
+                )
+            }
+        }
+        // Only the repos whose remote has api provider have metadata. The
+        // group can also be the name of a repo, to refresh only it.
+        let repos: Vec<&Repo> = db
+            .filter(remote, "")
+            .into_iter()
+            .filter(|repo| {
+                group.is_empty()
+                    || repo.name == group
+                    || repo
+                        .name
+                        .starts_with(&format!("{}/", group.trim_end_matches('/')))
+            })
+            .filter(|repo| match cfg.get_remote(&repo.remote) {
+                Some(remote) => remote.api.is_some(),
+                None => false,
+            })
+            .collect();
+        if repos.is_empty() {
+            _ = writeln!(io::stderr(), "nothing to do");
+            return Ok(());
+        }
+
+        let results = util::parallel(&repos, |repo| fetch_meta(&cfg, repo, now));
+        let mut metas = Vec::with_capacity(results.len());
+        let mut failed = 0;
+        for (repo, result) in repos.iter().zip(results) {
+            match result {
+                Ok(meta) => metas.push((repo.remote.clone(), repo.name.clone(), meta)),
+                Err(err) => {
+                    _ = writeln!(
+                        io::stderr(),
+                        "{} refresh {}:{} failed: {:#}",
+                        util::Palette::get().error.apply_to("error"),
+                        style(&repo.remote).yellow(),
+                        style(&repo.name).yellow(),
+                        err
+                    );
+                    failed += 1;
+                }
+            }
+        }
+        let total = metas.len() + failed;
+        // The fetching might take a while, reopen the database to not lose
+        // the changes made meanwhile, such as the jumps.
+        let mut db = Database::open()?;
+        for (remote, name, meta) in metas {
+            if let Some(idx) = db.get(&remote, &name) {
+                db.repos[idx].meta = Some(meta);
+            }
+        }
+        db.save()?;
+
+        if failed > 0 {
+            bail!("{} of {} repos failed to refresh", failed, total)
+        }
+        _ = writeln!(io::stderr(), "refreshed {} repos", style(total).yellow());
+        Ok(())
+    }
+}
+
+fn fetch_meta(cfg: &Config, repo: &Repo, now: Epoch) -> Result<RepoMeta> {
+    let remote = cfg.must_get_remote(&repo.remote)?;
+    let provider = api::create_provider(remote)?;
+    let mut meta = provider.get_meta(&repo.name)?;
+    meta.updated = now;
+    Ok(meta)
+}
+
+/// Refresh the expired metadata of the repo to jump, if `repo_meta` is
+/// enabled. The provider might be slow or rate limited, so the refresh runs
+/// in a detached `refresh-meta` process rather than blocking the jump, its
+/// errors are discarded, run `refresh-meta` to see them. The process writes
+/// the database too, call this after the database is saved.
+pub fn refresh_on_jump(db: &Database, cfg: &Config, remote: &str, name: &str, now: Epoch) {
+    if !cfg.repo_meta {
+        return;
+    }
+    match db.get(remote, name) {
+        Some(idx) if db.repos[idx].meta_expired(now) => {}
+        _ => return,
+    }
+    match cfg.get_remote(remote) {
+        Some(remote) if remote.api.is_some() => {}
+        _ => return,
+    }
+    let exe = match env::current_exe() {
+        Ok(exe) => exe,
+        Err(_) => return,
+    };
+    // A new process group, so that the process is not killed along with
+    // the shell job, such as the Ctrl-C after jumping.
+    _ = Command::new(exe)
+        .args(["refresh-meta", remote, name])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn();
+}
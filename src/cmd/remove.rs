@@ -1,16 +1,22 @@
 use std::fs;
 use std::io;
 
+use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
+use console::style;
 
+use crate::api::{self, Provider};
+
+use crate::cmd::archive;
 use crate::cmd::Remove;
 use crate::cmd::Run;
 
 use crate::config::Config;
-use crate::db::{Database, Repo};
+use crate::db::{Database, HistoryEntry, Repo};
 use crate::errors::SilentExit;
 use crate::util;
+use crate::util::Shell;
 
 impl Run for Remove {
     fn run(&self) -> Result<()> {
@@ -18,7 +24,22 @@ impl Run for Remove {
         let cfg = Config::parse()?;
 
         let idx = db.must_get(&self.remote, &self.name)?;
-        self.ensure_path(&db, &cfg, &db.repos[idx])?;
+        let provider = match self.remote_too {
+            true => Some(Self::confirm_remote(&cfg, &db.repos[idx])?),
+            false => None,
+        };
+        // The remote deletion cannot be undone, it is the last step, so that
+        // a declined or failed local removal keeps the remote.
+        let removed = remove_path(&db, &cfg, &db.repos[idx], self.force)?;
+        if provider.is_some() && !removed {
+            bail!(SilentExit { code: 60 })
+        }
+        archive::remove_archived(&cfg, &db.repos[idx], self.force)?;
+        if let Some(provider) = provider {
+            let name = &db.repos[idx].name;
+            util::print_operation(format!("delete {} ...", style(name).yellow()));
+            provider.delete_repo(name)?;
+        }
 
         db.repos.remove(idx);
         db.save()?;
@@ -27,31 +48,67 @@ impl Run for Remove {
 }
 
 impl Remove {
-    fn ensure_path(&self, db: &Database, cfg: &Config, repo: &Repo) -> Result<()> {
-        let path = repo.path(&cfg.workspace)?;
-        match fs::read_dir(&path) {
-            Ok(_) => {
-                let mut remove = self.force;
-                if !remove {
-                    match util::confirm(format!("do you want to remove {}", path.display())) {
-                        Ok(_) => remove = true,
-                        Err(err) => match err.downcast::<SilentExit>() {
-                            Ok(_) => return Ok(()),
-                            Err(err) => return Err(err),
-                        },
-                    };
+    fn confirm_remote(cfg: &Config, repo: &Repo) -> Result<Box<dyn Provider>> {
+        let remote = cfg.must_get_remote(&repo.remote)?;
+        let provider = api::create_provider(remote)?;
+        util::confirm(format!(
+            "do you want to delete {} in remote {}, this cannot be undone",
+            style(&repo.name).yellow(),
+            style(&remote.name).yellow()
+        ))?;
+        util::confirm_input(
+            format!("please type {} to confirm", style(&repo.name).yellow()),
+            &repo.name,
+        )?;
+        Ok(provider)
+    }
+}
+
+/// Remove the directory of repo in workspace, the empty parent directories
+/// are cleaned. With `force`, the confirm is skipped. Return false if the
+/// user declined the removal.
+pub fn remove_path(db: &Database, cfg: &Config, repo: &Repo, force: bool) -> Result<bool> {
+    let path = repo.path(&cfg.workspace)?;
+    match fs::read_dir(&path) {
+        Ok(_) => {
+            let mut remove = force;
+            if !remove {
+                match util::confirm(format!("do you want to remove {}", path.display())) {
+                    Ok(_) => remove = true,
+                    Err(err) => match err.downcast::<SilentExit>() {
+                        Ok(_) => return Ok(false),
+                        Err(err) => return Err(err),
+                    },
+                };
+            }
+            if remove {
+                util::check_processes(cfg, &path, force)?;
+                let path_str = util::path_to_str(&path)?;
+                if util::maintenance_repos()?
+                    .iter()
+                    .any(|repo| repo == path_str)
+                {
+                    Shell::git()
+                        .with_git_path(path_str)
+                        .args(["maintenance", "unregister"])
+                        .exec()?;
                 }
-                if remove {
-                    fs::remove_dir_all(&path)?;
-                    let paths = db.list_paths(&cfg.workspace)?;
-                    let empty_dir = util::EmptyDir::scan(&cfg.workspace, &paths)?;
-                    empty_dir.clean()?;
+                fs::remove_dir_all(&path)?;
+                if cfg.zoxide {
+                    util::zoxide("remove", &path);
                 }
-                Ok(())
+                HistoryEntry::new("remove")
+                    .repo(&repo.remote, &repo.name)
+                    .path(path_str)
+                    .record();
+                let paths = db.list_paths(&cfg.workspace)?;
+                let empty_dir = util::EmptyDir::scan(&cfg.workspace, &paths)?;
+                empty_dir.clean()?;
             }
-            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
-            Err(err) => Err(err)
-                .with_context(|| format!("could not read repository directory {}", path.display())),
+            Ok(true)
         }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(true),
+        Err(err) => Err(err)
+            .with_context(|| format!("could not read repository directory {}", path.display())),
     }
 }
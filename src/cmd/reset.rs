@@ -2,6 +2,7 @@ use anyhow::Result;
 
 use crate::cmd::Reset;
 use crate::cmd::Run;
+use crate::db::HistoryEntry;
 use crate::util;
 use crate::util::GitBranch;
 use crate::util::GitRemote;
@@ -23,9 +24,15 @@ impl Run for Reset {
             }
         };
 
+        // Record the commit before resetting, so that it can be found back.
+        let head = Shell::git().args(["rev-parse", "HEAD"]).exec()?;
         Shell::git()
             .args(["reset", "--hard", target.as_str()])
             .exec()?;
+        HistoryEntry::new("reset")
+            .current_path()
+            .detail(format!("{} -> {}", head, target))
+            .record();
 
         Ok(())
     }
@@ -0,0 +1,261 @@
+use anyhow::{bail, Result};
+use console::style;
+
+use crate::api::{self, Provider};
+use crate::cmd::Review;
+use crate::cmd::Run;
+use crate::config::{Config, Remote};
+use crate::db::Database;
+use crate::util;
+use crate::util::GitBranch;
+use crate::util::GitRemote;
+use crate::util::Shell;
+
+impl Run for Review {
+    fn run(&self) -> Result<()> {
+        GitBranch::ensure_no_uncommitted()?;
+        if self.done {
+            return self.done();
+        }
+        let number = match util::option_arg(&self.args) {
+            Some(number) => Self::parse_number(number)?,
+            None => bail!("please provide the merge number to review"),
+        };
+
+        let mut db = Database::open()?;
+        let config = Config::parse()?;
+        let repo = db.current_or_detect(&config)?;
+        let remote = config.must_get_remote(&repo.remote)?;
+        let provider = api::create_provider(remote)?;
+
+        // The merge ref lives in the target repo, for a forked repo, this
+        // is the upstream.
+        let git_remote = GitRemote::build(self.upstream)?;
+        Self::checkout(
+            provider.as_ref(),
+            &repo.name,
+            git_remote.as_str(),
+            number,
+            None,
+        )
+    }
+}
+
+impl Review {
+    const BRANCH_PREFIX: &str = "review/";
+
+    /// The branch used when the source branch is taken, see
+    /// [`Review::checkout_source`].
+    const SOURCE_PREFIX: &str = "pr/";
+
+    /// Checkout the merge ref into a review branch in current directory, the
+    /// branch is `review/<number>` if `branch` is `None`. The existing branch
+    /// is fast-forwarded, see [`Review::switch`].
+    pub fn checkout(
+        provider: &dyn Provider,
+        repo: &str,
+        git_remote: &str,
+        number: u64,
+        branch: Option<&str>,
+    ) -> Result<()> {
+        let merge_ref = provider.get_merge_ref(repo, number)?;
+
+        let base = GitBranch::current()?;
+        let branch = match branch {
+            Some(branch) => branch.to_string(),
+            None => Self::branch_name(number),
+        };
+        Shell::git()
+            .args(["fetch", git_remote, merge_ref.as_str()])
+            .exec()?;
+        if Self::switch(&branch, "FETCH_HEAD")? {
+            Self::record(&branch, &merge_ref, &base)?;
+        }
+        Ok(())
+    }
+
+    /// Checkout the source branch of the merge into a local branch tracking
+    /// it, so that the new commits can be pulled. For a merge from fork, the
+    /// fork is added as a git remote named by its owner if missing. The
+    /// local branch is `pr/<number>` if the source branch is taken by another
+    /// local branch, such as the "main" of fork. If the provider does not
+    /// report the source, the merge ref is checked out into `pr/<number>`
+    /// instead, see [`Review::checkout`].
+    pub fn checkout_source(
+        provider: &dyn Provider,
+        remote: &Remote,
+        repo: &str,
+        git_remote: &str,
+        number: u64,
+    ) -> Result<()> {
+        let fallback = format!("{}{}", Self::SOURCE_PREFIX, number);
+        let source = match provider.get_merge_source(repo, number)? {
+            Some(source) => source,
+            None => return Self::checkout(provider, repo, git_remote, number, Some(&fallback)),
+        };
+        let merge_ref = provider.get_merge_ref(repo, number)?;
+
+        let source_remote = match source.repo == repo {
+            true => git_remote.to_string(),
+            false => {
+                let owner = source.repo.split('/').next().unwrap_or_default();
+                GitRemote::ensure(remote, &source.repo, owner)?
+                    .as_str()
+                    .to_string()
+            }
+        };
+        let upstream = format!("{}/{}", source_remote, source.branch);
+        Shell::git()
+            .args(["fetch", source_remote.as_str()])
+            .arg(format!(
+                "+refs/heads/{}:refs/remotes/{}",
+                source.branch, upstream
+            ))
+            .exec()?;
+
+        // The branch checked out from this merge before, or tracking the
+        // source already, is reused.
+        let source_merge = format!("refs/heads/{}", source.branch);
+        let taken = |branch: &str| {
+            let tracked = Self::config_of(branch, "remote").as_ref() == Some(&source_remote)
+                && Self::config_of(branch, "merge").as_ref() == Some(&source_merge);
+            Self::branch_exists(branch)
+                && !tracked
+                && Self::merge_ref_of(branch).as_ref() != Some(&merge_ref)
+        };
+        let mut branch = source.branch.clone();
+        if taken(&branch) {
+            branch = fallback;
+            if taken(&branch) {
+                bail!(
+                    "branch {} already exists, please delete or rename it first",
+                    style(&branch).yellow()
+                )
+            }
+        }
+
+        let base = GitBranch::current()?;
+        if !Self::switch(&branch, &upstream)? {
+            return Ok(());
+        }
+        Shell::git()
+            .args([
+                "config",
+                &Self::config_key(&branch, "remote"),
+                &source_remote,
+            ])
+            .exec()?;
+        Shell::git()
+            .args(["config", &Self::config_key(&branch, "merge"), &source_merge])
+            .exec()?;
+        Self::record(&branch, &merge_ref, &base)
+    }
+
+    /// Switch to `branch` at `start`, returns true if the branch is created.
+    /// The existing branch is fast-forwarded, it is refused if the branch has
+    /// diverged, so that the local commits are not reset.
+    fn switch(branch: &str, start: &str) -> Result<bool> {
+        if !Self::branch_exists(branch) {
+            Shell::git()
+                .args(["checkout", "-b", branch, start])
+                .exec()?;
+            return Ok(true);
+        }
+        let is_ancestor = |from: &str, to: &str| {
+            Shell::git()
+                .mute()
+                .args(["merge-base", "--is-ancestor", from, to])
+                .exec()
+                .is_ok()
+        };
+        let behind = is_ancestor(branch, start);
+        if !behind && !is_ancestor(start, branch) {
+            bail!(
+                "branch {} has diverged from the merge, please rebase or delete it first",
+                style(branch).yellow()
+            )
+        }
+        Shell::git().args(["checkout", branch]).exec()?;
+        if behind {
+            Shell::git().args(["merge", "--ff-only", start]).exec()?;
+        }
+        Ok(false)
+    }
+
+    /// Record the mapping in git config of the branch, it will be removed
+    /// together with the branch.
+    fn record(branch: &str, merge_ref: &str, base: &str) -> Result<()> {
+        Shell::git()
+            .args(["config", &Self::config_key(branch, "gz-review"), merge_ref])
+            .exec()?;
+        if !base.is_empty() && base != branch {
+            Shell::git()
+                .args(["config", &Self::config_key(branch, "gz-review-base"), base])
+                .exec()?;
+        }
+        Ok(())
+    }
+
+    fn branch_exists(branch: &str) -> bool {
+        Shell::git()
+            .mute()
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("refs/heads/{}", branch))
+            .exec()
+            .is_ok()
+    }
+
+    /// The merge ref checked out into the branch, `None` if the branch is not
+    /// a review branch.
+    pub fn merge_ref_of(branch: &str) -> Option<String> {
+        Self::config_of(branch, "gz-review")
+    }
+
+    fn config_of(branch: &str, name: &str) -> Option<String> {
+        Shell::git()
+            .mute()
+            .args(["config", "--get", &Self::config_key(branch, name)])
+            .exec()
+            .ok()
+    }
+
+    fn done(&self) -> Result<()> {
+        let current = GitBranch::current()?;
+        let branch = match util::option_arg(&self.args) {
+            Some(number) => Self::branch_name(Self::parse_number(number)?),
+            None => current.clone(),
+        };
+        if Self::merge_ref_of(&branch).is_none() {
+            bail!("{} is not a review branch", style(&branch).yellow())
+        }
+
+        if branch == current {
+            let key = Self::config_key(&branch, "gz-review-base");
+            let back = match Shell::git().args(["config", "--get", &key]).exec() {
+                Ok(base) => base,
+                Err(_) => GitBranch::default()?,
+            };
+            Shell::git().args(["checkout", back.as_str()]).exec()?;
+        }
+        Shell::git()
+            .args(["branch", "-D", branch.as_str()])
+            .exec()?;
+
+        Ok(())
+    }
+
+    fn parse_number(s: &str) -> Result<u64> {
+        match s.parse() {
+            Ok(number) => Ok(number),
+            Err(_) => bail!("invalid merge number {}", style(s).yellow()),
+        }
+    }
+
+    fn branch_name(number: u64) -> String {
+        format!("{}{}", Self::BRANCH_PREFIX, number)
+    }
+
+    fn config_key(branch: &str, name: &str) -> String {
+        format!("branch.{}.{}", branch, name)
+    }
+}
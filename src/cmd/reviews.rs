@@ -0,0 +1,59 @@
+use std::io;
+use std::io::Write;
+
+use anyhow::Result;
+use console::style;
+
+use crate::api;
+use crate::cmd::jump;
+use crate::cmd::mine;
+use crate::cmd::Reviews;
+use crate::cmd::Run;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+
+impl Run for Reviews {
+    fn run(&self) -> Result<()> {
+        let cfg = Config::parse()?;
+        let now = util::current_time()?;
+
+        let merges = mine::list_merges(&cfg, &self.args, |provider| provider.list_review_merges())?;
+        if merges.is_empty() {
+            _ = writeln!(io::stderr(), "no merge requests your review");
+            return Ok(());
+        }
+        if !self.checkout && !self.open && !self.approve {
+            mine::show_merges(&merges, now);
+            return Ok(());
+        }
+
+        let (remote, merge) = mine::select_merge(&merges)?;
+        if self.open {
+            return cfg.open_url(&merge.url);
+        }
+        let provider = api::create_provider(remote)?;
+        if self.approve {
+            util::confirm(format!(
+                "do you want to approve {}:{}#{}",
+                remote.name,
+                style(&merge.repo).yellow(),
+                merge.number
+            ))?;
+            return provider.approve_merge(&merge.repo, merge.number);
+        }
+
+        let mut db = Database::open()?;
+        let idx = match db.get(&remote.name, &merge.repo) {
+            Some(idx) => idx,
+            None => {
+                let idx = db.add(&remote.name, &merge.repo, "");
+                db.resolve_collision(idx, &cfg)?;
+                idx
+            }
+        };
+        jump::enter(&mut db, &cfg, idx, now, |_, path| {
+            mine::checkout_merge(provider.as_ref(), remote, &merge.repo, merge.number, path)
+        })
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use console::style;
+
+use crate::cmd::Run;
+use crate::cmd::Scan;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+
+#[derive(Clone, Copy)]
+enum State {
+    Tracked,
+    Untracked,
+    Missing,
+}
+
+/// A directory in the scanned tree, with the counts of repos under it.
+#[derive(Default)]
+struct Node {
+    children: BTreeMap<String, Node>,
+
+    /// The state of repo if the directory is a repo itself.
+    state: Option<State>,
+
+    tracked: usize,
+    untracked: usize,
+    missing: usize,
+}
+
+impl Run for Scan {
+    fn run(&self) -> Result<()> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+        let workspace = PathBuf::from(&cfg.workspace);
+        let ignored = |rel: &str| {
+            cfg.scan_ignore
+                .iter()
+                .any(|pattern| util::glob_match(pattern, rel))
+        };
+
+        let mut root = Node::default();
+        let paths = db.list_paths(&cfg.workspace)?;
+        for path in paths.iter() {
+            // The repos attached outside workspace are not in the layout.
+            let rel = match path.strip_prefix(&workspace).ok().and_then(Path::to_str) {
+                Some(rel) => rel,
+                None => continue,
+            };
+            if ignored(rel) {
+                continue;
+            }
+            let state = match path.exists() {
+                true => State::Tracked,
+                false => State::Missing,
+            };
+            root.add(&rel.split('/').collect::<Vec<_>>(), state, self.depth);
+        }
+
+        let tracked: HashSet<&PathBuf> = paths.iter().collect();
+        let mut untracked = vec![];
+        Self::walk(
+            &workspace,
+            "",
+            1,
+            self.depth,
+            &tracked,
+            &ignored,
+            &mut untracked,
+        )?;
+        for rel in untracked {
+            root.add(
+                &rel.split('/').collect::<Vec<_>>(),
+                State::Untracked,
+                self.depth,
+            );
+        }
+
+        println!("{} {}", workspace.display(), root.counts());
+        root.show(1);
+        Ok(())
+    }
+}
+
+impl Scan {
+    /// Find the untracked repos under `dir`, the repos, tracked and ignored
+    /// directories are not walked into, nor the directories deeper than
+    /// `depth`. The found repos are relative to workspace.
+    fn walk(
+        dir: &Path,
+        rel: &str,
+        level: usize,
+        depth: Option<usize>,
+        tracked: &HashSet<&PathBuf>,
+        ignored: &dyn Fn(&str) -> bool,
+        out: &mut Vec<String>,
+    ) -> Result<()> {
+        if depth.is_some_and(|depth| level > depth) {
+            return Ok(());
+        }
+        let subs = match fs::read_dir(dir) {
+            Ok(subs) => subs,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("could not read dir {}", dir.display()))
+            }
+        };
+        for sub in subs {
+            let sub = sub.context("could not read sub directory")?;
+            let file_type = sub
+                .file_type()
+                .context("could not read file type for sub directory")?;
+            if !file_type.is_dir() {
+                continue;
+            }
+            let path = sub.path();
+            if tracked.contains(&path) {
+                continue;
+            }
+            let name = sub.file_name();
+            let name = name.to_string_lossy();
+            let sub_rel = match rel {
+                "" => name.to_string(),
+                rel => format!("{}/{}", rel, name),
+            };
+            if ignored(&sub_rel) {
+                continue;
+            }
+            if path.join(".git").exists() {
+                out.push(sub_rel);
+                continue;
+            }
+            Self::walk(&path, &sub_rel, level + 1, depth, tracked, ignored, out)?;
+        }
+        Ok(())
+    }
+}
+
+impl Node {
+    /// Add the repo to the tree, the repos deeper than `depth` are counted
+    /// in their ancestor at `depth`.
+    fn add(&mut self, segments: &[&str], state: State, depth: Option<usize>) {
+        match state {
+            State::Tracked => self.tracked += 1,
+            State::Untracked => self.untracked += 1,
+            State::Missing => self.missing += 1,
+        }
+        if segments.is_empty() {
+            self.state = Some(state);
+            return;
+        }
+        if depth == Some(0) {
+            return;
+        }
+        self.children
+            .entry(segments[0].to_string())
+            .or_default()
+            .add(&segments[1..], state, depth.map(|depth| depth - 1));
+    }
+
+    fn counts(&self) -> String {
+        let mut counts = vec![];
+        if self.tracked > 0 {
+            counts.push(format!("{} tracked", style(self.tracked).green()));
+        }
+        if self.untracked > 0 {
+            counts.push(format!("{} untracked", style(self.untracked).yellow()));
+        }
+        if self.missing > 0 {
+            counts.push(format!("{} missing", style(self.missing).red()));
+        }
+        if counts.is_empty() {
+            return String::from("(empty)");
+        }
+        format!("({})", counts.join(", "))
+    }
+
+    fn show(&self, level: usize) {
+        let indent = "  ".repeat(level);
+        for (name, child) in self.children.iter() {
+            let desc = match child.state {
+                Some(State::Tracked) => style("tracked").green().to_string(),
+                Some(State::Untracked) => style("untracked").yellow().to_string(),
+                Some(State::Missing) => style("missing").red().to_string(),
+                None => child.counts(),
+            };
+            println!("{}{} {}", indent, name, desc);
+            child.show(level + 1);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::io;
+use std::io::Write;
+
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use console::style;
+
+use crate::cmd::Run;
+use crate::cmd::Set;
+use crate::config::Config;
+use crate::db::Database;
+
+impl Run for Set {
+    fn run(&self) -> Result<()> {
+        let mut db = Database::open()?;
+        let cfg = Config::parse()?;
+        let idx = db.current_index(&cfg)?;
+        let repo = &mut db.repos[idx];
+
+        match self.key.as_str() {
+            "use-ssh" => repo.use_ssh = self.parse_bool()?,
+            key => bail!("unknown option {}", style(key).yellow()),
+        }
+
+        _ = writeln!(
+            io::stderr(),
+            "set {} for {}",
+            style(&self.key).yellow(),
+            style(&repo.name).yellow()
+        );
+        db.save()
+    }
+}
+
+impl Set {
+    fn parse_bool(&self) -> Result<Option<bool>> {
+        match &self.value {
+            Some(value) => {
+                let value = value
+                    .parse()
+                    .with_context(|| format!("invalid bool value {}", style(value).yellow()))?;
+                Ok(Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::fs;
+use std::io::{self, Write};
+use std::time::Instant;
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::Run;
+use crate::cmd::{Snapshot, SnapshotAction};
+use crate::config::Config;
+use crate::db::{Database, Repo};
+use crate::errors::SilentExit;
+use crate::util;
+use crate::util::Shell;
+
+/// The manifest of a workspace, it is written in yaml so that it can be
+/// kept in dotfiles and edited by hand.
+#[derive(Serialize, Deserialize, Debug)]
+struct Manifest {
+    repos: Vec<ManifestRepo>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ManifestRepo {
+    pub remote: String,
+    pub name: String,
+
+    /// The attached path, empty means the repo is in workspace.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub path: String,
+
+    /// The branch checked out when creating the snapshot, empty means the
+    /// repo was not cloned or in detached HEAD.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub branch: String,
+}
+
+impl Run for Snapshot {
+    fn run(&self) -> Result<()> {
+        match &self.action {
+            SnapshotAction::Create { file } => self.create(file),
+            SnapshotAction::Restore { file } => self.restore(file),
+        }
+    }
+}
+
+impl Snapshot {
+    fn create(&self, file: &Option<String>) -> Result<()> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+
+        let results = util::parallel(&db.repos, |repo| Self::snapshot_repo(repo, &cfg.workspace));
+        let mut repos = Vec::with_capacity(results.len());
+        for repo in results {
+            repos.push(repo?);
+        }
+        repos.sort_by(|a, b| (&a.remote, &a.name).cmp(&(&b.remote, &b.name)));
+
+        let manifest = Manifest { repos };
+        let data = serde_yaml::to_string(&manifest).context("could not serialize manifest")?;
+        match file {
+            Some(file) => {
+                fs::write(file, data).with_context(|| {
+                    format!("could not write manifest {}", style(file).yellow())
+                })?;
+                _ = writeln!(
+                    io::stderr(),
+                    "{} repos saved to {}",
+                    manifest.repos.len(),
+                    style(file).yellow()
+                );
+            }
+            None => print!("{}", data),
+        }
+        Ok(())
+    }
+
+    fn snapshot_repo(repo: &Repo, workspace: &str) -> Result<ManifestRepo> {
+        let path = repo.path(workspace)?;
+        let branch = match path.exists() {
+            true => Shell::git()
+                .mute()
+                .with_git_path(util::path_to_str(&path)?)
+                .args(["branch", "--show-current"])
+                .exec()
+                .unwrap_or_default(),
+            false => String::new(),
+        };
+        Ok(ManifestRepo {
+            remote: repo.remote.clone(),
+            name: repo.name.clone(),
+            path: repo.path.clone(),
+            branch,
+        })
+    }
+
+    fn restore(&self, file: &str) -> Result<()> {
+        let data = fs::read_to_string(file)
+            .with_context(|| format!("could not read manifest {}", style(file).yellow()))?;
+        let manifest: Manifest = serde_yaml::from_str(&data)
+            .with_context(|| format!("could not parse manifest {}", style(file).yellow()))?;
+
+        let mut db = Database::open()?;
+        let cfg = Config::parse()?;
+        let start = Instant::now();
+        let result = restore_repos(&mut db, &cfg, &manifest.repos);
+        cfg.notify_done(start, "snapshot restore is done");
+        result
+    }
+}
+
+/// Ensure the repos exist locally, the newly cloned repos will check out the
+/// recorded branches. The failed repos are reported and skipped.
+pub fn restore_repos(db: &mut Database, cfg: &Config, repos: &[ManifestRepo]) -> Result<()> {
+    let mut failed = 0;
+    for item in repos {
+        if let Err(err) = restore_repo(db, cfg, item) {
+            // The git command has already printed its error.
+            let reason = match err.downcast_ref::<SilentExit>() {
+                Some(_) => String::new(),
+                None => format!(": {:#}", err),
+            };
+            _ = writeln!(
+                io::stderr(),
+                "{} restore {}:{} failed{}",
+                util::Palette::get().error.apply_to("error"),
+                style(&item.remote).yellow(),
+                style(&item.name).yellow(),
+                reason
+            );
+            failed += 1;
+        }
+    }
+    // Save the restored repos even if some of them failed, so that
+    // restoring again will only retry the failed ones.
+    db.save()?;
+
+    if failed > 0 {
+        bail!("{} of {} repos failed to restore", failed, repos.len())
+    }
+    Ok(())
+}
+
+fn restore_repo(db: &mut Database, cfg: &Config, item: &ManifestRepo) -> Result<()> {
+    let remote = cfg.must_get_remote(&item.remote)?;
+    let idx = match db.get(&item.remote, &item.name) {
+        Some(idx) => idx,
+        None => {
+            let idx = db.add(&item.remote, &item.name, &item.path);
+            if let Err(err) = db.resolve_collision(idx, cfg) {
+                db.repos.remove(idx);
+                return Err(err);
+            }
+            idx
+        }
+    };
+    let repo = &db.repos[idx];
+    let existed = repo.path(&cfg.workspace)?.exists();
+    let path = repo.ensure_path(&cfg.workspace, remote)?;
+    // The repo without clone config is created empty, there is no
+    // branch to check out.
+    if existed || remote.clone.is_none() || item.branch.is_empty() {
+        return Ok(());
+    }
+
+    let path = util::path_to_str(&path)?;
+    let current = Shell::git()
+        .mute()
+        .with_git_path(path)
+        .args(["branch", "--show-current"])
+        .exec()?;
+    if current != item.branch {
+        Shell::git()
+            .with_git_path(path)
+            .args(["checkout", item.branch.as_str()])
+            .exec()?;
+    }
+    Ok(())
+}
@@ -4,16 +4,32 @@ use console::style;
 
 use crate::cmd::Run;
 use crate::cmd::Squash;
+use crate::config::{Config, Ticket};
+use crate::db::Database;
 use crate::util;
 use crate::util::GitBranch;
+use crate::util::GitRebase;
 use crate::util::GitRemote;
 use crate::util::Shell;
 
 impl Run for Squash {
     fn run(&self) -> Result<()> {
+        if self.fixup {
+            return self.fixup();
+        }
         GitBranch::ensure_no_uncommitted()?;
         let remote = GitRemote::build(self.upstream)?;
         let target = remote.target(util::option_arg(&self.args))?;
+        if self.autosquash {
+            // Use a no-op sequence editor to accept the todo list generated
+            // by autosquash, so that the rebase is non-interactive.
+            return GitRebase::run(
+                Shell::git()
+                    .env("GIT_SEQUENCE_EDITOR", "true")
+                    .args(["rebase", "-i", "--autosquash", target.as_str()])
+                    .inherit(),
+            );
+        }
 
         let commits = Self::commits_between(&target)?;
         if commits.is_empty() {
@@ -44,11 +60,32 @@ impl Run for Squash {
             .args(["reset", "--soft", set.as_str()])
             .exec()?;
 
+        let (trailers, ticket) = self.metadata()?;
+
+        // If the branch has a ticket but no message provided, put the ticket
+        // into the editor so that user only needs to complete it.
+        let mut edit = false;
+        let message = match (&self.message, &ticket) {
+            (Some(msg), Some(id)) => Some(Ticket::prefix(id, msg)),
+            (Some(msg), None) => Some(msg.clone()),
+            (None, Some(id)) => {
+                edit = true;
+                Some(format!("{} ", id))
+            }
+            (None, None) => None,
+        };
+
         let mut args = vec!["commit"];
-        if let Some(msg) = &self.message {
+        if let Some(msg) = &message {
             args.push("-m");
             args.push(msg);
         }
+        if edit {
+            args.push("-e");
+        }
+        for trailer in &trailers {
+            args.push(trailer);
+        }
         Shell::git().args(&args).inherit().exec()?;
 
         Ok(())
@@ -56,6 +93,54 @@ impl Run for Squash {
 }
 
 impl Squash {
+    fn fixup(&self) -> Result<()> {
+        let staged = Shell::git()
+            .args(["diff", "--cached", "--name-only"])
+            .exec()?;
+        if staged.is_empty() {
+            bail!("no staged change to fixup, please stage them first")
+        }
+        let remote = GitRemote::build(self.upstream)?;
+        let target = remote.target(util::option_arg(&self.args))?;
+
+        let commits = Self::commits_between(&target)?;
+        if commits.is_empty() {
+            bail!("no commit ahead {} to fixup", style(&target).yellow())
+        }
+        let mut fzf = util::Fzf::build(&[])?;
+        let commit = &commits[fzf.query(&commits)?];
+        let id = commit.split_whitespace().next().unwrap_or_default();
+
+        let fixup = format!("--fixup={}", id);
+        Shell::git()
+            .args(["commit", fixup.as_str()])
+            .inherit()
+            .exec()?;
+
+        Ok(())
+    }
+
+    /// Get the trailers and the ticket id for the squashed commit.
+    fn metadata(&self) -> Result<(Vec<String>, Option<String>)> {
+        let db = Database::open()?;
+        let cfg = Config::parse()?;
+        // Squash can be used in repo not managed by us, no metadata for it.
+        let repo = match db.current(&cfg) {
+            Ok(repo) => repo,
+            Err(_) => return Ok((vec![], None)),
+        };
+        let remote = cfg.must_get_remote(&repo.remote)?;
+        let trailers = match &remote.trailers {
+            Some(trailers) if !self.no_trailers => trailers.args()?,
+            _ => vec![],
+        };
+        let ticket = match &remote.ticket {
+            Some(ticket) => ticket.extract(&GitBranch::current()?)?,
+            None => None,
+        };
+        Ok((trailers, ticket))
+    }
+
     fn commits_between(target: &str) -> Result<Vec<String>> {
         let target = format!("HEAD...{}", target);
         let output = Shell::git()
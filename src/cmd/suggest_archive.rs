@@ -0,0 +1,126 @@
+use std::cmp::Reverse;
+use std::io;
+use std::io::Write;
+
+use anyhow::Result;
+use console::{style, Term};
+
+use crate::cmd::archive;
+use crate::cmd::remove;
+use crate::cmd::Run;
+use crate::cmd::SuggestArchive;
+use crate::config::Config;
+use crate::db::{Database, Epoch, Repo};
+use crate::util;
+
+impl Run for SuggestArchive {
+    fn run(&self) -> Result<()> {
+        let mut db = Database::open()?;
+        let cfg = Config::parse()?;
+        let now = util::current_time()?;
+
+        let remote = self.args.first().map(String::as_str).unwrap_or_default();
+        let group = self.args.get(1).map(String::as_str).unwrap_or_default();
+        if !remote.is_empty() {
+            cfg.must_get_remote(remote)?;
+        }
+        let days = self.days.unwrap_or(cfg.archive_after);
+        let before = now.saturating_sub(days * util::DAY);
+
+        // The attached repos are not in workspace, leave them alone.
+        let repos: Vec<&Repo> = db
+            .filter(remote, group)
+            .into_iter()
+            .filter(|repo| repo.path.is_empty() && repo.last_accessed < before)
+            .collect();
+        let sizes = util::parallel(&repos, |repo| {
+            let path = repo.path(&cfg.workspace)?;
+            if !path.exists() {
+                return Ok(None);
+            }
+            util::dir_size(&path).map(Some)
+        });
+        let mut stale: Vec<(String, String, Epoch, u64)> = vec![];
+        for (repo, size) in repos.iter().zip(sizes) {
+            if let Some(size) = size? {
+                stale.push((
+                    repo.remote.clone(),
+                    repo.name.clone(),
+                    repo.last_accessed,
+                    size,
+                ));
+            }
+        }
+        if stale.is_empty() {
+            _ = writeln!(io::stderr(), "no repo is idle for more than {} days", days);
+            return Ok(());
+        }
+        // The largest first, they are most worth cleaning.
+        stale.sort_by_key(|(_, _, _, size)| Reverse(*size));
+
+        if !self.interactive {
+            for (remote, name, last_accessed, size) in stale.iter() {
+                println!(
+                    "{}:{} {} {}",
+                    style(remote).yellow(),
+                    style(name).yellow(),
+                    style(util::human_bytes(*size)).cyan(),
+                    style(util::format_since(now, *last_accessed)).dim()
+                );
+            }
+            let total: u64 = stale.iter().map(|(_, _, _, size)| size).sum();
+            _ = writeln!(
+                io::stderr(),
+                "{} repos idle for more than {} days, {} in total",
+                style(stale.len()).yellow(),
+                days,
+                style(util::human_bytes(total)).yellow()
+            );
+            return Ok(());
+        }
+
+        let term = Term::stderr();
+        let actions = match cfg.cold_storage.is_empty() {
+            true => "[r]emove [s]kip [q]uit",
+            false => "[a]rchive [r]emove [s]kip [q]uit",
+        };
+        for (remote, name, last_accessed, size) in stale.iter() {
+            _ = write!(
+                io::stderr(),
+                "{}:{} ({}, {}) {} ",
+                style(remote).yellow(),
+                style(name).yellow(),
+                util::human_bytes(*size),
+                util::format_since(now, *last_accessed),
+                style(actions).dim()
+            );
+            let key = term.read_char()?;
+            _ = writeln!(io::stderr(), "{}", key);
+            let idx = match db.get(remote, name) {
+                Some(idx) => idx,
+                None => continue,
+            };
+            let result = match key {
+                'a' if !cfg.cold_storage.is_empty() => {
+                    archive::archive_repo(&cfg, &db, &db.repos[idx], true)
+                }
+                'r' => remove::remove_path(&db, &cfg, &db.repos[idx], true).map(|_| {
+                    db.repos.remove(idx);
+                }),
+                'q' => break,
+                _ => continue,
+            };
+            if let Err(err) = result {
+                _ = writeln!(
+                    io::stderr(),
+                    "{} {:#}",
+                    util::Palette::get().error.apply_to("error"),
+                    err
+                );
+            }
+        }
+        db.save()?;
+
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
@@ -5,7 +7,8 @@ use console::style;
 
 use crate::cmd::Run;
 use crate::cmd::Tag;
-use crate::config::Config;
+use crate::config::{Config, RepoConfig};
+use crate::db::HistoryEntry;
 use crate::util;
 use crate::util::GitTag;
 use crate::util::Shell;
@@ -13,15 +16,23 @@ use crate::util::Shell;
 impl Run for Tag {
     fn run(&self) -> Result<()> {
         if self.show_rules {
-            let cfg = Config::parse()?;
-            let mut rules: Vec<_> = cfg.tag_rule.iter().map(|(key, _)| key).collect();
+            let rules = Self::rules()?;
+            let mut rules: Vec<_> = rules.iter().collect();
             rules.sort();
-            for rule in rules {
+            if self.explain {
+                return self.explain(rules);
+            }
+            for (rule, _) in rules {
                 println!("{}", rule);
             }
             return Ok(());
         }
         let tags = GitTag::list().context("unable to list tag")?;
+        if self.dry_run {
+            let (_, _, new_tag) = self.apply_rule(tags)?;
+            println!("{}", new_tag);
+            return Ok(());
+        }
         if self.delete {
             return self.delete(tags);
         }
@@ -48,6 +59,10 @@ impl Tag {
         let tag = self.get_tag_or_latest(tags)?;
 
         Shell::git().args(["tag", "-d", tag.as_str()]).exec()?;
+        HistoryEntry::new("delete-tag")
+            .current_path()
+            .detail(tag.as_str())
+            .record();
         if self.push {
             Shell::git()
                 .args(["push", "--delete", "origin", tag.as_str()])
@@ -58,16 +73,8 @@ impl Tag {
     }
 
     fn create(&self, tags: Vec<GitTag>) -> Result<()> {
-        let tag = if let Some(rule_key) = self.rule.as_ref() {
-            let cfg = Config::parse()?;
-            let rule_value = cfg.tag_rule.get(rule_key);
-            if let None = rule_value {
-                bail!("could not find rule {}", rule_key)
-            }
-            let rule = rule_value.unwrap();
-
-            let tag = self.get_tag_or_latest(tags)?;
-            let new_tag = tag.apply_rule(rule)?;
+        let tag = if self.rule.is_some() {
+            let (rule_key, tag, new_tag) = self.apply_rule(tags)?;
 
             println!();
             println!(
@@ -116,6 +123,53 @@ impl Tag {
         Ok(())
     }
 
+    /// Apply the rule to the given or latest tag, return the rule name, the
+    /// tag and the new tag.
+    fn apply_rule(&self, tags: Vec<GitTag>) -> Result<(&String, GitTag, GitTag)> {
+        let rule_key = match self.rule.as_ref() {
+            Some(rule_key) => rule_key,
+            None => bail!("require rule to apply"),
+        };
+        let rules = Self::rules()?;
+        let rule = match rules.get(rule_key) {
+            Some(rule) => rule,
+            None => bail!("could not find rule {}", rule_key),
+        };
+
+        let tag = self.get_tag_or_latest(tags)?;
+        let new_tag = tag.apply_rule(rule)?;
+        Ok((rule_key, tag, new_tag))
+    }
+
+    fn explain(&self, rules: Vec<(&String, &String)>) -> Result<()> {
+        let tags = GitTag::list().context("unable to list tag")?;
+        let tag = self.get_tag_or_latest(tags)?;
+        println!("Apply rules to {}:", style(tag.as_str()).yellow());
+        let width = rules.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+        for (key, rule) in rules {
+            let result = match tag.apply_rule(rule) {
+                Ok(new_tag) => style(new_tag.as_str()).yellow().to_string(),
+                Err(err) => style(format!("{:#}", err)).red().to_string(),
+            };
+            println!(
+                "  {} {} -> {}",
+                style(format!("{:width$}", key, width = width)).magenta(),
+                style(rule).dim(),
+                result
+            );
+        }
+        Ok(())
+    }
+
+    /// The tag rules in config, overridden by the repo config.
+    fn rules() -> Result<HashMap<String, String>> {
+        let mut rules = Config::parse()?.tag_rule;
+        if let Some(repo_cfg) = RepoConfig::current()? {
+            rules.extend(repo_cfg.tag_rule);
+        }
+        Ok(rules)
+    }
+
     fn get_tag_or_latest(&self, tags: Vec<GitTag>) -> Result<GitTag> {
         if self.args.is_empty() {
             GitTag::latest()
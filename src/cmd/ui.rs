@@ -0,0 +1,360 @@
+use std::io::{self, Stderr};
+
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{Frame, Terminal};
+
+use crate::api;
+use crate::cmd::archive;
+use crate::cmd::jump;
+use crate::cmd::remove;
+use crate::cmd::Run;
+use crate::cmd::Ui;
+use crate::config::Config;
+use crate::db::{Database, Epoch, Repo};
+use crate::git;
+use crate::util;
+use crate::util::Shell;
+
+/// The status of a repo shown in the dashboard.
+struct Item {
+    name: String,
+    score: f64,
+    branch: String,
+    dirty: bool,
+    cloned: bool,
+    meta: String,
+}
+
+struct App<'a> {
+    db: &'a mut Database,
+    cfg: &'a Config,
+
+    items: Vec<Item>,
+    shown: Vec<usize>,
+    state: TableState,
+
+    filter: String,
+    message: String,
+    confirm_remove: bool,
+}
+
+impl Run for Ui {
+    fn run(&self) -> Result<()> {
+        let mut db = Database::open()?;
+        let cfg = Config::parse()?;
+        let now = util::current_time()?;
+
+        let results = util::parallel(&db.repos, |repo| Item::build(repo, &cfg.workspace, now));
+        let mut items = Vec::with_capacity(results.len());
+        for item in results {
+            items.push(item?);
+        }
+
+        let mut app = App {
+            db: &mut db,
+            cfg: &cfg,
+            items,
+            shown: vec![],
+            state: TableState::default(),
+            filter: String::new(),
+            message: String::new(),
+            confirm_remove: false,
+        };
+        app.refresh();
+
+        // The stdout is used to output the path to jump, so draw the
+        // dashboard on stderr.
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stderr()))
+            .context("unable to init terminal")?;
+        enter_screen(&mut terminal)?;
+
+        let result = app.run(&mut terminal);
+
+        leave_screen(&mut terminal)?;
+
+        let idx = match result? {
+            Some(idx) => idx,
+            None => return Ok(()),
+        };
+        jump::enter(&mut db, &cfg, idx, now, |_, _| Ok(()))
+    }
+}
+
+impl Item {
+    fn build(repo: &Repo, workspace: &str, now: Epoch) -> Result<Item> {
+        let name = format!("{}:{}", repo.remote, repo.name);
+        let score = repo.score(now);
+        let meta = match &repo.meta {
+            Some(meta) => meta.summary(),
+            None => String::new(),
+        };
+        let path = repo.path(workspace)?;
+        if !path.exists() {
+            return Ok(Item {
+                name,
+                score,
+                branch: String::new(),
+                dirty: false,
+                cloned: false,
+                meta,
+            });
+        }
+        let reader = git::reader(Some(util::path_to_str(&path)?), true);
+        Ok(Item {
+            name,
+            score,
+            branch: reader.current_branch().unwrap_or_default(),
+            dirty: reader.count_changes().unwrap_or_default() > 0,
+            cloned: true,
+            meta,
+        })
+    }
+}
+
+impl App<'_> {
+    const HELP: &'static str =
+        "enter: jump  ctrl-o: open  ctrl-f: fetch  ctrl-d: remove  up/down: move  esc: quit";
+
+    fn run(&mut self, terminal: &mut Terminal<CrosstermBackend<Stderr>>) -> Result<Option<usize>> {
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .context("unable to draw dashboard")?;
+            let key = match event::read().context("unable to read terminal event")? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+            if self.confirm_remove {
+                self.confirm_remove = false;
+                self.message.clear();
+                if let KeyCode::Char('y') = key.code {
+                    self.remove(terminal)?;
+                }
+                continue;
+            }
+            match self.handle(key) {
+                Some(action) => return Ok(action),
+                None => continue,
+            }
+        }
+    }
+
+    /// Handle the key event, returns `Some` to exit the dashboard.
+    fn handle(&mut self, key: KeyEvent) -> Option<Option<usize>> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Some(None),
+            KeyCode::Char('c') if ctrl => return Some(None),
+            KeyCode::Enter => return Some(self.selected()),
+            KeyCode::Up => self.move_by(-1),
+            KeyCode::Char('p') if ctrl => self.move_by(-1),
+            KeyCode::Down => self.move_by(1),
+            KeyCode::Char('n') if ctrl => self.move_by(1),
+            KeyCode::Char('o') if ctrl => self.open(),
+            KeyCode::Char('f') if ctrl => self.fetch(),
+            KeyCode::Char('d') if ctrl => {
+                if let Some(idx) = self.selected() {
+                    self.message = format!("remove {}? (y/n)", self.items[idx].name);
+                    self.confirm_remove = true;
+                }
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.refresh();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.filter.push(c);
+                self.refresh();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn draw(&mut self, frame: &mut Frame<CrosstermBackend<Stderr>>) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .split(frame.size());
+
+        let filter = Paragraph::new(self.filter.as_str())
+            .block(Block::default().borders(Borders::ALL).title("Filter"));
+        frame.render_widget(filter, chunks[0]);
+
+        let rows: Vec<Row> = self
+            .shown
+            .iter()
+            .map(|idx| {
+                let item = &self.items[*idx];
+                let status = match (item.cloned, item.dirty) {
+                    (false, _) => Cell::from("not cloned").style(Style::default().fg(Color::Red)),
+                    (true, true) => Cell::from("dirty").style(Style::default().fg(Color::Yellow)),
+                    (true, false) => Cell::from("clean").style(Style::default().fg(Color::Green)),
+                };
+                Row::new(vec![
+                    Cell::from(item.name.as_str()),
+                    Cell::from(format!("{:.2}", item.score)),
+                    Cell::from(item.branch.as_str()),
+                    status,
+                    Cell::from(item.meta.as_str()).style(Style::default().fg(Color::Cyan)),
+                ])
+            })
+            .collect();
+        let header = Row::new(vec!["Repo", "Score", "Branch", "Status", "About"])
+            .style(Style::default().add_modifier(Modifier::BOLD));
+        let widths = [
+            Constraint::Percentage(30),
+            Constraint::Percentage(8),
+            Constraint::Percentage(17),
+            Constraint::Percentage(10),
+            Constraint::Percentage(35),
+        ];
+        let table = Table::new(rows)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title("Repos"))
+            .widths(&widths)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, chunks[1], &mut self.state);
+
+        let status = match self.message.is_empty() {
+            true => Span::styled(Self::HELP, Style::default().fg(Color::DarkGray)),
+            false => Span::styled(self.message.as_str(), Style::default().fg(Color::Yellow)),
+        };
+        frame.render_widget(Paragraph::new(Spans::from(status)), chunks[2]);
+    }
+
+    /// Apply the filter, the item matches if all chars of the filter appear
+    /// in its name in order.
+    fn refresh(&mut self) {
+        let filter = self.filter.to_lowercase();
+        self.shown = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                let mut chars = item.name.chars().flat_map(char::to_lowercase);
+                filter
+                    .chars()
+                    .all(|c| chars.any(|name_char| name_char == c))
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        match self.shown.is_empty() {
+            true => self.state.select(None),
+            false => self.state.select(Some(0)),
+        }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.state.selected().map(|idx| self.shown[idx])
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.shown.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or_default() as isize;
+        let max = self.shown.len() as isize - 1;
+        let next = (current + delta).clamp(0, max);
+        self.state.select(Some(next as usize));
+    }
+
+    fn open(&mut self) {
+        let idx = match self.selected() {
+            Some(idx) => idx,
+            None => return,
+        };
+        let repo = &self.db.repos[idx];
+        let result = (|| -> Result<()> {
+            let remote = self.cfg.must_get_remote(&repo.remote)?;
+            let provider = api::create_provider(remote)?;
+            let url = provider.get_repo_url(&repo.name, None, remote)?;
+            self.cfg.open_url(url)
+        })();
+        self.message = match result {
+            Ok(_) => format!("opened {}", repo.name),
+            Err(err) => format!("open failed: {}", err),
+        };
+    }
+
+    fn fetch(&mut self) {
+        let idx = match self.selected() {
+            Some(idx) => idx,
+            None => return,
+        };
+        let repo = &self.db.repos[idx];
+        if !self.items[idx].cloned {
+            self.message = format!("{} is not cloned", repo.name);
+            return;
+        }
+        let result = (|| -> Result<String> {
+            let path = repo.path(&self.cfg.workspace)?;
+            Shell::git()
+                .mute()
+                .with_git_path(util::path_to_str(&path)?)
+                .args(["fetch", "--all", "--prune", "--quiet"])
+                .exec()
+        })();
+        self.message = match result {
+            Ok(_) => format!("fetched {}", repo.name),
+            Err(_) => format!("fetch {} failed", repo.name),
+        };
+    }
+
+    /// Remove the repo like the `remove` command, the dashboard is suspended
+    /// since the removal might prompt.
+    fn remove(&mut self, terminal: &mut Terminal<CrosstermBackend<Stderr>>) -> Result<()> {
+        let idx = match self.selected() {
+            Some(idx) => idx,
+            None => return Ok(()),
+        };
+        leave_screen(terminal)?;
+        let result = (|| -> Result<bool> {
+            let repo = &self.db.repos[idx];
+            if !remove::remove_path(self.db, self.cfg, repo, false)? {
+                return Ok(false);
+            }
+            archive::remove_archived(self.cfg, repo, false)?;
+            Ok(true)
+        })();
+        enter_screen(terminal)?;
+        if !result? {
+            return Ok(());
+        }
+        self.db.repos.remove(idx);
+        let item = self.items.remove(idx);
+        self.db.save()?;
+
+        self.message = format!("removed {}", item.name);
+        self.refresh();
+        Ok(())
+    }
+}
+
+fn enter_screen(terminal: &mut Terminal<CrosstermBackend<Stderr>>) -> Result<()> {
+    enable_raw_mode().context("unable to enable terminal raw mode")?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)
+        .context("unable to enter alternate screen")?;
+    terminal.clear().context("unable to clear terminal")
+}
+
+fn leave_screen(terminal: &mut Terminal<CrosstermBackend<Stderr>>) -> Result<()> {
+    disable_raw_mode().context("unable to disable terminal raw mode")?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)
+        .context("unable to leave alternate screen")?;
+    terminal.show_cursor().context("unable to show cursor")
+}
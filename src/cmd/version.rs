@@ -0,0 +1,219 @@
+use std::env;
+use std::env::consts;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use serde::Deserialize;
+
+use crate::api::rest::{Auth, Rest};
+use crate::cmd::Run;
+use crate::cmd::Version;
+use crate::config::Config;
+use crate::util;
+use crate::util::Shell;
+
+#[derive(Deserialize, Debug)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Run for Version {
+    fn run(&self) -> Result<()> {
+        let current = env!("CARGO_PKG_VERSION");
+        if !self.check_update {
+            println!("git-zoxide {}", current);
+            return Ok(());
+        }
+
+        let rest = Rest::new("github", Self::api_url(), Auth::None)?;
+        let current_version = parse_version(current);
+        let mut releases: Vec<(Vec<u64>, Release)> = rest
+            .get::<Vec<Release>>(Self::RELEASES_PATH, &[("per_page", "30")])?
+            .into_iter()
+            .filter(|release| !release.draft && !release.prerelease)
+            .map(|release| (parse_version(&release.tag_name), release))
+            .filter(|(version, _)| *version > current_version)
+            .collect();
+        if releases.is_empty() {
+            _ = writeln!(io::stderr(), "git-zoxide {} is up to date", current);
+            return Ok(());
+        }
+        releases.sort_by(|(version1, _), (version2, _)| version2.cmp(version1));
+
+        for (_, release) in &releases {
+            println!("{}", style(&release.tag_name).green().bold());
+            let body = release.body.as_deref().unwrap_or_default().trim();
+            match body.is_empty() {
+                true => println!("{}", style("<no changelog>").dim()),
+                false => println!("{}", body.replace("\r\n", "\n")),
+            }
+            println!();
+        }
+        let (_, latest) = &releases[0];
+        _ = writeln!(
+            io::stderr(),
+            "{} newer releases, the latest is {}, current is {}",
+            style(releases.len()).yellow(),
+            style(&latest.tag_name).green(),
+            style(current).yellow()
+        );
+        if !self.install {
+            return Ok(());
+        }
+        Self::install(&rest, latest)
+    }
+}
+
+impl Version {
+    const API_URL: &str = "https://api.github.com";
+    const RELEASES_PATH: &str = "repos/fioncat/git-zoxide/releases";
+
+    /// The api url can be overridden by `_GZ_RELEASE_API`, for GitHub
+    /// mirrors.
+    fn api_url() -> String {
+        match env::var("_GZ_RELEASE_API") {
+            Ok(url) if !url.is_empty() => url,
+            _ => String::from(Self::API_URL),
+        }
+    }
+
+    /// The target in release assets, same as `install.sh`.
+    fn target() -> Result<String> {
+        let os = match consts::OS {
+            "linux" => "unknown-linux-gnu",
+            "macos" => "apple-darwin",
+            os => bail!("sorry, there is no release for {}", style(os).yellow()),
+        };
+        Ok(format!("{}-{}", consts::ARCH, os))
+    }
+
+    fn install(rest: &Rest, release: &Release) -> Result<()> {
+        let name = format!("git-zoxide_{}.tar.gz", Self::target()?);
+        let checksum_name = format!("{}.sha256", name);
+        let find_asset = |name: &str| match release.assets.iter().find(|a| a.name == name) {
+            Some(asset) => Ok(asset.browser_download_url.as_str()),
+            None => bail!(
+                "could not find {} in release {}",
+                style(name).yellow(),
+                style(&release.tag_name).yellow()
+            ),
+        };
+        let url = find_asset(&name)?;
+        // Refuse to install without checksum, the binary would replace the
+        // one in use.
+        let checksum_url = find_asset(&checksum_name)?;
+
+        let exe = env::current_exe().context("unable to get current binary path")?;
+        let exe = fs::canonicalize(&exe).unwrap_or(exe);
+        util::confirm(format!(
+            "do you want to replace {} with {}",
+            style(exe.display()).yellow(),
+            style(&release.tag_name).green()
+        ))?;
+
+        util::print_operation(format!("download {}", style(&name).yellow()));
+        let data = rest.download(url)?;
+        let checksum = String::from_utf8(rest.download(checksum_url)?)
+            .context("the checksum file is not valid utf-8")?;
+        // The checksum file is the output of `shasum -a 256`, in format
+        // "<hex>  <name>".
+        let expect = checksum.split_whitespace().next().unwrap_or_default();
+        let actual: String = openssl::sha::sha256(&data)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        if !expect.eq_ignore_ascii_case(&actual) {
+            bail!(
+                "checksum mismatch for {}, expect {}, got {}",
+                style(&name).yellow(),
+                expect,
+                actual
+            )
+        }
+
+        let tmp = Config::get_data_dir()?.join("update");
+        if tmp.exists() {
+            fs::remove_dir_all(&tmp).context("unable to clean update directory")?;
+        }
+        fs::create_dir_all(&tmp).context("unable to create update directory")?;
+        let archive = tmp.join(&name);
+        fs::write(&archive, data).context("unable to write release archive")?;
+        Shell::new("tar")
+            .args(["-xzf", util::path_to_str(&archive)?])
+            .args(["-C", util::path_to_str(&tmp)?])
+            .exec()?;
+
+        // Copy the binary next to current one and rename, so that the
+        // replacement is atomic.
+        let dir = exe.parent().context("invalid current binary path")?;
+        let staged = dir.join(".git-zoxide.update");
+        fs::copy(tmp.join("git-zoxide"), &staged).with_context(|| {
+            format!(
+                "unable to write {}, please check permission",
+                style(staged.display()).yellow()
+            )
+        })?;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))
+            .context("unable to set permission of new binary")?;
+        fs::rename(&staged, &exe).context("unable to replace current binary")?;
+        _ = fs::remove_dir_all(&tmp);
+
+        _ = writeln!(
+            io::stderr(),
+            "git-zoxide is updated to {}",
+            style(&release.tag_name).green()
+        );
+        Ok(())
+    }
+}
+
+/// Parse version like "v0.3.1" into numbers for comparing, the non-numeric
+/// parts are ignored.
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_numbers() {
+        for (version, expect) in [
+            ("v0.3.1", vec![0, 3, 1]),
+            ("0.3.1", vec![0, 3, 1]),
+            ("v1.10", vec![1, 10]),
+            ("v0.4.0-rc1", vec![0, 4, 0]),
+            ("nightly", vec![0]),
+        ] {
+            assert_eq!(parse_version(version), expect, "{}", version);
+        }
+        assert!(parse_version("v0.10.0") > parse_version("v0.9.9"));
+        assert!(parse_version("v0.3.1") > parse_version("v0.3"));
+    }
+}
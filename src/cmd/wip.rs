@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+
+use crate::cmd::Run;
+use crate::cmd::Wip;
+use crate::config::Config;
+use crate::db::Database;
+use crate::util;
+use crate::util::GitWip;
+use crate::util::Shell;
+
+impl Run for Wip {
+    fn run(&self) -> Result<()> {
+        if self.restore {
+            if !GitWip::restore(None)? {
+                bail!("no work in progress saved for current branch")
+            }
+            return Ok(());
+        }
+        if !GitWip::save(None)? {
+            bail!("no change to save")
+        }
+        Ok(())
+    }
+}
+
+/// Save changes of current repository before jumping to the target, only
+/// works when `wip_on_jump` is enabled.
+pub fn save_on_jump(db: &Database, cfg: &Config, target: usize) -> Result<()> {
+    if !cfg.wip_on_jump {
+        return Ok(());
+    }
+    match db.current_index(cfg) {
+        Ok(idx) if idx != target => {
+            if GitWip::save(None)? {
+                util::print_operation("saved work in progress changes");
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Restore changes saved for the target repository after jumping, only
+/// works when `wip_on_jump` is enabled.
+pub fn restore_on_jump(cfg: &Config, path: &PathBuf) -> Result<()> {
+    if !cfg.wip_on_jump {
+        return Ok(());
+    }
+    let path = util::path_to_str(path)?;
+    // Do not mess up the changes already in target repository.
+    let status = Shell::git()
+        .with_git_path(path)
+        .args(["status", "-s"])
+        .exec()?;
+    if !status.is_empty() {
+        return Ok(());
+    }
+    if GitWip::restore(Some(path))? {
+        util::print_operation("restored work in progress changes");
+    }
+    Ok(())
+}
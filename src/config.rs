@@ -1,43 +1,263 @@
 use anyhow::bail;
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use console::style;
+use regex::Regex;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::fs;
-use std::io;
-use std::path::PathBuf;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Instant;
 
-use crate::util::{self, Shell};
+use crate::db::TrustStore;
+use crate::util::{self, Keyring, Shell};
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pub workspace: String,
 
-    #[serde(default = "empty_vec")]
+    #[serde(default)]
     pub remotes: Vec<Remote>,
 
-    #[serde(default = "empty_map")]
+    #[serde(default)]
     pub keyword_map: HashMap<String, String>,
 
-    #[serde(default = "empty_map")]
+    /// The aliases of groups, such as `k8s: kubernetes`. The alias followed
+    /// by "/" can be used as the leading group of repo name, such as
+    /// "k8s/kubectl", so that the deep subgroups do not need to be typed.
+    #[serde(default)]
+    pub group_map: HashMap<String, String>,
+
+    #[serde(default)]
     pub tag_rule: HashMap<String, String>,
+
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+
+    #[serde(default)]
+    pub hooks: HashMap<String, String>,
+
+    #[serde(default = "default_bool")]
+    pub notify: bool,
+
+    #[serde(default = "default_notify_after")]
+    pub notify_after: u64,
+
+    #[serde(default = "default_bool")]
+    pub wip_on_jump: bool,
+
+    #[serde(default = "default_rank")]
+    pub rank: RankMode,
+
+    #[serde(default = "default_bool")]
+    pub session_boost: bool,
+
+    pub templates: Option<Templates>,
+
+    #[serde(default)]
+    pub repos: Vec<DeclaredRepo>,
+
+    /// Resolve symlinks when comparing paths, so that the repos can be
+    /// found when the workspace is a symlink.
+    #[serde(default)]
+    pub resolve_symlinks: bool,
+
+    /// Cache the branch and dirty state of repos when jumping, the cache is
+    /// shown in `list --long` and the fzf preview.
+    #[serde(default = "default_bool")]
+    pub status_cache: bool,
+
+    /// Fetch the metadata (description, stars, etc.) of repos from provider
+    /// in background when jumping, the metadata is refreshed weekly and shown
+    /// in `list --long`, the fzf preview and the dashboard.
+    #[serde(default = "default_bool")]
+    pub repo_meta: bool,
+
+    /// The directory to keep the archived repos, such as a slow or encrypted
+    /// mount. The archived repos are still jumpable, and will be restored to
+    /// workspace when jumping. Empty means archiving is disabled.
+    #[serde(default = "empty_string")]
+    pub cold_storage: String,
+
+    /// The repos not accessed in this many days are suggested to archive or
+    /// remove by `suggest-archive`.
+    #[serde(default = "default_archive_after")]
+    pub archive_after: u64,
+
+    /// Check the processes (such as a dev server) running under the repo
+    /// before removing or archiving it.
+    #[serde(default = "default_bool")]
+    pub process_check: bool,
+
+    /// The colors used in output.
+    #[serde(default)]
+    pub theme: Theme,
+
+    /// The command to open urls, the placeholder `{url}` is replaced with
+    /// the quoted url, or the url is appended if there is no placeholder.
+    /// Empty means the default browser of system.
+    #[serde(default = "empty_string")]
+    pub browser: String,
+
+    /// The glob patterns of directories ignored by `scan`, relative to the
+    /// workspace, such as `**/node_modules` or `local/tmp/**`.
+    #[serde(default)]
+    pub scan_ignore: Vec<String>,
+
+    /// Add the path of repo to zoxide after jumping, and remove it after
+    /// removing the repo, so that `z` works for the repos too. Nothing is
+    /// done if zoxide is not installed.
+    #[serde(default = "default_bool")]
+    pub zoxide: bool,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct Remote {
     pub name: String,
     pub user: Option<User>,
+
+    /// Commit signing applied to the repos in this remote.
+    pub signing: Option<Signing>,
     pub clone: Option<Clone>,
     pub api: Option<API>,
 
-    #[serde(default = "empty_vec")]
+    #[serde(default)]
     pub on_create: Vec<Step>,
+
+    /// The initial branch of the repos created (not cloned) in this remote,
+    /// empty means to use the default of git.
+    #[serde(default = "empty_string")]
+    pub default_branch: String,
+
+    #[serde(default)]
+    pub merge_target: HashMap<String, String>,
+
+    pub trailers: Option<Trailers>,
+
+    pub ticket: Option<Ticket>,
+
+    #[serde(default)]
+    pub protected_branches: Vec<String>,
+
+    /// The file installed as `commit.template` for the repos in this remote,
+    /// empty means not to install.
+    #[serde(default = "empty_string")]
+    pub commit_template: String,
+
+    /// Override `commit_template` for repos or groups, the key can be a
+    /// full repo name or a group.
+    #[serde(default)]
+    pub group_commit_template: HashMap<String, String>,
+
+    /// The environment variables injected to the commands running for the
+    /// repos in this remote, such as `on_create` steps and hooks.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+
+    /// Extend or override `env` for repos or groups, the key can be a full
+    /// repo name or a group.
+    #[serde(default)]
+    pub group_env: HashMap<String, HashMap<String, String>>,
+
+    /// The template of `.envrc` written to the repos cloned or created in
+    /// this remote, `direnv allow` is run after writing if direnv is
+    /// installed. The placeholders `{remote}`, `{name}`, `{group}`, `{base}`
+    /// and `{path}` are replaced. Empty means not to write.
+    #[serde(default = "empty_string")]
+    pub envrc: String,
+
+    /// Override `envrc` for repos or groups, the key can be a full repo name
+    /// or a group.
+    #[serde(default)]
+    pub group_envrc: HashMap<String, String>,
+}
+
+/// The templates used to generate the files of a newly created repo.
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Templates {
+    #[serde(default)]
+    pub licenses: HashMap<String, String>,
+
+    #[serde(default = "empty_string")]
+    pub readme: String,
+}
+
+/// The repo declared in config, `gz bootstrap` ensures it exists locally.
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredRepo {
+    pub remote: String,
+    pub name: String,
+
+    /// Attach the repo to this path rather than workspace.
+    #[serde(default = "empty_string")]
+    pub path: String,
+
+    /// The branch or tag to check out after cloning.
+    #[serde(default = "empty_string")]
+    pub pin: String,
+
+    /// Labels to select a subset of repos to bootstrap.
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+/// The config in `.gz.yaml` at the root of a repo, which overrides the
+/// config for that repo only. The file is versioned with the code, so it is
+/// used only after the user trusts it.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RepoConfig {
+    /// The tag rules, merged into `tag_rule` of config.
+    #[serde(default)]
+    pub tag_rule: HashMap<String, String>,
+
+    /// The target repo of `gz merge`, overrides `merge_target` of remote.
+    #[serde(default = "empty_string")]
+    pub merge_target: String,
+
+    /// The target branch of `gz merge`, default is the HEAD branch.
+    #[serde(default = "empty_string")]
+    pub merge_branch: String,
+
+    /// The bash script run in the repo after jumping to it, with the `env`
+    /// of remote. Its output is printed to stderr.
+    #[serde(default = "empty_string")]
+    pub on_enter: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Ticket {
+    pub regex: String,
+
+    #[serde(default = "empty_string")]
+    pub url_template: String,
+}
+
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Trailers {
+    #[serde(default = "default_bool")]
+    pub sign_off: bool,
+
+    #[serde(default = "default_bool")]
+    pub change_id: bool,
+
+    #[serde(default)]
+    pub custom: HashMap<String, String>,
+}
+
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct Step {
     pub name: String,
     pub run: Option<String>,
@@ -45,13 +265,90 @@ pub struct Step {
     pub copy: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct User {
     pub name: String,
     pub email: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Signing {
+    #[serde(default = "default_signing_format")]
+    pub format: SigningFormat,
+
+    /// The signing key, for ssh format, this can be the path to the public
+    /// key.
+    pub key: String,
+}
+
+/// The colors used in output. The colors are in dotted format, such as
+/// `yellow.bold` or `black.on_white`, empty means to use the color of the
+/// base theme.
+#[derive(Deserialize, JsonSchema, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+    #[serde(default = "default_theme_base")]
+    pub base: ThemeBase,
+
+    /// The banner of operations.
+    #[serde(default = "empty_string")]
+    pub operation: String,
+
+    /// The banner of git commands executed.
+    #[serde(default = "empty_string")]
+    pub command: String,
+
+    #[serde(default = "empty_string")]
+    pub error: String,
+
+    #[serde(default = "empty_string")]
+    pub warning: String,
+
+    /// The branch statuses shown in `branch --all`.
+    #[serde(default = "empty_string")]
+    pub branch_sync: String,
+    #[serde(default = "empty_string")]
+    pub branch_gone: String,
+    #[serde(default = "empty_string")]
+    pub branch_ahead: String,
+    #[serde(default = "empty_string")]
+    pub branch_behind: String,
+    #[serde(default = "empty_string")]
+    pub branch_conflict: String,
+    #[serde(default = "empty_string")]
+    pub branch_detached: String,
+}
+
+/// The built-in themes.
+#[derive(Deserialize, JsonSchema, Default, Clone, Copy, PartialEq, Debug)]
+pub enum ThemeBase {
+    /// For dark terminals
+    #[default]
+    #[serde(rename = "dark")]
+    Dark,
+    /// For light terminals, no yellow is used
+    #[serde(rename = "light")]
+    Light,
+    /// No color at all
+    #[serde(rename = "plain")]
+    Plain,
+}
+
+/// The format of commit signing, see `gpg.format` in git.
+#[derive(Deserialize, JsonSchema, Clone, Copy, PartialEq, Debug)]
+pub enum SigningFormat {
+    #[serde(rename = "openpgp")]
+    Openpgp,
+    #[serde(rename = "ssh")]
+    Ssh,
+    #[serde(rename = "x509")]
+    X509,
+}
+
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct Clone {
     pub domain: String,
 
@@ -60,49 +357,200 @@ pub struct Clone {
 
     #[serde(default = "empty_string")]
     pub ssh_groups: String,
+
+    #[serde(default = "empty_string")]
+    pub url_template: String,
+
+    #[serde(default = "default_bool")]
+    pub mirror: bool,
+
+    #[serde(default)]
+    pub mirror_repos: Vec<String>,
+
+    #[serde(default = "empty_string")]
+    pub reference_cache: String,
+
+    #[serde(default = "default_bool")]
+    pub maintenance: bool,
+
+    /// The ssh private key used by the repos in this remote, it is set to
+    /// `core.sshCommand` of the repos when cloning or attaching. Empty means
+    /// to use the default ssh config.
+    #[serde(default = "empty_string")]
+    pub ssh_key: String,
+
+    /// The free space required on the workspace filesystem before cloning,
+    /// such as "5GiB", the size of repo reported by provider is added if
+    /// available. Empty means not to check.
+    #[serde(default = "empty_string")]
+    pub min_free_space: String,
+
+    /// Abort cloning when the free space is not enough, otherwise only warn.
+    #[serde(default = "default_bool")]
+    pub abort_on_low_space: bool,
+
+    /// The times to retry a failed clone, with backoff between retries.
+    #[serde(default)]
+    pub retry: u32,
+
+    /// Check out the default branch of remote after cloning, even if the
+    /// HEAD of remote points to another branch. The default branch is got
+    /// from provider, or `git remote show` if api is not enabled.
+    #[serde(default = "default_bool")]
+    pub checkout_default: bool,
+
+    /// The working branch created after cloning, such as "{user}/main", the
+    /// placeholder `{user}` is replaced with the login name of current user.
+    /// Empty means not to create.
+    #[serde(default = "empty_string")]
+    pub on_clone_branch: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct API {
     pub provider: Provider,
 
     #[serde(default = "empty_string")]
     pub token: String,
 
+    /// Where to get the token, the token in keyring is stored by `gz auth
+    /// set`, which is safer than the plaintext config.
+    #[serde(default = "default_token_from")]
+    pub token_from: TokenSource,
+
+    /// The client id of the OAuth app used by `gz auth login`, the device
+    /// flow must be enabled for the app. Only for Github, empty uses the app
+    /// shipped with gz, which is only available for github.com.
+    #[serde(default = "empty_string")]
+    pub client_id: String,
+
     #[serde(default = "empty_string")]
     pub url: String,
+
+    #[serde(default = "empty_string")]
+    pub web_url_template: String,
+
+    #[serde(default = "empty_string")]
+    pub branch_url_template: String,
+
+    #[serde(default = "empty_string")]
+    pub merge_url_template: String,
+
+    #[serde(default = "empty_string")]
+    pub compare_url_template: String,
+
+    /// The times to retry the request that is rate limited or failed by
+    /// server, with backoff between retries.
+    #[serde(default = "default_api_retry")]
+    pub retry: u32,
+}
+
+/// Where to get the api token.
+#[derive(Deserialize, JsonSchema, Clone, Copy, PartialEq, Debug)]
+pub enum TokenSource {
+    /// The `token` in config
+    #[serde(rename = "config")]
+    Config,
+    /// The system keyring
+    #[serde(rename = "keyring")]
+    Keyring,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, JsonSchema, Debug)]
 pub enum Provider {
     #[serde(rename = "github")]
     Github,
     #[serde(rename = "gitlab")]
     Gitlab,
+    #[serde(rename = "gerrit")]
+    Gerrit,
+    #[serde(rename = "codeberg")]
+    Codeberg,
+    #[serde(rename = "gitea", alias = "forgejo")]
+    Gitea,
+    #[serde(rename = "sourcehut")]
+    Sourcehut,
+    #[serde(rename = "bitbucket")]
+    Bitbucket,
+    #[serde(rename = "none")]
+    None,
+}
+
+/// How to rank the repositories when jumping and listing.
+#[derive(Deserialize, JsonSchema, ValueEnum, Clone, Copy, PartialEq, Debug)]
+pub enum RankMode {
+    /// Rank by the accesses of all time
+    #[serde(rename = "alltime")]
+    Alltime,
+    /// Rank by the accesses in the recent 90 days, the accesses in last
+    /// week weigh the most
+    #[serde(rename = "recent")]
+    Recent,
 }
 
 fn empty_string() -> String {
     String::new()
 }
 
-fn empty_vec<T>() -> Vec<T> {
-    vec![]
+fn default_bool() -> bool {
+    false
 }
 
-fn empty_map<K, V>() -> HashMap<K, V> {
-    HashMap::new()
+fn default_rank() -> RankMode {
+    RankMode::Alltime
 }
 
-fn default_bool() -> bool {
-    false
+fn default_theme_base() -> ThemeBase {
+    ThemeBase::Dark
+}
+
+fn default_signing_format() -> SigningFormat {
+    SigningFormat::Openpgp
+}
+
+fn default_notify_after() -> u64 {
+    10
+}
+
+fn default_archive_after() -> u64 {
+    90
+}
+
+fn default_token_from() -> TokenSource {
+    TokenSource::Config
+}
+
+fn default_api_retry() -> u32 {
+    3
 }
 
 fn default_config() -> Config {
     Config {
         workspace: String::from("${HOME}/dev"),
-        keyword_map: empty_map(),
-        tag_rule: empty_map(),
+        keyword_map: HashMap::new(),
+        group_map: HashMap::new(),
+        tag_rule: HashMap::new(),
+        aliases: HashMap::new(),
+        hooks: HashMap::new(),
         remotes: vec![],
+        notify: false,
+        notify_after: default_notify_after(),
+        wip_on_jump: false,
+        rank: default_rank(),
+        session_boost: false,
+        templates: None,
+        repos: vec![],
+        resolve_symlinks: false,
+        status_cache: false,
+        repo_meta: false,
+        cold_storage: String::new(),
+        archive_after: default_archive_after(),
+        process_check: false,
+        theme: Theme::default(),
+        browser: String::new(),
+        scan_ignore: vec![],
+        zoxide: false,
     }
 }
 
@@ -128,29 +576,190 @@ impl Config {
         Ok(path)
     }
 
-    fn read_config() -> Result<Config> {
-        let path = Self::get_path()?;
-        let file = match fs::File::open(&path) {
+    fn read_config(path: &PathBuf) -> Result<Config> {
+        let file = match fs::File::open(path) {
             Ok(file) => file,
             Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(default_config()),
             Err(err) => return Err(err).context("could not read config file"),
         };
         match serde_yaml::from_reader(file) {
             Ok(config) => Ok(config),
-            Err(err) => return Err(err).context("could not parse config yaml"),
+            Err(err) => return Err(err).context(
+                "could not parse config yaml, use `gz config --schema` to see the available fields",
+            ),
         }
     }
 
     pub fn parse() -> Result<Config> {
-        let mut config = Self::read_config()?;
+        Self::parse_from(&Self::get_path()?)
+    }
+
+    /// Parse config from the file, the default config will be used if the
+    /// file does not exist.
+    pub fn parse_from(path: &PathBuf) -> Result<Config> {
+        let mut config = Self::read_config(path)?;
         if let Err(err) = config.normalize() {
             return Err(err).context("unable to validate config");
         };
+        util::Palette::init(&config.theme);
         Ok(config)
     }
 
+    /// Add the entries to `keyword_map` of the config file. The file is
+    /// edited as text rather than re-serialized, so that the comments and
+    /// layout written by user are kept. The result is parsed again before
+    /// writing, the file is untouched if it becomes invalid.
+    pub fn add_keyword_map(path: &PathBuf, entries: &[(String, String)]) -> Result<()> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            // The workspace is required, keep the default one.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                format!("workspace: {}\n", default_config().workspace)
+            }
+            Err(err) => return Err(err).context("could not read config file"),
+        };
+        let mut lines: Vec<String> = content.lines().map(String::from).collect();
+
+        let mut new_lines = Vec::with_capacity(entries.len());
+        let header = lines.iter().position(|line| {
+            line.strip_prefix("keyword_map:")
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '#']))
+        });
+        let insert_at = match header {
+            Some(idx) => {
+                let rest = lines[idx]["keyword_map:".len()..].trim();
+                if !rest.is_empty() && !rest.starts_with('#') {
+                    bail!("the keyword_map in config is written inline, please edit it manually")
+                }
+                // Follow the indent of the existing entries.
+                let indent = lines[idx + 1..]
+                    .iter()
+                    .find(|line| !line.trim().is_empty() && !line.trim().starts_with('#'))
+                    .filter(|line| line.starts_with([' ', '\t']))
+                    .map(|line| line[..line.len() - line.trim_start().len()].to_string())
+                    .unwrap_or_else(|| String::from("  "));
+                for (key, value) in entries {
+                    new_lines.push(format!(
+                        "{}{}: {}",
+                        indent,
+                        yaml_scalar(key)?,
+                        yaml_scalar(value)?
+                    ));
+                }
+                idx + 1
+            }
+            None => {
+                new_lines.push(String::from("keyword_map:"));
+                for (key, value) in entries {
+                    new_lines.push(format!("  {}: {}", yaml_scalar(key)?, yaml_scalar(value)?));
+                }
+                lines.len()
+            }
+        };
+        lines.splice(insert_at..insert_at, new_lines);
+        let mut content = lines.join("\n");
+        content.push('\n');
+
+        let config: Config = serde_yaml::from_str(&content)
+            .context("could not parse the edited config, please edit it manually")?;
+        for (key, value) in entries {
+            if config.keyword_map.get(key) != Some(value) {
+                bail!(
+                    "keyword {} is not written to config correctly, please edit it manually",
+                    style(key).yellow()
+                )
+            }
+        }
+        util::write(path, content).context("could not write config file")
+    }
+
+    /// Set `token_from` of the remote api to keyring in the config file,
+    /// which is edited as text like [`Config::add_keyword_map`].
+    pub fn set_keyring_token(path: &PathBuf, remote: &str) -> Result<()> {
+        let content = fs::read_to_string(path).context("could not read config file")?;
+        let mut lines: Vec<String> = content.lines().map(String::from).collect();
+        let manually = || {
+            format!(
+                "could not find api of remote {} in config, please set token_from manually",
+                style(remote).yellow()
+            )
+        };
+        // The key and its column, the "- " of list item is skipped.
+        let key_of = |line: &str| {
+            let key = line.trim_start();
+            let key = key.strip_prefix("- ").unwrap_or(key).trim_start();
+            (line.len() - key.len(), key.to_string())
+        };
+        let is_blank = |line: &str| line.trim().is_empty() || line.trim().starts_with('#');
+
+        // The keys of the remote item are at the same column as "name".
+        let (name_idx, column) = lines
+            .iter()
+            .enumerate()
+            .find_map(|(idx, line)| {
+                let (column, key) = key_of(line);
+                let value = key.strip_prefix("name:")?;
+                let value = value.split(" #").next().unwrap_or_default().trim();
+                (value.trim_matches(['"', '\'']) == remote).then_some((idx, column))
+            })
+            .with_context(manually)?;
+        let start = (0..=name_idx)
+            .rev()
+            .find(|idx| lines[*idx].trim_start().starts_with("- "))
+            .with_context(manually)?;
+        let end = (start + 1..lines.len())
+            .find(|idx| !is_blank(&lines[*idx]) && key_of(&lines[*idx]).0 < column)
+            .unwrap_or(lines.len());
+        let api_idx = (start..end)
+            .find(|idx| {
+                let (key_column, key) = key_of(&lines[*idx]);
+                key_column == column && key.starts_with("api:")
+            })
+            .with_context(manually)?;
+        let rest = key_of(&lines[api_idx]).1["api:".len()..].trim().to_string();
+        if !rest.is_empty() && !rest.starts_with('#') {
+            bail!("the api of remote is written inline, please set token_from manually")
+        }
+
+        let api_end = (api_idx + 1..end)
+            .find(|idx| !is_blank(&lines[*idx]) && key_of(&lines[*idx]).0 <= column)
+            .unwrap_or(end);
+        let indent = match (api_idx + 1..api_end).find(|idx| !is_blank(&lines[*idx])) {
+            Some(idx) => {
+                let line = &lines[idx];
+                line[..line.len() - line.trim_start().len()].to_string()
+            }
+            None => bail!(manually()),
+        };
+        let token_from = format!("{}token_from: keyring", indent);
+        let existing = (api_idx + 1..api_end).find(|idx| {
+            let line = &lines[*idx];
+            line.starts_with(&indent) && line[indent.len()..].starts_with("token_from:")
+        });
+        match existing {
+            Some(idx) => lines[idx] = token_from,
+            None => lines.insert(api_idx + 1, token_from),
+        }
+        let mut content = lines.join("\n");
+        content.push('\n');
+
+        let config: Config = serde_yaml::from_str(&content)
+            .context("could not parse the edited config, please set token_from manually")?;
+        let edited = config
+            .remotes
+            .iter()
+            .find(|r| r.name == remote)
+            .and_then(|r| r.api.as_ref())
+            .map(|api| api.token_from);
+        if edited != Some(TokenSource::Keyring) {
+            bail!(manually())
+        }
+        util::write(path, content).context("could not write config file")
+    }
+
     fn normalize(&mut self) -> Result<()> {
         self.workspace = util::expand_env(&self.workspace)?;
+        self.cold_storage = util::expand_env(&self.cold_storage)?;
         let mut remote_set: HashSet<&String> = HashSet::with_capacity(self.remotes.len());
         for remote in &mut self.remotes {
             if let Some(_) = remote_set.get(&remote.name) {
@@ -161,10 +770,99 @@ impl Config {
             if let Some(api) = &mut remote.api {
                 api.token = util::expand_env(&api.token)?;
             };
+            if let Some(clone) = &mut remote.clone {
+                clone.reference_cache = util::expand_env(&clone.reference_cache)?;
+                clone.ssh_key = util::expand_env(&clone.ssh_key)?;
+                if !clone.min_free_space.is_empty() {
+                    util::parse_bytes(&clone.min_free_space)?;
+                }
+            }
+            if let Some(signing) = &mut remote.signing {
+                if signing.key.is_empty() {
+                    bail!("signing key of remote {} is empty", remote.name)
+                }
+                signing.key = util::expand_env(&signing.key)?;
+            }
+            remote.commit_template = util::expand_env(&remote.commit_template)?;
+            for path in remote.group_commit_template.values_mut() {
+                *path = util::expand_env(&path)?;
+            }
+            remote.envrc = util::expand_env(&remote.envrc)?;
+            for path in remote.group_envrc.values_mut() {
+                *path = util::expand_env(&path)?;
+            }
+            let group_env = remote
+                .group_env
+                .values_mut()
+                .flat_map(|env| env.values_mut());
+            for value in remote.env.values_mut().chain(group_env) {
+                *value = util::expand_env(&value)?;
+            }
+        }
+        if let Some(templates) = &mut self.templates {
+            for path in templates.licenses.values_mut() {
+                *path = util::expand_env(&path)?;
+            }
+            templates.readme = util::expand_env(&templates.readme)?;
+        }
+        let mut repo_set: HashSet<(&String, &String)> = HashSet::with_capacity(self.repos.len());
+        for repo in &mut self.repos {
+            if !remote_set.contains(&repo.remote) {
+                bail!(
+                    "the remote {} of declared repo {} is not found in your config",
+                    style(&repo.remote).yellow(),
+                    style(&repo.name).yellow()
+                )
+            }
+            if !repo_set.insert((&repo.remote, &repo.name)) {
+                bail!(
+                    "declared repo {}:{} is duplicate in your config",
+                    repo.remote,
+                    repo.name
+                )
+            }
+            repo.path = util::expand_env(&repo.path)?;
+        }
+        for name in self.hooks.keys() {
+            if !name.starts_with("pre_") && !name.starts_with("post_") {
+                bail!(
+                    "hook {} is invalid, it should be pre_<command> or post_<command>",
+                    style(name).yellow()
+                )
+            }
         }
         Ok(())
     }
 
+    /// Send desktop notification for a finished operation, if notification
+    /// is enabled and the operation took long enough.
+    pub fn notify_done(&self, start: Instant, msg: impl AsRef<str>) {
+        if !self.notify || start.elapsed().as_secs() < self.notify_after {
+            return;
+        }
+        util::notify("git-zoxide", msg.as_ref());
+    }
+
+    /// Open the url with `browser` in config, see [`util::open_url`].
+    pub fn open_url(&self, url: impl AsRef<str>) -> Result<()> {
+        util::open_url(url, &self.browser)
+    }
+
+    /// Expand the leading group alias of the name by `group_map`, the alias
+    /// must be followed by "/", such as "w/" or "w/repo", so that a repo
+    /// named like an alias is not expanded. The name is returned as is if
+    /// it has no alias.
+    pub fn map_group(&self, name: &str) -> String {
+        let (group, rest) = match name.split_once('/') {
+            Some(split) => split,
+            None => return name.to_string(),
+        };
+        match self.group_map.get(group) {
+            Some(group) => format!("{}/{}", group.trim_matches('/'), rest),
+            None => name.to_string(),
+        }
+    }
+
     pub fn get_remote<'a>(&'a self, name: &str) -> Option<&'a Remote> {
         self.remotes.iter().find(|remote| remote.name == name)
     }
@@ -172,9 +870,216 @@ impl Config {
     pub fn must_get_remote<'a>(&'a self, name: &str) -> Result<&'a Remote> {
         match self.get_remote(name) {
             Some(remote) => Ok(remote),
-            None => bail!("could not find remote {}", style(name).yellow()),
+            None => bail!(
+                "could not find remote {}{}",
+                style(name).yellow(),
+                util::did_you_mean(
+                    name,
+                    self.remotes
+                        .iter()
+                        .map(|remote| (remote.name.as_str(), remote.name.as_str()))
+                )
+            ),
         }
     }
+
+    /// Find the remotes whose clone domain is `domain`, there can be more
+    /// than one, such as the work and personal accounts on github.com.
+    pub fn get_remotes_by_domain<'a>(&'a self, domain: &str) -> Vec<&'a Remote> {
+        self.remotes
+            .iter()
+            .filter(|remote| match &remote.clone {
+                Some(clone) => clone.domain == domain,
+                None => false,
+            })
+            .collect()
+    }
+}
+
+impl Remote {
+    /// Get the api token, from keyring if `token_from` is keyring. Empty
+    /// means the api is not enabled or the token is not set.
+    pub fn get_token(&self) -> Result<String> {
+        let api = match &self.api {
+            Some(api) => api,
+            None => return Ok(String::new()),
+        };
+        match api.token_from {
+            TokenSource::Config => Ok(api.token.clone()),
+            TokenSource::Keyring => match Keyring::get(&self.name)? {
+                Some(token) => Ok(token),
+                None => bail!(
+                    "could not find token of remote {} in keyring, please set it by `gz auth set`",
+                    style(&self.name).yellow()
+                ),
+            },
+        }
+    }
+
+    /// Get the overridden merge target repo for a repo. The key of
+    /// `merge_target` can be a full repo name or a group. For group, the
+    /// rest of the repo name will be appended to the mapped value, for
+    /// example, `mirrors: team` maps `mirrors/foo` to `team/foo`.
+    pub fn get_merge_target(&self, name: &str) -> Option<String> {
+        if let Some(target) = self.merge_target.get(name) {
+            return Some(target.clone());
+        }
+        // Find the longest group matched.
+        let mut group = name;
+        while let Some(idx) = group.rfind('/') {
+            group = &group[..idx];
+            if let Some(target) = self.merge_target.get(group) {
+                let rest = &name[group.len()..];
+                return Some(format!("{}{}", target.trim_end_matches('/'), rest));
+            }
+        }
+        None
+    }
+
+    /// Get the commit template for a repo, the `group_commit_template` of the
+    /// repo or its longest group takes precedence over `commit_template`.
+    pub fn get_commit_template(&self, name: &str) -> Option<&str> {
+        if let Some(path) = find_group(&self.group_commit_template, name) {
+            return Some(path);
+        }
+        match self.commit_template.is_empty() {
+            true => None,
+            false => Some(&self.commit_template),
+        }
+    }
+
+    /// Get the `.envrc` template for a repo, the `group_envrc` of the repo
+    /// or its longest group takes precedence over `envrc`.
+    pub fn get_envrc(&self, name: &str) -> Option<&str> {
+        if let Some(path) = find_group(&self.group_envrc, name) {
+            return Some(path);
+        }
+        match self.envrc.is_empty() {
+            true => None,
+            false => Some(&self.envrc),
+        }
+    }
+
+    /// Get the environment variables for a repo, the `group_env` of the repo
+    /// and its groups extend `env`, the longer group takes precedence.
+    pub fn get_env(&self, name: &str) -> BTreeMap<&str, &str> {
+        let mut keys = vec![name];
+        let mut group = name;
+        while let Some(idx) = group.rfind('/') {
+            group = &group[..idx];
+            keys.push(group);
+        }
+        let mut env: BTreeMap<&str, &str> = self
+            .env
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        for key in keys.into_iter().rev() {
+            if let Some(group_env) = self.group_env.get(key) {
+                env.extend(
+                    group_env
+                        .iter()
+                        .map(|(key, value)| (key.as_str(), value.as_str())),
+                );
+            }
+        }
+        env
+    }
+
+    /// Check if the branch is protected from force-pushing, the
+    /// `protected_branches` can contain glob patterns like `release/*`.
+    pub fn is_protected(&self, branch: &str) -> bool {
+        self.protected_branches
+            .iter()
+            .any(|pattern| util::glob_match(pattern, branch))
+    }
+}
+
+impl Clone {
+    /// The ssh command using `ssh_key`, only the key is offered to the
+    /// server, the keys in ssh agent are ignored.
+    pub fn ssh_command(&self) -> Option<String> {
+        if self.ssh_key.is_empty() {
+            return None;
+        }
+        let key = self.ssh_key.replace('\'', "'\\''");
+        Some(format!("ssh -i '{}' -o IdentitiesOnly=yes", key))
+    }
+}
+
+/// Find the value of the repo or its longest group in the map.
+fn find_group<'a>(map: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    if let Some(value) = map.get(name) {
+        return Some(value);
+    }
+    let mut group = name;
+    while let Some(idx) = group.rfind('/') {
+        group = &group[..idx];
+        if let Some(value) = map.get(group) {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Format the string as a YAML scalar, quoted only if required.
+fn yaml_scalar(s: &str) -> Result<String> {
+    let value = serde_yaml::to_string(s).context("could not encode yaml")?;
+    Ok(value.trim_end().to_string())
+}
+
+impl Trailers {
+    /// Build the `git commit` arguments to append the trailers.
+    pub fn args(&self) -> Result<Vec<String>> {
+        let mut args = Vec::with_capacity(self.custom.len() + 2);
+        if self.sign_off {
+            args.push(String::from("--signoff"));
+        }
+        if self.change_id {
+            // The Change-Id used by Gerrit is "I" followed by 40 hex chars.
+            let mut id = String::with_capacity(41);
+            id.push('I');
+            for _ in 0..40 {
+                id.push(char::from_digit(fastrand::u32(0..16), 16).unwrap());
+            }
+            args.push(format!("--trailer=Change-Id: {}", id));
+        }
+        let mut keys: Vec<_> = self.custom.keys().collect();
+        keys.sort();
+        for key in keys {
+            let value = util::expand_env(&self.custom[key])?;
+            args.push(format!("--trailer={}: {}", key, value));
+        }
+        Ok(args)
+    }
+}
+
+impl Ticket {
+    /// Extract the ticket id from branch name, returns `None` if the branch
+    /// does not contain a ticket.
+    pub fn extract(&self, branch: &str) -> Result<Option<String>> {
+        let re = Regex::new(&self.regex)
+            .with_context(|| format!("invalid ticket regex {}", style(&self.regex).yellow()))?;
+        Ok(re.find(branch).map(|m| m.as_str().to_string()))
+    }
+
+    /// Build the url of the ticket, the `{ticket}` in template will be
+    /// replaced with ticket id.
+    pub fn url(&self, id: &str) -> Option<String> {
+        if self.url_template.is_empty() {
+            return None;
+        }
+        Some(util::render_template(&self.url_template, &[("ticket", id)]))
+    }
+
+    /// Prefix the message with ticket id, skip if the message already
+    /// mentions it.
+    pub fn prefix(id: &str, msg: &str) -> String {
+        if msg.contains(id) {
+            return msg.to_string();
+        }
+        format!("{} {}", id, msg)
+    }
 }
 
 impl Step {
@@ -234,3 +1139,271 @@ impl Step {
         Ok(())
     }
 }
+
+impl RepoConfig {
+    const FILE_NAME: &str = ".gz.yaml";
+
+    /// Load the config of the repo at `root`, `None` if the repo has no
+    /// config or the user does not trust it. The user is asked to trust the
+    /// config when it is seen for the first time or changed.
+    pub fn load(root: &Path) -> Result<Option<RepoConfig>> {
+        let path = root.join(Self::FILE_NAME);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("could not read {}", path.display()))
+            }
+        };
+        let path_str = util::path_to_str(&path)?;
+        let hash: String = openssl::sha::sha256(content.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        let mut store = TrustStore::open()?;
+        let trusted = match store.get(path_str, &hash) {
+            Some(trusted) => trusted,
+            None if !console::Term::stderr().is_term() => {
+                _ = writeln!(
+                    io::stderr(),
+                    "{} {} is not trusted, it is ignored until you trust it in a terminal",
+                    util::Palette::get().warning.apply_to("warning"),
+                    style(path.display()).yellow()
+                );
+                return Ok(None);
+            }
+            None => {
+                _ = writeln!(io::stderr(), "{}", style(path.display()).yellow());
+                // Escape the control characters, so that the content cannot
+                // hide itself with terminal escape sequences.
+                let shown: String = content
+                    .trim_end()
+                    .chars()
+                    .map(|c| match c {
+                        '\n' | '\t' => c.to_string(),
+                        c if c.is_control() => c.escape_debug().to_string(),
+                        c => c.to_string(),
+                    })
+                    .collect();
+                _ = writeln!(io::stderr(), "{}", shown);
+                let trusted = util::ask("the repo config above can run scripts, do you trust it")?;
+                store.set(path_str, &hash, trusted);
+                store.save()?;
+                trusted
+            }
+        };
+        if !trusted {
+            return Ok(None);
+        }
+        let cfg = serde_yaml::from_str(&content)
+            .with_context(|| format!("could not parse {}", path.display()))?;
+        Ok(Some(cfg))
+    }
+
+    /// Load the config of the repo at current directory, see [`RepoConfig::load`].
+    pub fn current() -> Result<Option<RepoConfig>> {
+        match util::git_roots(&util::current_dir()?)?.into_iter().next() {
+            Some(root) => Self::load(&root),
+            None => Ok(None),
+        }
+    }
+
+    /// Run the `on_enter` script of the repo at `path`.
+    pub fn run_on_enter(remote: &Remote, name: &str, path: &PathBuf) -> Result<()> {
+        let cfg = match Self::load(path)? {
+            Some(cfg) if !cfg.on_enter.is_empty() => cfg,
+            _ => return Ok(()),
+        };
+        util::print_operation(format!("exec {} ...", style("on_enter").yellow()));
+        let mut cmd = Shell::bash(&cfg.on_enter);
+        for (key, val) in remote.get_env(name) {
+            cmd.env(key, val);
+        }
+        cmd.with_path(path);
+        // The stdout is reserved for the path to jump.
+        let output = cmd.exec()?;
+        if !output.is_empty() {
+            _ = writeln!(io::stderr(), "{}", output);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit<F>(content: Option<&str>, edit: F) -> (Result<()>, String)
+    where
+        F: FnOnce(&PathBuf) -> Result<()>,
+    {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        if let Some(content) = content {
+            fs::write(&path, content).unwrap();
+        }
+        let result = edit(&path);
+        (result, fs::read_to_string(&path).unwrap_or_default())
+    }
+
+    fn add_keyword_map(content: Option<&str>) -> (Result<()>, String) {
+        let entries = [(String::from("gz"), String::from("git-zoxide"))];
+        edit(content, |path| Config::add_keyword_map(path, &entries))
+    }
+
+    #[test]
+    fn add_keyword_map_section() {
+        // Missing file.
+        let (result, content) = add_keyword_map(None);
+        result.unwrap();
+        assert_eq!(
+            content,
+            "workspace: ${HOME}/dev\nkeyword_map:\n  gz: git-zoxide\n"
+        );
+
+        // Missing section.
+        let (result, content) = add_keyword_map(Some("# my config\nworkspace: ~/dev\n"));
+        result.unwrap();
+        assert_eq!(
+            content,
+            "# my config\nworkspace: ~/dev\nkeyword_map:\n  gz: git-zoxide\n"
+        );
+
+        // Empty section, followed by another key.
+        let (result, content) = add_keyword_map(Some("keyword_map:\nworkspace: ~/dev\n"));
+        result.unwrap();
+        assert_eq!(
+            content,
+            "keyword_map:\n  gz: git-zoxide\nworkspace: ~/dev\n"
+        );
+    }
+
+    #[test]
+    fn add_keyword_map_indent() {
+        let config = "\
+keyword_map: # the shortcuts
+    # dotfiles
+    dot: dotfiles
+workspace: ~/dev
+";
+        let (result, content) = add_keyword_map(Some(config));
+        result.unwrap();
+        assert_eq!(
+            content,
+            "\
+keyword_map: # the shortcuts
+    gz: git-zoxide
+    # dotfiles
+    dot: dotfiles
+workspace: ~/dev
+"
+        );
+    }
+
+    #[test]
+    fn add_keyword_map_inline() {
+        let config = "keyword_map: {dot: dotfiles}\n";
+        let (result, content) = add_keyword_map(Some(config));
+        assert!(result.is_err());
+        assert_eq!(content, config);
+    }
+
+    fn set_keyring_token(content: &str) -> (Result<()>, String) {
+        edit(Some(content), |path| Config::set_keyring_token(path, "gh"))
+    }
+
+    #[test]
+    fn set_keyring_token_insert() {
+        let config = "\
+workspace: ~/dev
+remotes:
+  - name: gl
+    api:
+      provider: gitlab
+  # the github
+  - name: \"gh\" # main
+    api: # github.com
+        provider: github
+    clone:
+      domain: github.com
+";
+        let (result, content) = set_keyring_token(config);
+        result.unwrap();
+        assert_eq!(
+            content,
+            "\
+workspace: ~/dev
+remotes:
+  - name: gl
+    api:
+      provider: gitlab
+  # the github
+  - name: \"gh\" # main
+    api: # github.com
+        token_from: keyring
+        provider: github
+    clone:
+      domain: github.com
+"
+        );
+
+        // The key of the item is at the same column as "name".
+        let config = "\
+workspace: ~/dev
+remotes:
+- api:
+    provider: github
+  name: gh
+";
+        let (result, content) = set_keyring_token(config);
+        result.unwrap();
+        assert_eq!(
+            content,
+            "\
+workspace: ~/dev
+remotes:
+- api:
+    token_from: keyring
+    provider: github
+  name: gh
+"
+        );
+    }
+
+    #[test]
+    fn set_keyring_token_replace() {
+        let config = "\
+workspace: ~/dev
+remotes:
+  - name: gh
+    api:
+      provider: github
+      token_from: config
+";
+        let (result, content) = set_keyring_token(config);
+        result.unwrap();
+        assert_eq!(
+            content,
+            config.replace("token_from: config", "token_from: keyring")
+        );
+    }
+
+    #[test]
+    fn set_keyring_token_manually() {
+        for config in [
+            // Missing remote.
+            "workspace: ~/dev\nremotes:\n  - name: gl\n    api:\n      provider: gitlab\n",
+            // Missing api.
+            "workspace: ~/dev\nremotes:\n  - name: gh\n    clone:\n      domain: github.com\n",
+            // Inline api.
+            "workspace: ~/dev\nremotes:\n  - name: gh\n    api: {provider: github}\n",
+            // Empty api.
+            "workspace: ~/dev\nremotes:\n  - name: gh\n    api:\n",
+        ] {
+            let (result, content) = set_keyring_token(config);
+            assert!(result.is_err(), "{}", config);
+            assert_eq!(content, config);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::db::{Database, Epoch};
+use crate::util;
+
+/// A mutating operation recorded in the history log.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct HistoryEntry {
+    pub time: Epoch,
+
+    /// The operation, such as "clone", "remove" or "delete-branch".
+    pub action: String,
+
+    /// The repo operated, empty if the operation is done in a directory not
+    /// bound to the database.
+    #[serde(default)]
+    pub remote: String,
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(default)]
+    pub path: String,
+
+    /// The detail of operation, such as the deleted branch.
+    #[serde(default)]
+    pub detail: String,
+}
+
+/// The append-only log of mutating operations, stored as JSON lines in the
+/// data directory. Unlike the other data files, the log is never rewritten,
+/// so it is safe to be appended by concurrent commands.
+pub struct History;
+
+impl HistoryEntry {
+    pub fn new(action: &str) -> HistoryEntry {
+        HistoryEntry {
+            action: action.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn repo(mut self, remote: impl AsRef<str>, name: impl AsRef<str>) -> HistoryEntry {
+        self.remote = remote.as_ref().to_string();
+        self.name = name.as_ref().to_string();
+        self
+    }
+
+    pub fn path(mut self, path: impl AsRef<str>) -> HistoryEntry {
+        self.path = path.as_ref().to_string();
+        self
+    }
+
+    /// Use the git root of current directory as path.
+    pub fn current_path(self) -> HistoryEntry {
+        let root = util::current_dir()
+            .and_then(|dir| util::git_roots(&dir))
+            .ok()
+            .and_then(|roots| roots.into_iter().next());
+        match root {
+            Some(root) => self.path(root.display().to_string()),
+            None => self,
+        }
+    }
+
+    pub fn detail(mut self, detail: impl AsRef<str>) -> HistoryEntry {
+        self.detail = detail.as_ref().to_string();
+        self
+    }
+
+    /// Append the entry to history. The operation has been done when this
+    /// is called, so a failure only prints warning.
+    pub fn record(mut self) {
+        if let Err(err) = History::append(&mut self) {
+            _ = writeln!(
+                io::stderr(),
+                "{} unable to record history: {:#}",
+                util::Palette::get().warning.apply_to("warning"),
+                err
+            );
+        }
+    }
+}
+
+impl History {
+    fn path() -> Result<PathBuf> {
+        Ok(config::Config::get_data_dir()?.join("history.jsonl"))
+    }
+
+    fn append(entry: &mut HistoryEntry) -> Result<()> {
+        if Database::in_memory() {
+            return Ok(());
+        }
+        entry.time = util::current_time()?;
+        let mut line = serde_json::to_string(entry).context("could not encode history")?;
+        line.push('\n');
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("unable to create data directory")?;
+        }
+        // A single write of a line in append mode is atomic enough for the
+        // concurrent commands.
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .context("could not open history file")?;
+        file.write_all(line.as_bytes())
+            .context("could not write history file")
+    }
+
+    /// Read all entries, in the order they were appended. The broken lines (for example,
+    /// written by an interrupted command) are skipped.
+    pub fn read() -> Result<Vec<HistoryEntry>> {
+        if Database::in_memory() {
+            return Ok(vec![]);
+        }
+        let content = match fs::read_to_string(Self::path()?) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err).context("could not read history file"),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
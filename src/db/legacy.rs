@@ -0,0 +1,30 @@
+use serde::Deserialize;
+
+use crate::db::repo::{Epoch, Rank, Repo};
+
+/// The repo data stored in database version 1.
+#[derive(Debug, Deserialize)]
+pub struct RepoV1 {
+    pub remote: String,
+    pub name: String,
+    pub path: String,
+
+    pub last_accessed: Epoch,
+    pub accessed: Rank,
+}
+
+impl From<RepoV1> for Repo {
+    fn from(repo: RepoV1) -> Repo {
+        Repo {
+            remote: repo.remote,
+            name: repo.name,
+            path: repo.path,
+            last_accessed: repo.last_accessed,
+            accessed: repo.accessed,
+            use_ssh: None,
+            history: vec![],
+            session: String::new(),
+            meta: None,
+        }
+    }
+}
@@ -1,64 +1,349 @@
+mod history;
+mod legacy;
 mod repo;
+mod store;
 
-use console::style;
-use std::{collections::HashMap, fs, io, path::PathBuf};
+use console::{style, Term};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+};
 
 use anyhow::{bail, Context, Result};
-use bincode::Options;
+use serde::{Deserialize, Serialize};
 
-pub use crate::db::repo::{Epoch, Repo};
-use crate::{config, util};
+use crate::config::RankMode;
+pub use crate::db::history::{History, HistoryEntry};
+pub use crate::db::repo::{Epoch, Repo, RepoMeta};
+pub use crate::db::store::Store;
+use crate::git;
+use crate::util::{self, Shell};
+use crate::{config, errors};
 
 pub struct Database {
-    path: PathBuf,
+    /// The database file, `None` means in-memory database.
+    path: Option<PathBuf>,
     pub repos: Vec<Repo>,
+
+    /// The repos when the database was opened or saved, to record the added
+    /// and removed repos in history. `None` means not recording.
+    saved: Option<HashSet<(String, String)>>,
 }
 
 impl Database {
-    const VERSION: u32 = 1;
+    const VERSION: u32 = 2;
+
+    /// Setting the env to this value makes an in-memory database, the other
+    /// data files, such as the stores and history, are in memory too.
+    const MEMORY_PATH: &str = ":memory:";
 
     pub fn open() -> Result<Database> {
-        let data_dir = config::Config::get_data_dir()?;
-        let path = data_dir.join("database");
+        if Self::in_memory() {
+            return Ok(Self::memory());
+        }
+        let path = match env::var_os("_GZ_DATABASE_PATH") {
+            Some(path) => PathBuf::from(path),
+            None => config::Config::get_data_dir()?.join("database"),
+        };
+        let mut db = Self::open_at(path)?;
+        db.saved = Some(db.keys());
+        Ok(db)
+    }
 
+    /// Open the database from the file, the file will be created when saving
+    /// if it does not exist.
+    pub fn open_at(path: PathBuf) -> Result<Database> {
         match fs::read(&path) {
             Ok(bytes) => Ok(Database {
-                path,
                 repos: Self::deserialize(&bytes)?,
+                path: Some(path),
+                saved: None,
             }),
             Err(err) if err.kind() == io::ErrorKind::NotFound => {
-                fs::create_dir_all(&data_dir).with_context(|| {
-                    format!("unable to create data directory: {}", data_dir.display())
-                })?;
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).with_context(|| {
+                        format!("unable to create data directory: {}", dir.display())
+                    })?;
+                }
                 Ok(Database {
-                    path,
+                    path: Some(path),
                     repos: vec![],
+                    saved: None,
                 })
             }
             Err(err) => Err(err).context("could not read database file"),
         }
     }
 
+    /// Whether the data is in memory, see [`Database::MEMORY_PATH`].
+    pub fn in_memory() -> bool {
+        env::var_os("_GZ_DATABASE_PATH").is_some_and(|path| path == Self::MEMORY_PATH)
+    }
+
+    /// The in-memory database, nothing will be written when saving.
+    pub fn memory() -> Database {
+        Database {
+            path: None,
+            repos: vec![],
+            saved: None,
+        }
+    }
+
     pub fn save(&mut self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
         let bytes = Self::serialize(&self.repos)?;
-        if let Err(err) = util::write(&self.path, bytes) {
+        if let Err(err) = util::write(path, bytes) {
             return Err(err).context("could not write database file");
         }
 
+        if let Some(saved) = &self.saved {
+            let keys = self.keys();
+            for (remote, name) in keys.difference(saved) {
+                HistoryEntry::new("db-add").repo(remote, name).record();
+            }
+            for (remote, name) in saved.difference(&keys) {
+                HistoryEntry::new("db-remove").repo(remote, name).record();
+            }
+            self.saved = Some(keys);
+        }
         Ok(())
     }
 
-    pub fn current(&self, workspace: impl AsRef<str>) -> Result<&Repo> {
+    fn keys(&self) -> HashSet<(String, String)> {
+        self.repos
+            .iter()
+            .map(|repo| (repo.remote.clone(), repo.name.clone()))
+            .collect()
+    }
+
+    pub fn current(&self, cfg: &config::Config) -> Result<&Repo> {
+        let idx = self.current_index(cfg)?;
+        Ok(&self.repos[idx])
+    }
+
+    pub fn current_index(&self, cfg: &config::Config) -> Result<usize> {
+        let current_dir = util::current_dir()?;
+        match self.find_current(cfg, &current_dir)? {
+            Some(idx) => Ok(idx),
+            None => bail!(errors::REPO_NOT_BOUND),
+        }
+    }
+
+    /// Like `current`, but if current repository is not in the database,
+    /// detect its remote by matching the domain of "origin" url with the
+    /// config. The detected repository is added to the database, it will be
+    /// saved only if the user chooses to attach it.
+    pub fn current_or_detect(&mut self, cfg: &config::Config) -> Result<&Repo> {
         let current_dir = util::current_dir()?;
+        if let Some(idx) = self.find_current(cfg, &current_dir)? {
+            return Ok(&self.repos[idx]);
+        }
 
-        for repo in &self.repos {
-            let path = repo.path(workspace.as_ref())?;
-            if current_dir.starts_with(&path) {
-                return Ok(repo);
+        let root = match util::git_roots(&current_dir)?.into_iter().next() {
+            Some(root) => root,
+            None => bail!(errors::REPO_NOT_BOUND),
+        };
+        let root = util::path_to_str(&root)?;
+        let url = match Shell::git()
+            .mute()
+            .with_git_path(root)
+            .args(["config", "--get", "remote.origin.url"])
+            .exec()
+        {
+            Ok(url) => url,
+            Err(_) => bail!(errors::REPO_NOT_BOUND),
+        };
+        let (domain, name) = match util::parse_git_url(&url) {
+            Some(parsed) => parsed,
+            None => bail!("could not parse origin url {}", style(&url).yellow()),
+        };
+        let remote = match self.remotes_by_domain(cfg, &domain, &name)?[..] {
+            [remote] => remote,
+            [] => bail!(
+                "{}, and no remote matches the origin domain {}",
+                errors::REPO_NOT_BOUND,
+                style(&domain).yellow()
+            ),
+            _ => bail!(
+                "{}, and multiple remotes match the origin domain {}, please attach it manually",
+                errors::REPO_NOT_BOUND,
+                style(&domain).yellow()
+            ),
+        };
+        if let Some(idx) = self.get(&remote.name, &name) {
+            bail!(
+                "repository {}:{} is already bound to {}",
+                style(&remote.name).yellow(),
+                style(&name).yellow(),
+                style(self.repos[idx].path(&cfg.workspace)?.display()).yellow()
+            )
+        }
+
+        _ = writeln!(
+            io::stderr(),
+            "detected repository {}:{}",
+            style(&remote.name).yellow(),
+            style(&name).yellow()
+        );
+        let idx = self.add(&remote.name, &name, root);
+        if Term::stderr().is_term() && util::ask("do you want to attach it")? {
+            self.save()?;
+        }
+        Ok(&self.repos[idx])
+    }
+
+    /// Check whether the directory of the repo already exists but belongs to
+    /// another repository, returns the explanation of the conflict. This can
+    /// happen when the names differ only in case (on case-insensitive file
+    /// systems), or the repository was renamed.
+    pub fn find_collision(&self, idx: usize, cfg: &config::Config) -> Result<Option<String>> {
+        let repo = &self.repos[idx];
+        let path = repo.path(&cfg.workspace)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let path_str = util::path_to_str(&path)?.to_lowercase();
+        for (other_idx, other) in self.repos.iter().enumerate() {
+            if other_idx == idx {
+                continue;
+            }
+            if util::path_to_str(&other.path(&cfg.workspace)?)?.to_lowercase() == path_str {
+                return Ok(Some(format!(
+                    "the directory is used by repository {}:{}",
+                    style(&other.remote).yellow(),
+                    style(&other.name).yellow()
+                )));
+            }
+        }
+
+        let clone = match cfg.get_remote(&repo.remote) {
+            Some(remote) => match &remote.clone {
+                Some(clone) => clone,
+                None => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+        let url = Shell::git()
+            .mute()
+            .with_git_path(util::path_to_str(&path)?)
+            .args(["config", "--get", "remote.origin.url"])
+            .exec()
+            .unwrap_or_default();
+        if let Some((domain, name)) = util::parse_git_url(&url) {
+            if domain != clone.domain || name != repo.name {
+                return Ok(Some(format!(
+                    "the directory is a clone of {}",
+                    style(format!("{}/{}", domain, name)).yellow()
+                )));
+            }
+        }
+        Ok(None)
+    }
+
+    /// If the directory of the newly added repo belongs to another
+    /// repository, explain the conflict and let the user choose to reuse the
+    /// directory, relocate the repo to another path or abort.
+    pub fn resolve_collision(&mut self, idx: usize, cfg: &config::Config) -> Result<()> {
+        let reason = match self.find_collision(idx, cfg)? {
+            Some(reason) => reason,
+            None => return Ok(()),
+        };
+        let path = self.repos[idx].path(&cfg.workspace)?;
+        let msg = format!(
+            "path {} of {}:{} already exists, {}",
+            style(path.display()).yellow(),
+            style(&self.repos[idx].remote).yellow(),
+            style(&self.repos[idx].name).yellow(),
+            reason
+        );
+        if !Term::stderr().is_term() {
+            bail!(msg)
+        }
+        _ = writeln!(io::stderr(), "{}", msg);
+
+        let options = ["reuse the directory", "relocate to another path", "abort"];
+        match util::select("what do you want to do", &options)? {
+            0 => Ok(()),
+            1 => {
+                let path = util::input("please input the new path")?;
+                let path = util::expand_env(path.trim())?;
+                if Path::new(&path).exists() {
+                    bail!("path {} already exists", style(&path).yellow())
+                }
+                self.repos[idx].path = path;
+                Ok(())
+            }
+            _ => bail!(errors::SilentExit { code: 60 }),
+        }
+    }
+
+    /// Find the remotes matching the domain of repo url. When multiple
+    /// remotes share the domain, they are narrowed to the ones already
+    /// holding the repo `name`, then to the remote of current repo. More
+    /// than one remote returned means it is still ambiguous.
+    pub fn remotes_by_domain<'a>(
+        &self,
+        cfg: &'a config::Config,
+        domain: &str,
+        name: &str,
+    ) -> Result<Vec<&'a config::Remote>> {
+        let remotes = cfg.get_remotes_by_domain(domain);
+        if remotes.len() <= 1 {
+            return Ok(remotes);
+        }
+        if !name.is_empty() {
+            let holding: Vec<_> = remotes
+                .iter()
+                .filter(|remote| self.get(&remote.name, name).is_some())
+                .copied()
+                .collect();
+            if holding.len() == 1 {
+                return Ok(holding);
             }
         }
+        if let Some(idx) = self.find_current(cfg, &util::current_dir()?)? {
+            let current = &self.repos[idx].remote;
+            if let Some(remote) = remotes.iter().find(|remote| &remote.name == current) {
+                return Ok(vec![remote]);
+            }
+        }
+        Ok(remotes)
+    }
 
-        bail!("current path does not bound to any repository")
+    fn find_current(&self, cfg: &config::Config, dir: &Path) -> Result<Option<usize>> {
+        // Match the top-level of current git repository rather than the
+        // prefix of current dir, so that the nested repositories (such as
+        // submodules) will not be treated as their parents.
+        // Resolve the symlinks of workspace and root once rather than every
+        // repo path, the attached paths are resolved when attaching.
+        let workspace = match cfg.resolve_symlinks {
+            true => fs::canonicalize(&cfg.workspace)
+                .ok()
+                .and_then(|path| path.to_str().map(String::from)),
+            false => None,
+        };
+        for root in util::git_roots(dir)? {
+            let resolved = match workspace {
+                Some(_) => fs::canonicalize(&root).ok(),
+                None => None,
+            };
+            for (idx, repo) in self.repos.iter().enumerate() {
+                if repo.path(&cfg.workspace)? == root {
+                    return Ok(Some(idx));
+                }
+                if let (Some(workspace), Some(resolved)) = (&workspace, &resolved) {
+                    if &repo.path(workspace)? == resolved {
+                        return Ok(Some(idx));
+                    }
+                }
+            }
+        }
+        Ok(None)
     }
 
     pub fn get<R, N>(&self, remote: R, name: N) -> Option<usize>
@@ -71,13 +356,14 @@ impl Database {
             .position(|repo| repo.remote == remote.as_ref() && repo.name == name.as_ref())
     }
 
-    pub fn get_by_path<S>(&self, path: S) -> Option<usize>
+    pub fn get_by_path<S>(&self, path: S, resolve_symlinks: bool) -> Option<usize>
     where
         S: AsRef<str>,
     {
-        self.repos
-            .iter()
-            .position(|repo| repo.path != "" && repo.path == path.as_ref())
+        let path = Path::new(path.as_ref());
+        self.repos.iter().position(|repo| {
+            repo.path != "" && util::same_path(Path::new(&repo.path), path, resolve_symlinks)
+        })
     }
 
     pub fn add<R, N, P>(&mut self, remote: R, name: N, path: P) -> usize
@@ -92,6 +378,10 @@ impl Database {
             path: path.as_ref().to_string(),
             last_accessed: 0,
             accessed: 0.0,
+            use_ssh: None,
+            history: vec![],
+            session: String::new(),
+            meta: None,
         });
         self.repos.len() - 1
     }
@@ -105,14 +395,37 @@ impl Database {
             repo.remote.as_str() == remote.as_ref() && repo.name.as_str() == name.as_ref()
         }) {
             Some(idx) => Ok(idx),
-            None => bail!(
-                "could not find repository {}:{}",
-                style(remote.as_ref()).yellow(),
-                style(name.as_ref()).yellow()
-            ),
+            None => {
+                // The remote might be wrong too, suggest across all remotes.
+                let full = format!("{}:{}", remote.as_ref(), name.as_ref());
+                let names: Vec<String> = self
+                    .repos
+                    .iter()
+                    .map(|repo| format!("{}:{}", repo.remote, repo.name))
+                    .collect();
+                bail!(
+                    "could not find repository {}:{}{}",
+                    style(remote.as_ref()).yellow(),
+                    style(name.as_ref()).yellow(),
+                    util::did_you_mean(
+                        &full,
+                        names.iter().map(|name| (name.as_str(), name.as_str()))
+                    )
+                )
+            }
         }
     }
 
+    /// Filter repos by remote and group, the empty value means no filter.
+    pub fn filter(&self, remote: &str, group: &str) -> Vec<&Repo> {
+        let prefix = format!("{}/", group.trim_end_matches('/'));
+        self.repos
+            .iter()
+            .filter(|repo| remote.is_empty() || repo.remote == remote)
+            .filter(|repo| group.is_empty() || repo.name.starts_with(&prefix))
+            .collect()
+    }
+
     pub fn list_paths(&self, workspace: &String) -> Result<Vec<PathBuf>> {
         let mut paths: Vec<PathBuf> = Vec::with_capacity(self.repos.len());
         for repo in &self.repos {
@@ -136,74 +449,206 @@ impl Database {
             Some(kw) => kw,
             None => keyword.as_ref(),
         };
+        match self.match_keyword_all(remote.as_ref(), keyword).first() {
+            Some(idx) => Ok(*idx),
+            None => bail!(
+                "could not find repository matches {}{}",
+                style(keyword).yellow(),
+                self.suggest_keyword(remote.as_ref(), keyword, km)
+            ),
+        }
+    }
+
+    /// Build the "did you mean" suggestion for a keyword matching nothing,
+    /// the keyword is compared with the names of repos (the base names if
+    /// the keyword has no group) and the keys of keyword map.
+    fn suggest_keyword(&self, remote: &str, keyword: &str, km: &HashMap<String, String>) -> String {
+        let repos = self.filter(remote, "");
+        let names: Vec<(String, String)> = repos
+            .iter()
+            .map(|repo| {
+                let key = match keyword.contains('/') {
+                    true => repo.name.clone(),
+                    false => util::split_name(&repo.name).1,
+                };
+                let display = match remote.is_empty() {
+                    true => format!("{}:{}", repo.remote, repo.name),
+                    false => repo.name.clone(),
+                };
+                (key, display)
+            })
+            .collect();
+        let candidates = names
+            .iter()
+            .map(|(key, display)| (key.as_str(), display.as_str()))
+            .chain(km.keys().map(|key| (key.as_str(), key.as_str())));
+        util::did_you_mean(keyword, candidates)
+    }
+
+    /// Return all the repos match the keyword, in the order of database.
+    pub fn match_keyword_all(&self, remote: impl AsRef<str>, keyword: &str) -> Vec<usize> {
         let (group, base) = util::split_name(keyword);
-        let opt = self.repos.iter().position(|repo| {
-            if remote.as_ref() != "" && repo.remote != remote.as_ref() {
-                return false;
-            }
-            let (repo_group, repo_base) = util::split_name(&repo.name);
-            if group == "" {
-                return repo_base.contains(&base);
-            }
+        self.repos
+            .iter()
+            .enumerate()
+            .filter(|(_, repo)| {
+                if remote.as_ref() != "" && repo.remote != remote.as_ref() {
+                    return false;
+                }
+                let (repo_group, repo_base) = util::split_name(&repo.name);
+                if group == "" {
+                    return repo_base.contains(&base);
+                }
 
-            repo_group == group && repo_base.contains(&base)
-        });
-        match opt {
-            Some(idx) => Ok(idx),
-            None => bail!(
-                "could not find repository matches {}",
-                style(keyword).yellow()
+                repo_group == group && repo_base.contains(&base)
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Return all the repos whose cached description or topics contain all
+    /// the words of the keyword (case-insensitive), in the order of database.
+    pub fn match_meta(&self, remote: &str, keyword: &str) -> Vec<usize> {
+        let words: Vec<String> = keyword
+            .split_whitespace()
+            .map(|word| word.to_lowercase())
+            .collect();
+        if words.is_empty() {
+            return vec![];
+        }
+        self.repos
+            .iter()
+            .enumerate()
+            .filter(|(_, repo)| remote.is_empty() || repo.remote == remote)
+            .filter(|(_, repo)| match &repo.meta {
+                Some(meta) => {
+                    let text = format!("{} {}", meta.description, meta.topics.join(" "));
+                    let text = text.to_lowercase();
+                    words.iter().all(|word| text.contains(word))
+                }
+                None => false,
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Match the keyword, the bound repo takes precedence. If `select` is
+    /// true and the keyword is ambiguous, let user select one with fzf and
+    /// bind the keyword to it. If `deep` is true, the repos whose metadata
+    /// matches the keyword are appended after the repos whose name matches.
+    pub fn match_keyword_bound(
+        &self,
+        remote: &str,
+        keyword: &str,
+        km: &HashMap<String, String>,
+        select: bool,
+        deep: bool,
+    ) -> Result<usize> {
+        let mut bindings = Bindings::open()?;
+        if let Some(idx) = bindings.resolve(self, remote, keyword) {
+            bindings.hit(keyword);
+            bindings.save()?;
+            return Ok(idx);
+        }
+
+        let mapped = match km.get(keyword) {
+            Some(kw) => kw.as_str(),
+            None => keyword,
+        };
+        let mut items = self.match_keyword_all(remote, mapped);
+        if deep {
+            for idx in self.match_meta(remote, keyword) {
+                if !items.contains(&idx) {
+                    items.push(idx);
+                }
+            }
+        }
+        match items.len() {
+            0 => bail!(
+                "could not find repository matches {}{}",
+                style(mapped).yellow(),
+                self.suggest_keyword(remote, mapped, km)
             ),
+            1 => return Ok(items[0]),
+            _ => {}
+        }
+        if !select {
+            return Ok(items[0]);
         }
+        let keys: Vec<String> = items
+            .iter()
+            .map(|idx| format!("{}:{}", self.repos[*idx].remote, self.repos[*idx].name))
+            .collect();
+        let mut fzf = util::Fzf::build(&[])?;
+        let idx = items[fzf.query(&keys)?];
+
+        bindings.bind(keyword, &self.repos[idx]);
+        bindings.save()?;
+        Ok(idx)
     }
 
     pub fn update(&mut self, idx: usize, now: Epoch) {
         let mut repo = &mut self.repos[idx];
         repo.last_accessed = now;
         repo.accessed += 1.0;
+        repo.record_access(now);
+        if let Some(session) = Self::session() {
+            repo.session = session;
+        }
+    }
+
+    /// The id of current shell session, it is set by the init script.
+    fn session() -> Option<String> {
+        match env::var("_GZ_SESSION") {
+            Ok(session) if !session.is_empty() => Some(session),
+            _ => None,
+        }
+    }
+
+    /// Move the repos accessed in current shell session to the front, the
+    /// order of the others is kept.
+    pub fn boost_session(&mut self) {
+        if let Some(session) = Self::session() {
+            self.repos.sort_by_key(|repo| repo.session != session);
+        }
     }
 
     pub fn sort(&mut self, now: Epoch) {
-        self.repos.sort_unstable_by(|repo1: &Repo, repo2: &Repo| {
-            repo2.score(now).total_cmp(&repo1.score(now))
+        self.sort_by_rank(now, RankMode::Alltime)
+    }
+
+    /// Sort the repos with the rank mode. The database should be sorted with
+    /// [`Database::sort`] before saving, the keyword matching depends on
+    /// the order of all time. The ties are broken by the all time score and
+    /// then the name, so the order is stable between runs, such as the
+    /// migrated repos without history in recent mode.
+    pub fn sort_by_rank(&mut self, now: Epoch, mode: RankMode) {
+        self.repos.sort_by(|repo1: &Repo, repo2: &Repo| {
+            repo2
+                .rank(now, mode)
+                .total_cmp(&repo1.rank(now, mode))
+                .then_with(|| repo2.score(now).total_cmp(&repo1.score(now)))
+                .then_with(|| repo1.name.cmp(&repo2.name))
+                .then_with(|| repo1.remote.cmp(&repo2.remote))
         })
     }
 
     fn serialize(repos: &[Repo]) -> Result<Vec<u8>> {
-        (|| -> bincode::Result<_> {
-            let buffer_size =
-                bincode::serialized_size(&Self::VERSION)? + bincode::serialized_size(&repos)?;
-            let mut buffer = Vec::with_capacity(buffer_size as usize);
-
-            bincode::serialize_into(&mut buffer, &Self::VERSION)?;
-            bincode::serialize_into(&mut buffer, &repos)?;
-
-            Ok(buffer)
-        })()
-        .context("could not serialize database")
+        store::encode(Self::VERSION, repos).context("could not serialize database")
     }
 
     fn deserialize(bytes: &[u8]) -> Result<Vec<Repo>> {
-        // Assume a maximum size for the database. This prevents
-        // bincode from throwing strange errors when it encounters
-        // invalid data.
-        const MAX_SIZE: u64 = 32 << 10; // 32 MiB
-
-        let deserializer = &mut bincode::options()
-            .with_fixint_encoding()
-            .with_limit(MAX_SIZE);
-
-        let version_size = deserializer.serialized_size(&Self::VERSION).unwrap() as _;
-        if bytes.len() < version_size {
-            bail!("could not deserialize database: corrupted data");
-        }
-        let (bytes_version, bytes_repos) = bytes.split_at(version_size);
-        let version = deserializer.deserialize(bytes_version)?;
-
+        let (version, bytes_repos) =
+            store::split_version(bytes).context("could not deserialize database")?;
         let repos = match version {
-            Self::VERSION => deserializer
-                .deserialize(bytes_repos)
-                .context("could not deserialize repo data")?,
+            Self::VERSION => {
+                store::decode(bytes_repos).context("could not deserialize repo data")?
+            }
+            1 => {
+                let repos: Vec<legacy::RepoV1> =
+                    store::decode(bytes_repos).context("could not deserialize repo data")?;
+                repos.into_iter().map(Repo::from).collect()
+            }
             version => bail!("unsupported version {version}, supports: {}", Self::VERSION),
         };
 
@@ -211,43 +656,15 @@ impl Database {
     }
 }
 
-pub struct Keywords {
-    path: PathBuf,
-    pub data: HashMap<String, Epoch>,
-}
+/// The keywords used recently, in format `keyword -> expire`.
+pub type Keywords = Store<HashMap<String, Epoch>>;
 
 impl Keywords {
-    const VERSION: u32 = 1;
-
+    /// Open the keywords, the expired keywords are dropped.
     pub fn open(now: Epoch) -> Result<Keywords> {
-        let data_dir = config::Config::get_data_dir()?;
-        let path = data_dir.join("keywords");
-
-        match fs::read(&path) {
-            Ok(bytes) => Ok(Keywords {
-                path,
-                data: Self::deserialize(&bytes, now)?,
-            }),
-            Err(err) if err.kind() == io::ErrorKind::NotFound => {
-                fs::create_dir_all(&data_dir).with_context(|| {
-                    format!("unable to create data directory: {}", data_dir.display())
-                })?;
-                Ok(Keywords {
-                    path,
-                    data: HashMap::new(),
-                })
-            }
-            Err(err) => Err(err).context("could not open keywords file"),
-        }
-    }
-
-    pub fn save(&mut self) -> Result<()> {
-        let bytes = Self::serialize(&self.data)?;
-        if let Err(err) = util::write(&self.path, bytes) {
-            return Err(err).context("could not write keywords file");
-        }
-
-        Ok(())
+        let mut keywords: Keywords = Store::load("keywords", 1)?;
+        keywords.data.retain(|_, expire| *expire >= now);
+        Ok(keywords)
     }
 
     pub fn list(&self) -> Vec<&str> {
@@ -259,47 +676,381 @@ impl Keywords {
     pub fn add(&mut self, keyword: &str, now: Epoch) {
         self.data.insert(keyword.to_string(), now + util::DAY);
     }
+}
 
-    fn serialize(data: &HashMap<String, Epoch>) -> Result<Vec<u8>> {
-        (|| -> bincode::Result<_> {
-            let buffer_size =
-                bincode::serialized_size(&Self::VERSION)? + bincode::serialized_size(&data)?;
-            let mut buffer = Vec::with_capacity(buffer_size as usize);
+/// The keyword bindings selected by user.
+#[derive(Default, Deserialize, Serialize)]
+pub struct BindingData {
+    /// The bound repo `(remote, name)` of each keyword.
+    pub repos: HashMap<String, (String, String)>,
 
-            bincode::serialize_into(&mut buffer, &Self::VERSION)?;
-            bincode::serialize_into(&mut buffer, &data)?;
+    /// How many times each bound keyword has been used.
+    pub hits: HashMap<String, u32>,
+}
 
-            Ok(buffer)
-        })()
-        .context("could not serialize database")
+/// The keyword bindings selected by user, a bound keyword jumps to the repo
+/// directly, which takes precedence over the keyword matching.
+pub type Bindings = Store<BindingData>;
+
+impl Bindings {
+    pub fn open() -> Result<Bindings> {
+        Store::load("bindings", 1)
     }
 
-    fn deserialize(bytes: &[u8], now: Epoch) -> Result<HashMap<String, Epoch>> {
-        const MAX_SIZE: u64 = 32 << 10; // 32 MiB
+    pub fn bind(&mut self, keyword: &str, repo: &Repo) {
+        self.data.repos.insert(
+            keyword.to_string(),
+            (repo.remote.clone(), repo.name.clone()),
+        );
+        // Binding by selection is the first use of the keyword.
+        self.data.hits.insert(keyword.to_string(), 1);
+    }
 
-        let deserializer = &mut bincode::options()
-            .with_fixint_encoding()
-            .with_limit(MAX_SIZE);
+    pub fn unbind(&mut self, keyword: &str) -> Option<(String, String)> {
+        self.data.hits.remove(keyword);
+        self.data.repos.remove(keyword)
+    }
 
-        let version_size = deserializer.serialized_size(&Self::VERSION).unwrap() as _;
-        if bytes.len() < version_size {
-            bail!("could not deserialize database: corrupted data");
+    pub fn hit(&mut self, keyword: &str) {
+        *self.data.hits.entry(keyword.to_string()).or_default() += 1;
+    }
+
+    /// Find the repo bound to the keyword, the `remote` filters the repo if
+    /// it is not empty.
+    pub fn resolve(&self, db: &Database, remote: &str, keyword: &str) -> Option<usize> {
+        let (bound_remote, name) = self.data.repos.get(keyword)?;
+        if !remote.is_empty() && remote != bound_remote {
+            return None;
         }
-        let (bytes_version, bytes_data) = bytes.split_at(version_size);
-        let version = deserializer.deserialize(bytes_version)?;
+        db.get(bound_remote, name)
+    }
+}
 
-        let data: HashMap<String, Epoch> = match version {
-            Self::VERSION => deserializer
-                .deserialize(bytes_data)
-                .context("could not deserialize repo data")?,
-            version => bail!("unsupported version {version}, supports: {}", Self::VERSION),
+/// The cached branch and dirty state of a repo.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RepoStatus {
+    pub branch: String,
+    pub dirty: bool,
+
+    pub updated: Epoch,
+}
+
+/// The status of repos cached when jumping, so that listing repos does not
+/// need to invoke git for every repo. The key is `(remote, name)`.
+pub type StatusCache = Store<HashMap<(String, String), RepoStatus>>;
+
+impl StatusCache {
+    pub fn open() -> Result<StatusCache> {
+        Store::load("status", 1)
+    }
+
+    pub fn get(&self, repo: &Repo) -> Option<&RepoStatus> {
+        self.data.get(&(repo.remote.clone(), repo.name.clone()))
+    }
+
+    /// Refresh the status of the repo, the repo not cloned is removed from
+    /// the cache.
+    pub fn refresh(&mut self, repo: &Repo, workspace: &str, now: Epoch) -> Result<()> {
+        let key = (repo.remote.clone(), repo.name.clone());
+        let path = repo.path(workspace)?;
+        if !path.exists() {
+            self.data.remove(&key);
+            return Ok(());
+        }
+        let reader = git::reader(Some(util::path_to_str(&path)?), true);
+        let status = RepoStatus {
+            branch: reader.current_branch().unwrap_or_default(),
+            dirty: reader.count_changes().unwrap_or_default() > 0,
+            updated: now,
         };
+        self.data.insert(key, status);
+        Ok(())
+    }
 
-        let data: HashMap<String, Epoch> = data
-            .iter()
-            .filter(|(_, expire)| expire >= &&now)
-            .map(|(key, expire)| (key.to_string(), *expire))
-            .collect();
-        Ok(data)
+    /// Refresh the status of the repo to jump and the repo to leave (the
+    /// current repo), if the cache is enabled.
+    pub fn refresh_on_jump(
+        db: &Database,
+        cfg: &config::Config,
+        idx: usize,
+        now: Epoch,
+    ) -> Result<()> {
+        if !cfg.status_cache {
+            return Ok(());
+        }
+        let mut cache = Self::open()?;
+        if let Ok(current) = db.current_index(cfg) {
+            if current != idx {
+                cache.refresh(&db.repos[current], &cfg.workspace, now)?;
+            }
+        }
+        cache.refresh(&db.repos[idx], &cfg.workspace, now)?;
+        cache.save()
+    }
+}
+
+/// A response of provider cached with its validators.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HttpEntry {
+    pub etag: String,
+    pub last_modified: String,
+    pub body: String,
+
+    pub updated: Epoch,
+}
+
+/// The responses of provider api, the cached response is validated by a
+/// conditional request (`If-None-Match` or `If-Modified-Since`), and served
+/// if the provider replies "not modified". The key is the request url.
+pub type HttpCache = Store<HashMap<String, HttpEntry>>;
+
+impl HttpCache {
+    /// The entries not used in this duration are dropped, to keep the cache
+    /// small.
+    const EXPIRE: Epoch = 7 * util::DAY;
+
+    pub fn open() -> Result<HttpCache> {
+        let now = util::current_time()?;
+        let mut cache: HttpCache = Store::load("http", 1)?;
+        cache
+            .data
+            .retain(|_, entry| now.saturating_sub(entry.updated) < Self::EXPIRE);
+        Ok(cache)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ActivityKind {
+    Commit,
+    Release,
+}
+
+/// An event happened in a repo, fetched from provider.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ActivityEvent {
+    pub kind: ActivityKind,
+
+    /// The short commit id, or the tag name of release.
+    pub id: String,
+    pub title: String,
+    pub author: String,
+    pub time: Epoch,
+}
+
+/// The activities of a repo fetched since `since`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RepoActivity {
+    pub since: Epoch,
+    pub events: Vec<ActivityEvent>,
+
+    pub updated: Epoch,
+}
+
+/// The activities of repos fetched from provider, so that showing activities
+/// repeatedly does not flood the provider. The key is `(remote, name)`.
+pub type ActivityCache = Store<HashMap<(String, String), RepoActivity>>;
+
+impl ActivityCache {
+    /// The cached activities older than this will be fetched again.
+    const EXPIRE: Epoch = util::HOUR;
+
+    pub fn open() -> Result<ActivityCache> {
+        Store::load("activity", 1)
+    }
+
+    /// Get the cached activities of the repo since `since`, return `None` if
+    /// the cache is expired or does not cover `since`.
+    pub fn get(&self, repo: &Repo, since: Epoch, now: Epoch) -> Option<Vec<&ActivityEvent>> {
+        let cached = self.data.get(&(repo.remote.clone(), repo.name.clone()))?;
+        if now.saturating_sub(cached.updated) >= Self::EXPIRE || cached.since > since {
+            return None;
+        }
+        Some(
+            cached
+                .events
+                .iter()
+                .filter(|event| event.time >= since)
+                .collect(),
+        )
+    }
+
+    pub fn put(&mut self, repo: &Repo, since: Epoch, events: Vec<ActivityEvent>, now: Epoch) {
+        self.data.insert(
+            (repo.remote.clone(), repo.name.clone()),
+            RepoActivity {
+                since,
+                events,
+                updated: now,
+            },
+        );
+    }
+}
+
+/// The trust decisions of the repo-local config files, the key is the path
+/// of file, the value is the sha256 of its content and whether it is
+/// trusted. The decision is dropped once the content changes.
+pub type TrustStore = Store<HashMap<String, (String, bool)>>;
+
+impl TrustStore {
+    pub fn open() -> Result<TrustStore> {
+        Store::load("trust", 1)
+    }
+
+    /// Get the decision for the file, `None` if it is never decided or its
+    /// content has changed since then.
+    pub fn get(&self, file: &str, hash: &str) -> Option<bool> {
+        match self.data.get(file) {
+            Some((decided_hash, trusted)) if decided_hash == hash => Some(*trusted),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, file: &str, hash: &str, trusted: bool) {
+        self.data
+            .insert(file.to_string(), (hash.to_string(), trusted));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: Epoch = 100 * util::DAY;
+
+    fn database() -> Database {
+        let mut db = Database::memory();
+        for (remote, name, accessed) in [
+            ("gh", "fioncat/git-zoxide", 10.0),
+            ("gh", "fioncat/dotfiles", 30.0),
+            ("gl", "team/git-zoxide", 20.0),
+            ("gl", "team/api", 0.0),
+        ] {
+            let idx = db.add(remote, name, "");
+            db.repos[idx].accessed = accessed;
+            db.repos[idx].last_accessed = NOW;
+        }
+        db
+    }
+
+    fn names(db: &Database) -> Vec<&str> {
+        db.repos.iter().map(|repo| repo.name.as_str()).collect()
+    }
+
+    #[test]
+    fn add() {
+        let mut db = database();
+        let idx = db.add("gh", "fioncat/new", "/tmp/new");
+        assert_eq!(idx, 4);
+        assert_eq!(db.get("gh", "fioncat/new"), Some(idx));
+        assert_eq!(db.get("gl", "fioncat/new"), None);
+        assert_eq!(db.repos[idx].path, "/tmp/new");
+        assert_eq!(db.filter("gh", "").len(), 3);
+        assert_eq!(db.filter("gh", "fioncat").len(), 3);
+        assert_eq!(db.filter("", "team").len(), 2);
+    }
+
+    #[test]
+    fn match_keyword() {
+        let mut db = database();
+        db.sort(NOW);
+        let mut km = HashMap::new();
+        km.insert(String::from("dot"), String::from("dotfiles"));
+
+        // The repo ranked first wins among the same base names.
+        let idx = db.match_keyword("", "git-zoxide", &km).unwrap();
+        assert_eq!(db.repos[idx].remote, "gl");
+        let idx = db.match_keyword("gh", "git-zoxide", &km).unwrap();
+        assert_eq!(db.repos[idx].name, "fioncat/git-zoxide");
+
+        let idx = db.match_keyword("", "dot", &km).unwrap();
+        assert_eq!(db.repos[idx].name, "fioncat/dotfiles");
+        assert!(db.match_keyword("", "missing", &km).is_err());
+    }
+
+    #[test]
+    fn sort() {
+        let mut db = database();
+        db.sort(NOW);
+        assert_eq!(
+            names(&db),
+            [
+                "fioncat/dotfiles",
+                "team/git-zoxide",
+                "fioncat/git-zoxide",
+                "team/api"
+            ]
+        );
+
+        // Without history, all repos tie in recent mode, the order falls back
+        // to the all time score and then the name.
+        db.repos.reverse();
+        db.sort_by_rank(NOW, RankMode::Recent);
+        assert_eq!(
+            names(&db),
+            [
+                "fioncat/dotfiles",
+                "team/git-zoxide",
+                "fioncat/git-zoxide",
+                "team/api"
+            ]
+        );
+
+        let idx = db.get("gl", "team/api").unwrap();
+        db.repos[idx].record_access(NOW);
+        db.sort_by_rank(NOW, RankMode::Recent);
+        assert_eq!(names(&db)[0], "team/api");
+    }
+
+    #[test]
+    fn open_at() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("data").join("database");
+        let mut db = Database::open_at(path.clone()).unwrap();
+        assert!(db.repos.is_empty());
+        db.repos = database().repos;
+        db.save().unwrap();
+
+        let db = Database::open_at(path).unwrap();
+        assert_eq!(
+            names(&db),
+            [
+                "fioncat/git-zoxide",
+                "fioncat/dotfiles",
+                "team/git-zoxide",
+                "team/api"
+            ]
+        );
+    }
+
+    #[test]
+    fn memory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        env::set_var("_GZ_DATA_PATH", dir.path());
+        env::set_var("_GZ_DATABASE_PATH", Database::MEMORY_PATH);
+
+        let mut db = Database::open().unwrap();
+        db.add("gh", "fioncat/git-zoxide", "");
+        db.save().unwrap();
+        let mut keywords = Keywords::open(NOW).unwrap();
+        keywords.add("zoxide", NOW);
+        keywords.save().unwrap();
+        let mut trust = TrustStore::open().unwrap();
+        trust.set("/tmp/repo/.gz.yaml", "hash", true);
+        trust.save().unwrap();
+        HistoryEntry::new("add")
+            .repo("gh", "fioncat/git-zoxide")
+            .record();
+
+        // Nothing is written, and the data is empty when opened again.
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+        assert!(Database::open().unwrap().repos.is_empty());
+        assert!(Keywords::open(NOW).unwrap().list().is_empty());
+        assert_eq!(
+            TrustStore::open()
+                .unwrap()
+                .get("/tmp/repo/.gz.yaml", "hash"),
+            None
+        );
+        assert!(History::read().unwrap().is_empty());
     }
 }
@@ -1,12 +1,19 @@
+use std::env;
 use std::fs;
-use std::io;
-use std::{path::PathBuf, str::FromStr};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use console::style;
 
 use serde::{Deserialize, Serialize};
 
-use crate::config::User;
+use crate::api;
+use crate::config::{Config, RankMode, SigningFormat};
+use crate::db::HistoryEntry;
 use crate::{
     config::{Clone, Remote},
     util::{self, Shell, DAY, HOUR, WEEK},
@@ -23,9 +30,59 @@ pub struct Repo {
 
     pub last_accessed: Epoch,
     pub accessed: Rank,
+
+    pub use_ssh: Option<bool>,
+
+    /// The access counts per day in the recent 90 days, in format
+    /// `(day, count)`, the day is the epoch of the start of the day.
+    pub history: Vec<(Epoch, u32)>,
+
+    /// The shell session that accessed the repo last time.
+    pub session: String,
+
+    /// The metadata fetched from provider, `None` means never fetched.
+    pub meta: Option<RepoMeta>,
+}
+
+/// The repo metadata in provider, see `repo_meta` in config.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct RepoMeta {
+    pub description: String,
+    pub stars: u64,
+    pub archived: bool,
+    pub topics: Vec<String>,
+
+    /// When the metadata was fetched.
+    pub updated: Epoch,
+}
+
+impl RepoMeta {
+    /// The one-line summary, in format `★<stars> [archived] <description>`.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("★{}", self.stars)];
+        if self.archived {
+            parts.push(String::from("[archived]"));
+        }
+        if !self.description.is_empty() {
+            parts.push(self.description.clone());
+        }
+        parts.join(" ")
+    }
 }
 
 impl Repo {
+    const HISTORY_DAYS: Epoch = 90;
+
+    /// The metadata older than this will be refreshed lazily.
+    const META_EXPIRE: Epoch = 7 * DAY;
+
+    /// The git config set to repo while cloning, the repo still having it
+    /// is a partial clone left by a failed or interrupted clone.
+    const CLONING_KEY: &str = "gz.cloning";
+
+    /// The max delay between clone retries, in seconds.
+    const MAX_RETRY_DELAY: u64 = 30;
+
     pub fn score(&self, now: Epoch) -> Rank {
         let duration = now.saturating_sub(self.last_accessed);
         if duration < HOUR {
@@ -39,6 +96,47 @@ impl Repo {
         }
     }
 
+    /// The score used to rank repos. In recent mode, the accesses in the
+    /// last 7, 30 and 90 days are weighted 4, 2 and 1.
+    pub fn rank(&self, now: Epoch, mode: RankMode) -> Rank {
+        match mode {
+            RankMode::Alltime => self.score(now),
+            RankMode::Recent => {
+                let week = self.accessed_within(now, WEEK);
+                let month = self.accessed_within(now, 30 * DAY);
+                let season = self.accessed_within(now, Self::HISTORY_DAYS * DAY);
+                (week * 4 + (month - week) * 2 + (season - month)) as Rank
+            }
+        }
+    }
+
+    /// The access count in the window before now.
+    pub fn accessed_within(&self, now: Epoch, window: Epoch) -> u32 {
+        let since = now.saturating_sub(window) / DAY * DAY;
+        self.history
+            .iter()
+            .filter(|(day, _)| *day >= since)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    pub fn meta_expired(&self, now: Epoch) -> bool {
+        match &self.meta {
+            Some(meta) => now.saturating_sub(meta.updated) >= Self::META_EXPIRE,
+            None => true,
+        }
+    }
+
+    pub fn record_access(&mut self, now: Epoch) {
+        let today = now / DAY * DAY;
+        match self.history.last_mut() {
+            Some((day, count)) if *day == today => *count += 1,
+            _ => self.history.push((today, 1)),
+        }
+        let expire = today.saturating_sub(Self::HISTORY_DAYS * DAY);
+        self.history.retain(|(day, _)| *day >= expire);
+    }
+
     pub fn path<S>(&self, workspace: S) -> Result<PathBuf>
     where
         S: AsRef<str>,
@@ -60,13 +158,33 @@ impl Repo {
         }
     }
 
+    /// The path of the repo archived in cold storage, the layout is the same
+    /// as workspace. Return `None` if cold storage is disabled or the repo
+    /// is attached to a custom path, which is never archived.
+    pub fn cold_path(&self, cold_storage: &str) -> Option<PathBuf> {
+        if cold_storage.is_empty() || !self.path.is_empty() {
+            return None;
+        }
+        Some(
+            PathBuf::from(cold_storage)
+                .join(&self.remote)
+                .join(&self.name),
+        )
+    }
+
     pub fn ensure_path(&self, workspace: impl AsRef<str>, remote: &Remote) -> Result<PathBuf> {
         let path = self.path(workspace.as_ref())?;
         match fs::read_dir(&path) {
-            Ok(_) => Ok(path),
+            Ok(_) => match &remote.clone {
+                Some(clone) if Self::is_partial(util::path_to_str(&path)?) => {
+                    self.ensure_clone(remote, clone, &path)?;
+                    Ok(path)
+                }
+                _ => Ok(path),
+            },
             Err(err) if err.kind() == io::ErrorKind::NotFound => match &remote.clone {
                 Some(clone) => {
-                    self.ensure_clone(clone, &path, &remote.user)?;
+                    self.ensure_clone(remote, clone, &path)?;
                     Ok(path)
                 }
                 None => {
@@ -79,15 +197,28 @@ impl Repo {
         }
     }
 
-    fn ensure_clone(&self, clone: &Clone, path: &PathBuf, user: &Option<User>) -> Result<()> {
+    fn ensure_clone(&self, remote: &Remote, clone: &Clone, path: &PathBuf) -> Result<()> {
         let url = self.clone_url(clone);
 
+        self.check_space(remote, clone, path)?;
         let path = util::path_to_str(path)?;
 
-        let mut git = Shell::git();
-        git.arg("clone").args([url.as_str(), path]).exec()?;
+        let reference = self.ensure_reference(clone, &url)?;
+        if self.use_mirror(clone) {
+            self.ensure_mirror_worktree(clone, &url, path, &reference)?;
+        } else {
+            Self::clone_retry(clone, &url, path, &reference)?;
+        }
+        self.install_ssh_key(clone, path)?;
 
-        if let Some(user) = user {
+        if clone.maintenance {
+            Shell::git()
+                .with_git_path(path)
+                .args(["maintenance", "start"])
+                .exec()?;
+        }
+
+        if let Some(user) = &remote.user {
             Shell::git()
                 .with_git_path(path)
                 .args(["config", "user.name"])
@@ -99,7 +230,463 @@ impl Repo {
                 .arg(&user.email)
                 .exec()?;
         }
+        self.install_commit_template(remote, path)?;
+        self.install_signing(remote, path)?;
+        // The worktrees share branches with mirror, the branch of mirror
+        // HEAD is checked out already. The repo is usable on the branch
+        // cloned, so a failure only prints warning.
+        if clone.checkout_default && !self.use_mirror(clone) {
+            if let Err(err) = self.checkout_default(remote, path) {
+                _ = writeln!(
+                    io::stderr(),
+                    "{} unable to checkout default branch: {:#}",
+                    util::Palette::get().warning.apply_to("warning"),
+                    err
+                );
+            }
+        }
+        Self::create_clone_branch(clone, path)?;
+        self.install_envrc(remote, path)?;
+
+        HistoryEntry::new("clone")
+            .repo(&remote.name, &self.name)
+            .path(path)
+            .detail(&url)
+            .record();
+        Ok(())
+    }
+
+    /// Check the free space of workspace filesystem before cloning, so that
+    /// a large clone does not fail halfway.
+    fn check_space(&self, remote: &Remote, clone: &Clone, path: &Path) -> Result<()> {
+        if clone.min_free_space.is_empty() {
+            return Ok(());
+        }
+        let mut required = util::parse_bytes(&clone.min_free_space)?;
+        if remote.api.is_some() {
+            // The size is an estimate, the clone goes on if the provider is
+            // unavailable.
+            let size = api::create_provider(remote).and_then(|provider| {
+                util::print_operation(format!(
+                    "provider: get size for {}",
+                    style(&self.name).yellow()
+                ));
+                provider.get_size(&self.name)
+            });
+            required += size.unwrap_or_default();
+        }
+        let free = util::free_space(path)?;
+        if free >= required {
+            return Ok(());
+        }
+        let msg = format!(
+            "not enough disk space to clone {}, {} free, {} required",
+            style(&self.name).yellow(),
+            util::human_bytes(free),
+            util::human_bytes(required)
+        );
+        if clone.abort_on_low_space {
+            bail!(msg)
+        }
+        _ = writeln!(
+            io::stderr(),
+            "{} {}",
+            util::Palette::get().warning.apply_to("warning"),
+            msg
+        );
+        Ok(())
+    }
+
+    /// Install the commit signing configured in remote to the repo.
+    pub fn install_signing(&self, remote: &Remote, path: &str) -> Result<()> {
+        let signing = match &remote.signing {
+            Some(signing) => signing,
+            None => return Ok(()),
+        };
+        let format = match signing.format {
+            SigningFormat::Openpgp => "openpgp",
+            SigningFormat::Ssh => "ssh",
+            SigningFormat::X509 => "x509",
+        };
+        Shell::git()
+            .with_git_path(path)
+            .args(["config", "gpg.format", format])
+            .exec()?;
+        Shell::git()
+            .with_git_path(path)
+            .args(["config", "user.signingkey"])
+            .arg(&signing.key)
+            .exec()?;
+        Shell::git()
+            .with_git_path(path)
+            .args(["config", "commit.gpgsign", "true"])
+            .exec()?;
+        Ok(())
+    }
+
+    /// Install the `ssh_key` configured in remote to the repo, so the
+    /// commands running in the repo use the key.
+    pub fn install_ssh_key(&self, clone: &Clone, path: &str) -> Result<()> {
+        if let Some(ssh_command) = clone.ssh_command() {
+            Shell::git()
+                .with_git_path(path)
+                .args(["config", "core.sshCommand", ssh_command.as_str()])
+                .exec()?;
+        }
+        Ok(())
+    }
 
+    /// Clone the repo, retry with backoff if failed. The partial clone left
+    /// by a failed or interrupted clone is resumed by fetching into it, or
+    /// removed to clone again if it could not be resumed.
+    fn clone_retry(clone: &Clone, url: &str, path: &str, reference: &[String]) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            // Nothing to resume in an empty directory, clone into it.
+            let resume = Self::is_partial(path) && Path::new(path).join(".git").exists();
+            let result = if resume {
+                Self::resume_clone(clone, path)
+            } else {
+                let cloning = format!("{}=true", Self::CLONING_KEY);
+                Self::clone_git(clone)
+                    .args(["clone", "-c", cloning.as_str()])
+                    .args(reference)
+                    .args([url, path])
+                    .exec()
+                    .map(|_| ())
+            };
+            if let Err(err) = result {
+                if attempt >= clone.retry {
+                    return Err(err);
+                }
+            } else {
+                break;
+            }
+            attempt += 1;
+            // The partial clone that could not be resumed is useless, but
+            // the one left by this clone can be resumed in next attempt.
+            if resume && Path::new(path).exists() {
+                fs::remove_dir_all(path)
+                    .with_context(|| format!("could not remove partial clone {}", path))?;
+            }
+            let delay = (1 << attempt).min(Self::MAX_RETRY_DELAY);
+            // The error of git has been printed to stderr.
+            _ = writeln!(
+                io::stderr(),
+                "{} clone failed, retry in {}s ({}/{})",
+                util::Palette::get().warning.apply_to("warning"),
+                delay,
+                attempt,
+                clone.retry
+            );
+            thread::sleep(Duration::from_secs(delay));
+        }
+        Shell::git()
+            .mute()
+            .with_git_path(path)
+            .args(["config", "--unset", Self::CLONING_KEY])
+            .exec()?;
+        Ok(())
+    }
+
+    /// Fetch into the partial clone, and check out the default branch.
+    fn resume_clone(clone: &Clone, path: &str) -> Result<()> {
+        Self::clone_git(clone)
+            .with_git_path(path)
+            .args(["fetch", "origin"])
+            .exec()?;
+        Shell::git()
+            .with_git_path(path)
+            .args(["remote", "set-head", "origin", "--auto"])
+            .exec()?;
+        let head = Shell::git()
+            .mute()
+            .with_git_path(path)
+            .args(["symbolic-ref", "--short", "refs/remotes/origin/HEAD"])
+            .exec()?;
+        let branch = head.strip_prefix("origin/").unwrap_or(&head);
+        Self::checkout_remote_branch(path, branch)
+    }
+
+    /// Check out the default branch reported by provider if the HEAD of
+    /// remote points to another branch. Without provider, the HEAD of remote
+    /// is the best we know, which is checked out by clone already.
+    fn checkout_default(&self, remote: &Remote, path: &str) -> Result<()> {
+        if remote.api.is_none() {
+            return Ok(());
+        }
+        util::print_operation(format!(
+            "provider: get default branch for {}",
+            style(&self.name).yellow()
+        ));
+        let branch = api::create_provider(remote)?.get_default_branch(&self.name)?;
+        let current = Shell::git()
+            .mute()
+            .with_git_path(path)
+            .args(["symbolic-ref", "--short", "HEAD"])
+            .exec()?;
+        if current == branch {
+            return Ok(());
+        }
+        Self::checkout_remote_branch(path, &branch)
+    }
+
+    /// Check out the remote-tracking branch of origin, the local branch is
+    /// created or reset to it.
+    fn checkout_remote_branch(path: &str, branch: &str) -> Result<()> {
+        let upstream = format!("origin/{}", branch);
+        Shell::git()
+            .with_git_path(path)
+            .args(["checkout", "-B", branch, "--track", upstream.as_str()])
+            .exec()?;
+        Ok(())
+    }
+
+    /// Create and check out the working branch `on_clone_branch`.
+    fn create_clone_branch(clone: &Clone, path: &str) -> Result<()> {
+        if clone.on_clone_branch.is_empty() {
+            return Ok(());
+        }
+        let user = env::var("USER").unwrap_or_default();
+        if user.is_empty() && clone.on_clone_branch.contains("{user}") {
+            bail!("could not get current user for on_clone_branch, please set env USER")
+        }
+        let branch = util::render_template(&clone.on_clone_branch, &[("user", user.as_str())]);
+        Shell::git()
+            .with_git_path(path)
+            .args(["checkout", "-b", branch.as_str()])
+            .exec()?;
+        Ok(())
+    }
+
+    /// Whether the clone is not finished, the directory is empty (the clone
+    /// was interrupted before creating ".git"), or the cloning marker is
+    /// still in the git config.
+    fn is_partial(path: &str) -> bool {
+        let path = Path::new(path);
+        if let Ok(mut entries) = fs::read_dir(path) {
+            if entries.next().is_none() {
+                return true;
+            }
+        }
+        // Read the config directly, this is checked on every jump, spawning
+        // git is too expensive.
+        let config = match fs::read_to_string(path.join(".git").join("config")) {
+            Ok(config) => config,
+            Err(_) => return false,
+        };
+        let (section, key) = Self::CLONING_KEY.split_once('.').unwrap();
+        let mut in_section = false;
+        for line in config.lines() {
+            let line = line.trim();
+            if let Some(header) = line.strip_prefix('[') {
+                in_section = header
+                    .trim_end_matches(']')
+                    .trim()
+                    .eq_ignore_ascii_case(section);
+                continue;
+            }
+            if in_section {
+                if let Some((name, _)) = line.split_once('=') {
+                    if name.trim().eq_ignore_ascii_case(key) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// The git command talking to the remote before the repo is configured,
+    /// it uses the `ssh_key` of remote.
+    fn clone_git(clone: &Clone) -> Shell {
+        let mut git = Shell::git();
+        if let Some(ssh_command) = clone.ssh_command() {
+            git.env("GIT_SSH_COMMAND", ssh_command);
+        }
+        git
+    }
+
+    /// Install the commit template configured in remote to the repo.
+    pub fn install_commit_template(&self, remote: &Remote, path: &str) -> Result<()> {
+        if let Some(template) = remote.get_commit_template(&self.name) {
+            Shell::git()
+                .with_git_path(path)
+                .args(["config", "commit.template", template])
+                .exec()?;
+        }
+        Ok(())
+    }
+
+    /// Write the `.envrc` rendered from the template configured in remote to
+    /// the repo, and allow it if direnv is installed. The `.envrc` already
+    /// in the repo is kept.
+    pub fn install_envrc(&self, remote: &Remote, path: &str) -> Result<()> {
+        let template = match remote.get_envrc(&self.name) {
+            Some(template) => template,
+            None => return Ok(()),
+        };
+        let envrc = PathBuf::from(path).join(".envrc");
+        if envrc.exists() {
+            return Ok(());
+        }
+        let template = fs::read_to_string(template).with_context(|| {
+            format!("unable to read envrc template {}", style(template).yellow())
+        })?;
+        let (group, base) = util::split_name(&self.name);
+        let content = util::render_template(
+            template,
+            &[
+                ("remote", remote.name.as_str()),
+                ("name", self.name.as_str()),
+                ("group", group.as_str()),
+                ("base", base.as_str()),
+                ("path", path),
+            ],
+        );
+        util::print_operation(format!("create {} ...", style(".envrc").yellow()));
+        fs::write(&envrc, content).context("unable to write .envrc")?;
+        if Shell::cmd_exists("direnv") {
+            Shell::new("direnv").args(["allow", path]).exec()?;
+        }
+        Ok(())
+    }
+
+    /// Fetch objects of the repo into the reference cache, and return the
+    /// arguments for `git clone` to borrow objects from it. The cache is a
+    /// bare repo that has a remote for every cloned repo, so forks of the
+    /// same project share their objects.
+    fn ensure_reference(&self, clone: &Clone, url: &str) -> Result<Vec<String>> {
+        if clone.reference_cache.is_empty() {
+            return Ok(vec![]);
+        }
+        let cache = clone.reference_cache.as_str();
+        match fs::read_dir(cache) {
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Shell::git().args(["init", "--bare", cache]).exec()?;
+            }
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("could not read reference cache {}", cache))
+            }
+        }
+
+        let remote = format!("{}/{}", self.remote, self.name);
+        let remotes = Shell::git().with_git_path(cache).arg("remote").exec()?;
+        if !remotes.lines().any(|line| line == remote) {
+            Shell::git()
+                .with_git_path(cache)
+                .args(["remote", "add", remote.as_str(), url])
+                .exec()?;
+        }
+        Self::clone_git(clone)
+            .with_git_path(cache)
+            .args(["fetch", remote.as_str()])
+            .exec()?;
+
+        Ok(vec![String::from("--reference-if-able"), cache.to_string()])
+    }
+
+    fn use_mirror(&self, clone: &Clone) -> bool {
+        clone.mirror
+            || clone
+                .mirror_repos
+                .iter()
+                .any(|pattern| util::glob_match(pattern, &self.name))
+    }
+
+    /// The path of the bare mirror used by the mirror clone mode.
+    pub fn mirror_path(&self) -> Result<PathBuf> {
+        Ok(Config::get_data_dir()?
+            .join("mirrors")
+            .join(&self.remote)
+            .join(format!("{}.git", self.name)))
+    }
+
+    /// In mirror clone mode, the repo is cloned as a bare repo into the data
+    /// directory, and the workspace directory is a linked worktree of it. So
+    /// the objects are shared and the workspace can be removed cheaply.
+    fn ensure_mirror_worktree(
+        &self,
+        clone: &Clone,
+        url: &str,
+        path: &str,
+        reference: &[String],
+    ) -> Result<()> {
+        let mirror = self.mirror_path()?;
+        let mirror = util::path_to_str(&mirror)?;
+        match fs::read_dir(mirror) {
+            Ok(_) => {
+                Self::clone_git(clone)
+                    .with_git_path(mirror)
+                    .args(["fetch", "origin", "--prune"])
+                    .exec()?;
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Self::clone_git(clone)
+                    .args(["clone", "--bare"])
+                    .args(reference)
+                    .args([url, mirror])
+                    .exec()?;
+                // The bare clone does not have remote-tracking branches, which
+                // are needed by the worktrees to track upstream.
+                Shell::git()
+                    .with_git_path(mirror)
+                    .args([
+                        "config",
+                        "remote.origin.fetch",
+                        "+refs/heads/*:refs/remotes/origin/*",
+                    ])
+                    .exec()?;
+                Self::clone_git(clone)
+                    .with_git_path(mirror)
+                    .args(["fetch", "origin"])
+                    .exec()?;
+            }
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("could not read mirror directory {}", mirror))
+            }
+        }
+
+        let branch = Shell::git()
+            .with_git_path(mirror)
+            .args(["symbolic-ref", "--short", "HEAD"])
+            .exec()?;
+        // The worktree might be removed before, prune it to reuse the branch.
+        Shell::git()
+            .with_git_path(mirror)
+            .args(["worktree", "prune"])
+            .exec()?;
+        // Fetching updates the remote-tracking branches only, the branch of
+        // mirror is stale. Create the worktree from upstream, unless the
+        // branch has commits not pushed, which are left by the removed
+        // worktree.
+        let upstream = format!("origin/{}", branch);
+        let stale = Shell::git()
+            .mute()
+            .with_git_path(mirror)
+            .args([
+                "merge-base",
+                "--is-ancestor",
+                branch.as_str(),
+                upstream.as_str(),
+            ])
+            .exec()
+            .is_ok();
+        let mut git = Shell::git();
+        git.with_git_path(mirror).args(["worktree", "add"]);
+        if stale {
+            git.args(["-B", branch.as_str(), path, upstream.as_str()]);
+        } else {
+            git.args([path, branch.as_str()]);
+        }
+        git.exec()?;
+        Shell::git()
+            .with_git_path(path)
+            .args(["branch", "--set-upstream-to", upstream.as_str()])
+            .exec()?;
         Ok(())
     }
 
@@ -108,12 +695,21 @@ impl Repo {
             format!("unable to create repository directory: {}", path.display())
         })?;
         let path_str = util::path_to_str(path)?;
-        Shell::git().with_git_path(path_str).arg("init").exec()?;
+        let mut git = Shell::git();
+        git.with_git_path(path_str).arg("init");
+        if !remote.default_branch.is_empty() {
+            git.args(["-b", remote.default_branch.as_str()]);
+        }
+        git.exec()?;
+        HistoryEntry::new("create")
+            .repo(&remote.name, &self.name)
+            .path(path_str)
+            .record();
+        self.install_envrc(remote, path_str)?;
         if !remote.on_create.is_empty() {
-            let env = vec![
-                ("REPO_NAME", self.name.as_str()),
-                ("REMOTE", remote.name.as_str()),
-            ];
+            let mut env: Vec<(&str, &str)> = remote.get_env(&self.name).into_iter().collect();
+            env.push(("REPO_NAME", self.name.as_str()));
+            env.push(("REMOTE", remote.name.as_str()));
             for step in &remote.on_create {
                 step.exec(path, &env)?;
             }
@@ -122,14 +718,32 @@ impl Repo {
     }
 
     pub fn clone_url(&self, cfg: &Clone) -> String {
-        let mut ssh = cfg.use_ssh;
-        if !ssh && cfg.ssh_groups != "" {
-            let (group, _) = util::split_name(&self.name);
-            if let Some(_) = cfg.ssh_groups.split(';').find(|s| s == &group) {
-                ssh = true;
-            }
+        if !cfg.url_template.is_empty() {
+            let (group, base) = util::split_name(&self.name);
+            return util::render_template(
+                &cfg.url_template,
+                &[
+                    ("domain", cfg.domain.as_str()),
+                    ("name", self.name.as_str()),
+                    ("group", group.as_str()),
+                    ("base", base.as_str()),
+                ],
+            );
         }
 
+        let ssh = match self.use_ssh {
+            Some(ssh) => ssh,
+            None => {
+                let (group, _) = util::split_name(&self.name);
+                cfg.use_ssh
+                    || cfg
+                        .ssh_groups
+                        .split(';')
+                        .filter(|pattern| !pattern.is_empty())
+                        .any(|pattern| util::glob_match(pattern, &group))
+            }
+        };
+
         if ssh {
             format!("git@{}:{}.git", cfg.domain, self.name)
         } else {
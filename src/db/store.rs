@@ -0,0 +1,115 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::config;
+use crate::db::Database;
+use crate::util;
+
+/// Assume a maximum size for the data files. This prevents bincode from
+/// throwing strange errors when it encounters invalid data.
+const MAX_SIZE: u64 = 32 << 20; // 32 MiB
+
+/// The size of the version header.
+const VERSION_SIZE: usize = 4;
+
+/// A file in the data directory, the data is encoded by bincode after a
+/// version header. The stores are the type aliases of it, such as
+/// [`crate::db::StatusCache`].
+pub struct Store<T> {
+    /// The file to save, `None` means in memory, see [`Database::in_memory`].
+    path: Option<PathBuf>,
+
+    /// The name of the file, used in the error messages too.
+    name: &'static str,
+    version: u32,
+
+    pub data: T,
+}
+
+impl<T> Store<T>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    /// Load the file `name` in the data directory, the data is empty if the
+    /// file does not exist or the data is in memory.
+    pub fn load(name: &'static str, version: u32) -> Result<Store<T>> {
+        if Database::in_memory() {
+            return Ok(Store {
+                path: None,
+                name,
+                version,
+                data: T::default(),
+            });
+        }
+        let data_dir = config::Config::get_data_dir()?;
+        let path = data_dir.join(name);
+
+        let data = match fs::read(&path) {
+            Ok(bytes) => {
+                let (file_version, bytes) = split_version(&bytes)
+                    .with_context(|| format!("could not deserialize {name}"))?;
+                if file_version != version {
+                    bail!("unsupported {name} version {file_version}, supports: {version}")
+                }
+                decode(bytes).with_context(|| format!("could not deserialize {name} data"))?
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                fs::create_dir_all(&data_dir).with_context(|| {
+                    format!("unable to create data directory: {}", data_dir.display())
+                })?;
+                T::default()
+            }
+            Err(err) => return Err(err).with_context(|| format!("could not open {name} file")),
+        };
+
+        Ok(Store {
+            path: Some(path),
+            name,
+            version,
+            data,
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let bytes = encode(self.version, &self.data)
+            .with_context(|| format!("could not serialize {}", self.name))?;
+        util::write(path, bytes).with_context(|| format!("could not write {} file", self.name))
+    }
+}
+
+/// Encode the data after the version header.
+pub fn encode<T: Serialize + ?Sized>(version: u32, data: &T) -> bincode::Result<Vec<u8>> {
+    let buffer_size = bincode::serialized_size(&version)? + bincode::serialized_size(data)?;
+    let mut buffer = Vec::with_capacity(buffer_size as usize);
+
+    bincode::serialize_into(&mut buffer, &version)?;
+    bincode::serialize_into(&mut buffer, data)?;
+
+    Ok(buffer)
+}
+
+/// Split the version header, return the version and the encoded data.
+pub fn split_version(bytes: &[u8]) -> Result<(u32, &[u8])> {
+    if bytes.len() < VERSION_SIZE {
+        bail!("corrupted data");
+    }
+    let (bytes_version, bytes_data) = bytes.split_at(VERSION_SIZE);
+    Ok((decode(bytes_version)?, bytes_data))
+}
+
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> bincode::Result<T> {
+    bincode::options()
+        .with_fixint_encoding()
+        .with_limit(MAX_SIZE)
+        .deserialize(bytes)
+}
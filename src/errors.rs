@@ -14,3 +14,5 @@ impl Display for SilentExit {
 
 pub const REPO_NO_UPSTREAM: &str =
     "this repo is not forked from another repo, so it has no upstream";
+
+pub const REPO_NOT_BOUND: &str = "current path does not bound to any repository";
@@ -0,0 +1,108 @@
+use anyhow::{bail, Context, Result};
+use console::style;
+
+use crate::git::Reader;
+use crate::util::{BranchStatus, GitBranch, GitTag, Shell};
+
+/// The reader running git command.
+pub struct Cli {
+    path: Option<String>,
+    mute: bool,
+}
+
+impl Cli {
+    /// The fields are separated by tab, which is not allowed in ref names:
+    /// branch name, HEAD marker, upstream, upstream track and worktree path.
+    /// The name comes first because the output is trimmed, the trailing
+    /// empty fields might be missing. The name strips `refs/heads/` rather
+    /// than using `short`, which is prefixed with `heads/` when ambiguous.
+    const BRANCH_FORMAT: &str =
+        "%(refname:lstrip=2)%09%(HEAD)%09%(upstream)%09%(upstream:track)%09%(worktreepath)";
+
+    pub fn new(path: Option<&str>, mute: bool) -> Cli {
+        Cli {
+            path: path.map(String::from),
+            mute,
+        }
+    }
+
+    fn git(&self) -> Shell {
+        let mut git = Shell::git();
+        if let Some(path) = &self.path {
+            git.with_git_path(path);
+        }
+        if self.mute {
+            git.mute();
+        }
+        git
+    }
+
+    fn parse_branch(line: &str) -> Result<GitBranch> {
+        let mut fields = line.split('\t');
+        let name = fields.next().unwrap_or_default().trim();
+        if name.is_empty() {
+            bail!(
+                "invalid branch description {}, please check your git command",
+                style(line).yellow()
+            )
+        }
+        let head = fields.next().unwrap_or_default();
+        let upstream = fields.next().unwrap_or_default();
+        let track = fields.next().unwrap_or_default();
+        let worktree = fields.next().unwrap_or_default();
+
+        // The track is like "[ahead 1, behind 2]", "[gone]", or empty when
+        // the branch is in sync with upstream.
+        let status = if upstream.is_empty() {
+            BranchStatus::Detached
+        } else if track.contains("gone") {
+            BranchStatus::Gone
+        } else {
+            BranchStatus::from_track(track.contains("ahead"), track.contains("behind"))
+        };
+
+        Ok(GitBranch {
+            name: name.to_string(),
+            status,
+            current: head == "*",
+            worktree: worktree.to_string(),
+        })
+    }
+}
+
+impl Reader for Cli {
+    fn current_branch(&self) -> Result<String> {
+        self.git().args(["branch", "--show-current"]).exec()
+    }
+
+    fn list_branches(&self) -> Result<Vec<GitBranch>> {
+        let output = self
+            .git()
+            .args(["for-each-ref", "refs/heads"])
+            .arg(format!("--format={}", Self::BRANCH_FORMAT))
+            .exec()
+            .context("unable to execute git for-each-ref command")?;
+        output
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(Self::parse_branch)
+            .collect()
+    }
+
+    fn count_changes(&self) -> Result<usize> {
+        let output = self.git().args(["status", "-s"]).exec()?;
+        Ok(output.lines().filter(|line| !line.is_empty()).count())
+    }
+
+    fn list_tags(&self) -> Result<Vec<GitTag>> {
+        let tags = self
+            .git()
+            .arg("tag")
+            .exec()?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| GitTag::from_str(line.trim()))
+            .collect();
+        Ok(tags)
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use anyhow::{anyhow, Context, Result};
+use gix::remote::Direction;
+use gix::status::UntrackedFiles;
+use gix::Repository;
+
+use crate::git::Reader;
+use crate::util::{BranchStatus, GitBranch, GitTag};
+
+/// The reader using gitoxide in process.
+pub struct Gitoxide {
+    repo: Repository,
+}
+
+impl Gitoxide {
+    /// Open the repository, returns `None` if gitoxide could not open it,
+    /// so that the caller can fall back to git command.
+    pub fn open(path: Option<&str>) -> Option<Gitoxide> {
+        let repo = gix::discover(path.unwrap_or(".")).ok()?;
+        Some(Gitoxide { repo })
+    }
+
+    /// Get the branches checked out in worktrees (include the main one),
+    /// the key is the full ref name, the value is the worktree path.
+    fn checked_out(&self) -> Result<HashMap<String, String>> {
+        let mut branches = HashMap::new();
+        let mut repos = vec![];
+        if let Ok(main) = self.repo.main_repo() {
+            repos.push(main);
+        }
+        for proxy in self.repo.worktrees()? {
+            if let Ok(repo) = proxy.into_repo_with_possibly_inaccessible_worktree() {
+                repos.push(repo);
+            }
+        }
+        for repo in repos {
+            let path = match repo.workdir() {
+                Some(path) => fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()),
+                None => continue,
+            };
+            if let Some(name) = repo.head_name()? {
+                branches.insert(name.as_bstr().to_string(), path.display().to_string());
+            }
+        }
+        Ok(branches)
+    }
+
+    fn status(&self, name: &gix::refs::FullNameRef, id: gix::ObjectId) -> Result<BranchStatus> {
+        if self
+            .repo
+            .branch_remote_ref_name(name, Direction::Fetch)
+            .is_none()
+        {
+            return Ok(BranchStatus::Detached);
+        }
+        let upstream = match self
+            .repo
+            .branch_remote_tracking_ref_name(name, Direction::Fetch)
+        {
+            Some(upstream) => upstream?,
+            None => return Ok(BranchStatus::Detached),
+        };
+        let mut upstream = match self.repo.try_find_reference(upstream.as_ref())? {
+            Some(upstream) => upstream,
+            None => return Ok(BranchStatus::Gone),
+        };
+        let upstream_id = upstream.peel_to_id()?.detach();
+        if upstream_id == id {
+            return Ok(BranchStatus::Sync);
+        }
+        let count = |tip: gix::ObjectId, hidden: gix::ObjectId| -> Result<usize> {
+            Ok(self
+                .repo
+                .rev_walk([tip])
+                .with_hidden([hidden])
+                .all()?
+                .count())
+        };
+        Ok(BranchStatus::from_track(
+            count(id, upstream_id)? > 0,
+            count(upstream_id, id)? > 0,
+        ))
+    }
+}
+
+impl Reader for Gitoxide {
+    fn current_branch(&self) -> Result<String> {
+        let name = self.repo.head_name().context("unable to read HEAD")?;
+        Ok(name
+            .map(|name| name.shorten().to_string())
+            .unwrap_or_default())
+    }
+
+    fn list_branches(&self) -> Result<Vec<GitBranch>> {
+        let head = self.repo.head_name().context("unable to read HEAD")?;
+        let checked_out = self.checked_out()?;
+        let mut branches = vec![];
+        for branch in self.repo.references()?.local_branches()? {
+            let mut branch = branch.map_err(|err| anyhow!(err))?;
+            let id = branch.peel_to_id()?.detach();
+            let name = branch.name();
+            let full_name = name.as_bstr().to_string();
+            branches.push(GitBranch {
+                name: name.shorten().to_string(),
+                status: self.status(name, id)?,
+                current: head.as_ref().is_some_and(|head| head.as_ref() == name),
+                worktree: checked_out.get(&full_name).cloned().unwrap_or_default(),
+            });
+        }
+        branches.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(branches)
+    }
+
+    fn count_changes(&self) -> Result<usize> {
+        // A file both staged and modified is reported twice (index against
+        // HEAD and worktree against index), count it once by its path as
+        // `git status -s` does. The untracked files are collapsed into their
+        // directories.
+        let items = self
+            .repo
+            .status(gix::progress::Discard)?
+            .untracked_files(UntrackedFiles::Collapsed)
+            .into_iter(None)?;
+        let mut paths = HashSet::new();
+        for item in items {
+            paths.insert(item?.location().to_owned());
+        }
+        Ok(paths.len())
+    }
+
+    fn list_tags(&self) -> Result<Vec<GitTag>> {
+        let mut tags = vec![];
+        for tag in self.repo.references()?.tags()? {
+            let tag = tag.map_err(|err| anyhow!(err))?;
+            tags.push(tag.name().shorten().to_string());
+        }
+        tags.sort();
+        Ok(tags.iter().map(GitTag::from_str).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::process::Command;
+
+    use super::*;
+    use crate::git::cli::Cli;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+            .args(["-c", "init.defaultBranch=main"])
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        git(path, &["init", "-q"]);
+        write(path, "a.txt", "a");
+        write(path, "b.txt", "b");
+        git(path, &["add", "."]);
+        git(path, &["commit", "-q", "-m", "init"]);
+        dir
+    }
+
+    fn readers(dir: &Path) -> (Gitoxide, Cli) {
+        let path = dir.to_str().unwrap();
+        (
+            Gitoxide::open(Some(path)).unwrap(),
+            Cli::new(Some(path), true),
+        )
+    }
+
+    #[test]
+    fn count_changes() {
+        let dir = repo();
+        let path = dir.path();
+        let (gix, _) = readers(path);
+        assert_eq!(gix.count_changes().unwrap(), 0);
+
+        // Staged and then modified again, one line in `git status -s`.
+        write(path, "a.txt", "a1");
+        git(path, &["add", "a.txt"]);
+        write(path, "a.txt", "a2");
+        fs::remove_file(path.join("b.txt")).unwrap();
+        // The untracked directory is one line.
+        write(path, "new/x.txt", "x");
+        write(path, "new/y.txt", "y");
+        write(path, "c.txt", "c");
+
+        let (gix, cli) = readers(path);
+        assert_eq!(gix.count_changes().unwrap(), 4);
+        assert_eq!(cli.count_changes().unwrap(), 4);
+    }
+
+    #[test]
+    fn list_branches() {
+        let dir = repo();
+        let path = dir.path();
+        git(path, &["branch", "feat"]);
+        git(path, &["tag", "feat"]);
+        git(path, &["tag", "v0.1.0"]);
+
+        let (gix, cli) = readers(path);
+        assert_eq!(gix.current_branch().unwrap(), "main");
+        assert_eq!(cli.current_branch().unwrap(), "main");
+
+        let names = |branches: Vec<GitBranch>| -> Vec<(String, bool)> {
+            branches
+                .into_iter()
+                .map(|branch| (branch.name, branch.current))
+                .collect()
+        };
+        let expect = vec![(String::from("feat"), false), (String::from("main"), true)];
+        assert_eq!(names(gix.list_branches().unwrap()), expect);
+        assert_eq!(names(cli.list_branches().unwrap()), expect);
+
+        let tags: Vec<String> = gix
+            .list_tags()
+            .unwrap()
+            .iter()
+            .map(|tag| tag.as_str().to_string())
+            .collect();
+        assert_eq!(tags, vec!["feat", "v0.1.0"]);
+    }
+}
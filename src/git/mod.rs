@@ -0,0 +1,38 @@
+//! The read operations of local git repository, such as listing branches
+//! and tags. By default they are done by running git command, with the
+//! `gitoxide` feature, they are done in process, which saves the subprocess
+//! spawns of commands reading many repos.
+
+mod cli;
+#[cfg(feature = "gitoxide")]
+mod gitoxide;
+
+use anyhow::Result;
+
+use crate::util::{GitBranch, GitTag};
+
+pub trait Reader {
+    // Get the name of current branch, empty if HEAD is detached.
+    fn current_branch(&self) -> Result<String>;
+
+    // List the local branches sorted by name, with their upstream status
+    // and the worktrees where they are checked out.
+    fn list_branches(&self) -> Result<Vec<GitBranch>>;
+
+    // Count the changed files, include the untracked ones, the same as the
+    // lines of `git status -s`.
+    fn count_changes(&self) -> Result<usize>;
+
+    // List the tags sorted by name.
+    fn list_tags(&self) -> Result<Vec<GitTag>>;
+}
+
+/// Open the reader for the repository in path, default is current
+/// directory. If `mute` is true, the git commands are not printed.
+pub fn reader(path: Option<&str>, mute: bool) -> Box<dyn Reader> {
+    #[cfg(feature = "gitoxide")]
+    if let Some(reader) = gitoxide::Gitoxide::open(path) {
+        return Box::new(reader);
+    }
+    Box::new(cli::Cli::new(path, mute))
+}
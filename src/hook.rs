@@ -0,0 +1,80 @@
+use anyhow::Result;
+use console::style;
+use serde_json::json;
+
+use crate::cmd::{Cmd, Run};
+use crate::config::Config;
+use crate::db::Database;
+use crate::util::{self, Shell};
+
+/// Run the command with the `pre_<command>` and `post_<command>` hooks in
+/// config. The hook is a bash script, it receives the context of the command
+/// as json from stdin. A failed pre hook aborts the command.
+pub fn run(cmd: Cmd, args: &[String]) -> Result<()> {
+    // Donot let a broken config block the command, the command reports
+    // config error itself if it needs config.
+    let cfg = match Config::parse() {
+        Ok(cfg) => cfg,
+        Err(_) => return cmd.run(),
+    };
+    let name = match args.get(1) {
+        Some(name) => name.as_str(),
+        None => return cmd.run(),
+    };
+    let cwd = util::current_dir()?;
+    let context = json!({
+        "command": name,
+        "args": &args[2..],
+        "cwd": cwd.display().to_string(),
+    });
+
+    if let Some(script) = cfg.hooks.get(&format!("pre_{}", name)) {
+        exec(&cfg, &format!("pre_{}", name), script, &context)?;
+    }
+    let result = cmd.run();
+
+    let script = match cfg.hooks.get(&format!("post_{}", name)) {
+        Some(script) => script,
+        None => return result,
+    };
+    let mut context = context;
+    context["success"] = json!(result.is_ok());
+    if let Err(err) = &result {
+        let msg = format!("{:#}", err);
+        context["error"] = json!(console::strip_ansi_codes(&msg));
+    }
+    let hook_result = exec(&cfg, &format!("post_{}", name), script, &context);
+    // The error of command takes precedence over the error of hook.
+    result.and(hook_result)
+}
+
+fn exec(cfg: &Config, name: &str, script: &str, context: &serde_json::Value) -> Result<()> {
+    util::print_operation(format!("hook {} ...", style(name).yellow()));
+    let mut shell = Shell::bash(script);
+    for (key, value) in repo_env(cfg) {
+        shell.env(key, value);
+    }
+    shell.input(context.to_string()).exec()?;
+    Ok(())
+}
+
+/// The environment variables of the repo at current directory, see `env`
+/// of remote in config. Empty if current directory is not a repo.
+fn repo_env(cfg: &Config) -> Vec<(String, String)> {
+    let db = match Database::open() {
+        Ok(db) => db,
+        Err(_) => return vec![],
+    };
+    let repo = match db.current_index(cfg) {
+        Ok(idx) => &db.repos[idx],
+        Err(_) => return vec![],
+    };
+    match cfg.get_remote(&repo.remote) {
+        Some(remote) => remote
+            .get_env(&repo.name)
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        None => vec![],
+    }
+}
@@ -1,3 +1,4 @@
+use std::env;
 use std::io::{self, Write};
 use std::process::ExitCode;
 
@@ -6,24 +7,39 @@ mod cmd;
 mod config;
 mod db;
 mod errors;
+mod git;
+mod hook;
+mod output;
 mod util;
 
 use clap::Parser;
-use console::{self, style};
 
-use crate::cmd::{Cmd, Run};
+use anyhow::Result;
+
+use crate::cmd::Cmd;
 use crate::errors::SilentExit;
+use crate::util::Palette;
 
 fn main() -> ExitCode {
     console::set_colors_enabled(true);
-    match Cmd::parse().run() {
+    match run() {
         Ok(()) => ExitCode::SUCCESS,
         Err(err) => match err.downcast::<SilentExit>() {
             Ok(SilentExit { code }) => code.into(),
             Err(err) => {
-                _ = writeln!(io::stderr(), "{}: {err:?}", style("error").red());
+                _ = writeln!(
+                    io::stderr(),
+                    "{}: {err:?}",
+                    Palette::get().error.apply_to("error")
+                );
                 ExitCode::FAILURE
             }
         },
     }
 }
+
+fn run() -> Result<()> {
+    let args = cmd::expand_alias(env::args().collect())?;
+    let cmd = Cmd::parse_from(&args);
+    hook::run(cmd, &args)
+}
@@ -0,0 +1,83 @@
+//! The porcelain output shared by commands supporting `--porcelain`.
+//!
+//! The porcelain output is meant for scripts, it is frozen and will not
+//! change along with the human-readable output. Each record is printed as a
+//! line, the fields are separated by a tab, with no color or padding. The
+//! tab, newline and backslash in fields are escaped as `\t`, `\n` and `\\`.
+//! The booleans are printed as `1` or `0`, the times are unix epochs, the
+//! sizes are bytes, and the unknown fields are empty.
+//!
+//! The existing fields are never reordered or removed, new fields might be
+//! appended to the end of a record, so scripts should not depend on the
+//! count of fields. The records of each command:
+//!
+//! * `list`: `<remote> <count>`, the count is the number of repos.
+//! * `list <remote>`: `<remote> <name> <path> <last_accessed> <branch>
+//!   <dirty>`, the branch and dirty are taken from the status cache.
+//! * `du`: `<remote> <name> <worktree> <git>`.
+//! * `branch`: `<name> <current> <status> <worktree>`, the status is one
+//!   of `sync`, `gone`, `ahead`, `behind`, `conflict` and `detached`, the
+//!   worktree is the path where the branch is checked out.
+//! * `history`: `<time> <action> <remote> <name> <path> <detail>`, the
+//!   remote and name are empty if the operation is not bound to a repo.
+//!
+//! The `--porcelain-v2` output of `list` is for the zsh completion, it is
+//! printed in the format of `_describe`, `<name>:<description>`, see
+//! [`Record::describe`].
+
+use std::fmt::Display;
+use std::io::{self, Write};
+
+/// A record of porcelain output, see the module document for format.
+pub struct Record {
+    fields: Vec<String>,
+    sep: char,
+}
+
+impl Default for Record {
+    fn default() -> Record {
+        Record {
+            fields: vec![],
+            sep: '\t',
+        }
+    }
+}
+
+impl Record {
+    pub fn new() -> Record {
+        Record::default()
+    }
+
+    /// A record in the `_describe` format of zsh, `<name>:<description>`.
+    /// The zsh splits the record at the first unescaped colon, so only the
+    /// colons in name are escaped, the description is kept as is.
+    pub fn describe(name: &str, desc: &str) -> Record {
+        Record {
+            fields: vec![Self::escape(name).replace(':', "\\:"), desc.to_string()],
+            sep: ':',
+        }
+    }
+
+    pub fn push<T: Display>(mut self, field: T) -> Record {
+        self.fields.push(Self::escape(&field.to_string()));
+        self
+    }
+
+    pub fn push_bool(self, field: bool) -> Record {
+        self.push(if field { 1 } else { 0 })
+    }
+
+    /// Print the record to stdout, the error is ignored so that piping the
+    /// output to commands like `head` does not panic.
+    pub fn print(self) {
+        let sep = self.sep.to_string();
+        _ = writeln!(io::stdout(), "{}", self.fields.join(&sep));
+    }
+
+    fn escape(field: &str) -> String {
+        field
+            .replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+    }
+}
@@ -8,18 +8,22 @@ use std::mem;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
 use std::time::SystemTime;
 
 use anyhow::{bail, Context, Result};
 use chrono::offset::Local;
 
 use crate::api;
-use crate::config::Config;
+use crate::config::{Config, Remote, Theme, ThemeBase};
 use crate::db::{Database, Epoch, Repo};
 use crate::errors::SilentExit;
+use crate::git;
 
-use console::{style, StyledObject, Term};
-use dialoguer::{theme::ColorfulTheme, Confirm};
+use console::{style, Style, StyledObject, Term};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Password, Select};
 use regex::{Captures, Regex};
 
 pub const SECOND: Epoch = 1;
@@ -97,6 +101,21 @@ fn tmpfile(dir: impl AsRef<Path>) -> Result<(File, PathBuf)> {
     }
 }
 
+/// Move the directory, the parent of `to` is created if missing. Fallback
+/// to `mv` if the directory cannot be renamed, such as moving across
+/// filesystems.
+pub fn move_dir(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("could not create directory {}", parent.display()))?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    Shell::new("mv").arg(from).arg(to).exec()?;
+    Ok(())
+}
+
 /// Similar to [`fs::rename`], but with retries on Windows.
 fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
     let from = from.as_ref();
@@ -170,6 +189,54 @@ pub fn confirm(msg: impl AsRef<str> + Into<String>) -> Result<()> {
     }
 }
 
+/// Like `confirm`, but returns the answer instead of exiting.
+pub fn ask(msg: impl AsRef<str> + Into<String>) -> Result<bool> {
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(msg)
+        .default(false)
+        .interact_on(&Term::stderr())
+        .context("could not do confirm prompt")
+}
+
+pub fn select(msg: impl Into<String>, items: &[&str]) -> Result<usize> {
+    Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(msg)
+        .items(items)
+        .default(0)
+        .interact_on(&Term::stderr())
+        .context("could not do select prompt")
+}
+
+pub fn input(msg: impl Into<String>) -> Result<String> {
+    Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(msg)
+        .interact_text_on(&Term::stderr())
+        .context("could not do input prompt")
+}
+
+/// Prompt for a secret, such as token, the input is not echoed.
+pub fn password(msg: impl Into<String>) -> Result<String> {
+    Password::with_theme(&ColorfulTheme::default())
+        .with_prompt(msg)
+        .interact_on(&Term::stderr())
+        .context("could not do password prompt")
+}
+
+/// Ask the user to type the expected text to confirm a dangerous operation,
+/// exit if the text does not match.
+pub fn confirm_input(msg: impl Into<String>, expect: impl AsRef<str>) -> Result<()> {
+    let text: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(msg)
+        .allow_empty(true)
+        .interact_text_on(&Term::stderr())
+        .context("could not do input prompt")?;
+    if text.trim() != expect.as_ref() {
+        _ = writeln!(io::stderr(), "input does not match, abort");
+        bail!(SilentExit { code: 60 })
+    }
+    Ok(())
+}
+
 pub fn edit<S>(msg: S, ext: S, required: bool) -> Result<String>
 where
     S: AsRef<str>,
@@ -190,19 +257,297 @@ where
     }
 }
 
-pub fn open_url(url: impl AsRef<str>) -> Result<()> {
-    open::that(url.as_ref()).with_context(|| {
+/// Open the url in browser. If the browser command is empty, the default
+/// browser of system is used, and when there is no display (such as over
+/// SSH), the url is printed to stderr instead. The stdout is not used since
+/// it might be captured as the path to jump, such as in `ui`.
+pub fn open_url(url: impl AsRef<str>, browser: &str) -> Result<()> {
+    let url = url.as_ref();
+    if !browser.is_empty() {
+        let quoted = shell_quote(url);
+        let script = match browser.contains("{url}") {
+            true => browser.replace("{url}", &quoted),
+            false => format!("{} {}", browser, quoted),
+        };
+        return Shell::bash(&script)
+            .exec()
+            .map(|_| ())
+            .with_context(|| format!("unable to open url {}", style(url).yellow()));
+    }
+    if !has_display() {
+        _ = writeln!(io::stderr(), "{}", url);
+        return Ok(());
+    }
+    open::that(url).with_context(|| {
         format!(
             "unable to open url {} in default browser",
-            style(url.as_ref()).yellow()
+            style(url).yellow()
         )
     })
 }
 
+/// Print the url to stdout rather than opening it, for `open --print`.
+pub fn print_url(url: impl AsRef<str>) -> Result<()> {
+    _ = writeln!(io::stdout(), "{}", url.as_ref());
+    Ok(())
+}
+
+/// Whether the browser can be opened. The macOS always has display, the
+/// others need X11 or Wayland, and the SSH sessions are treated as headless.
+fn has_display() -> bool {
+    if cfg!(target_os = "macos") {
+        return env::var_os("SSH_CONNECTION").is_none();
+    }
+    ["DISPLAY", "WAYLAND_DISPLAY"]
+        .iter()
+        .any(|key| env::var_os(key).is_some_and(|value| !value.is_empty()))
+}
+
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Send a desktop notification. This is best effort, all errors are ignored.
+pub fn notify(title: &str, body: &str) {
+    let mut cmd = if cfg!(target_os = "macos") {
+        let script = format!("display notification {:?} with title {:?}", body, title);
+        let mut cmd = Command::new("osascript");
+        cmd.args(["-e", script.as_str()]);
+        cmd
+    } else {
+        let mut cmd = Command::new("notify-send");
+        cmd.args([title, body]);
+        cmd
+    };
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    _ = cmd.status();
+}
+
+/// Run `zoxide add` or `zoxide remove` with the path. This is best effort,
+/// the errors (such as zoxide is not installed) are ignored.
+pub fn zoxide(action: &str, path: &Path) {
+    _ = Command::new("zoxide")
+        .arg(action)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// List the processes `(pid, name)` whose working directory or open files
+/// are under the path. This is best-effort: `/proc` is scanned on Linux,
+/// `lsof` is used elsewhere if installed. The current process and its
+/// parent (the shell) are excluded.
+pub fn path_processes(path: &Path) -> Vec<(u32, String)> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    #[cfg(unix)]
+    let excludes = [std::process::id(), std::os::unix::process::parent_id()];
+    #[cfg(not(unix))]
+    let excludes = [std::process::id()];
+    let mut procs = vec![];
+    if let Ok(entries) = fs::read_dir("/proc") {
+        for entry in entries.flatten() {
+            let pid: u32 = match entry.file_name().to_str().map(str::parse) {
+                Some(Ok(pid)) => pid,
+                _ => continue,
+            };
+            if excludes.contains(&pid) {
+                continue;
+            }
+            let dir = entry.path();
+            let under = |link: PathBuf| match fs::read_link(link) {
+                Ok(target) => target.starts_with(&path),
+                Err(_) => false,
+            };
+            let mut found = under(dir.join("cwd"));
+            if !found {
+                if let Ok(fds) = fs::read_dir(dir.join("fd")) {
+                    found = fds.flatten().any(|fd| under(fd.path()));
+                }
+            }
+            if found {
+                let name = fs::read_to_string(dir.join("comm")).unwrap_or_default();
+                procs.push((pid, name.trim().to_string()));
+            }
+        }
+        return procs;
+    }
+
+    // The lsof outputs fields in lines like "p<pid>" and "c<command>".
+    let output = match Command::new("lsof")
+        .arg("-Fpc")
+        .arg("+D")
+        .arg(&path)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+    {
+        Ok(output) => output,
+        Err(_) => return procs,
+    };
+    let mut pid = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(value) = line.strip_prefix('p') {
+            pid = value
+                .parse::<u32>()
+                .ok()
+                .filter(|pid| !excludes.contains(pid));
+        } else if let (Some(value), Some(id)) = (line.strip_prefix('c'), pid) {
+            procs.push((id, value.to_string()));
+            pid = None;
+        }
+    }
+    procs
+}
+
+/// Warn the processes running under the path before removing or moving it,
+/// see `process_check` in config. With `force`, only warn and continue
+/// rather than asking.
+pub fn check_processes(cfg: &Config, path: &Path, force: bool) -> Result<()> {
+    if !cfg.process_check {
+        return Ok(());
+    }
+    let procs = path_processes(path);
+    if procs.is_empty() {
+        return Ok(());
+    }
+    _ = writeln!(
+        io::stderr(),
+        "{} {} processes are running under {}:",
+        Palette::get().warning.apply_to("warning"),
+        procs.len(),
+        path.display()
+    );
+    for (pid, name) in procs {
+        _ = writeln!(io::stderr(), "  {} {}", style(pid).yellow(), name);
+    }
+    if force {
+        return Ok(());
+    }
+    confirm("continue anyway")
+}
+
 pub fn current_dir() -> Result<PathBuf> {
     env::current_dir().context("could not get current dir")
 }
 
+/// Compare two paths, if `resolve` is true, the symlinks in them are resolved
+/// before comparing, so a path under a symlinked workspace matches its
+/// target.
+pub fn same_path(a: &Path, b: &Path, resolve: bool) -> bool {
+    if a == b {
+        return true;
+    }
+    if !resolve {
+        return false;
+    }
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Walk up from `dir` to find the top-level of the git repository. The
+/// top-level is returned first; if it is a linked worktree, the top-level
+/// of its main repository follows. Returns empty if `dir` is not in a git
+/// repository.
+pub fn git_roots(dir: &Path) -> Result<Vec<PathBuf>> {
+    for path in dir.ancestors() {
+        let git = path.join(".git");
+        let meta = match fs::metadata(&git) {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(err).with_context(|| format!("could not read {}", git.display()))
+            }
+        };
+        let mut roots = vec![path.to_path_buf()];
+        if meta.is_dir() {
+            return Ok(roots);
+        }
+
+        // For linked worktrees and submodules, ".git" is a file pointing to
+        // the gitdir. Only the gitdir of a worktree has the "commondir" file,
+        // which points to the ".git" directory of main repository.
+        let content = fs::read_to_string(&git)
+            .with_context(|| format!("could not read {}", git.display()))?;
+        let gitdir = match content.trim().strip_prefix("gitdir:") {
+            Some(gitdir) => path.join(gitdir.trim()),
+            None => bail!("invalid git file {}", style(git.display()).yellow()),
+        };
+        let commondir = match fs::read_to_string(gitdir.join("commondir")) {
+            Ok(commondir) => gitdir.join(commondir.trim()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(roots),
+            Err(err) => return Err(err).context("could not read commondir of worktree"),
+        };
+        if let Ok(commondir) = fs::canonicalize(&commondir) {
+            if commondir.file_name() == Some(OsStr::new(".git")) {
+                if let Some(main) = commondir.parent() {
+                    roots.push(main.to_path_buf());
+                }
+            }
+        }
+        return Ok(roots);
+    }
+    Ok(vec![])
+}
+
+/// Parse the domain and repository name from a git remote url, supports
+/// "https://domain/name.git", "ssh://git@domain:port/name.git" and
+/// "git@domain:name.git".
+pub fn parse_git_url(url: impl AsRef<str>) -> Option<(String, String)> {
+    let (host, path) = match url.as_ref().split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        None => url.as_ref().split_once(':')?,
+    };
+    let domain = host.rsplit('@').next()?.split(':').next()?;
+    let name = path.trim_matches('/').trim_end_matches(".git");
+    if domain.is_empty() || name.is_empty() {
+        return None;
+    }
+    Some((domain.to_string(), name.to_string()))
+}
+
+/// Parse the domain and repository name from the url copied from browser
+/// or a clone url. The suffix of web pages is trimmed: GitLab separates it
+/// by "/-/", such as "/-/merge_requests"; otherwise the known pages of web
+/// urls, such as "/tree/main" in GitHub, are trimmed. The name must have the
+/// group, such as the user page "github.com/user" is not a repository.
+pub fn parse_repo_url(url: impl AsRef<str>) -> Option<(String, String)> {
+    let url = url.as_ref().trim();
+    let url = url.split(['?', '#']).next()?;
+    let (domain, name) = parse_git_url(url)?;
+    let mut parts: Vec<&str> = name.split('/').filter(|part| !part.is_empty()).collect();
+    match parts.iter().position(|part| *part == "-") {
+        Some(idx) => parts.truncate(idx),
+        // The clone urls have no web pages, a nested group might be named
+        // like a page.
+        None if url.starts_with("http://") || url.starts_with("https://") => {
+            if let Some(idx) = parts
+                .iter()
+                .skip(2)
+                .position(|part| WEB_PAGES.contains(part))
+            {
+                parts.truncate(idx + 2);
+            }
+        }
+        None => {}
+    }
+    if parts.len() < 2 {
+        return None;
+    }
+    Some((domain, parts.join("/")))
+}
+
+/// The first path segment of web pages after the repository name.
+const WEB_PAGES: &[&str] = &[
+    "tree", "blob", "commit", "commits", "pull", "pulls", "issues", "releases", "src", "actions",
+    "wiki", "compare",
+];
+
 pub fn str_to_path(s: impl AsRef<str>) -> Result<PathBuf> {
     let path = PathBuf::from_str(s.as_ref())
         .with_context(|| format!("could not parse path {}", style(s.as_ref()).yellow()))?;
@@ -228,8 +573,365 @@ pub fn path_to_str<'a>(path: &'a PathBuf) -> Result<&'a str> {
     }
 }
 
+/// Output the path of the repo to jump. If `_GZ_RESULT_FILE` is set, the
+/// path and the metadata of repo are written to that file instead of stdout,
+/// so that the caller does not need to capture stdout, which would take the
+/// terminal away from the interactive prompts.
+pub fn output_repo_path(cfg: &Config, repo: &Repo, path: &Path) -> Result<()> {
+    if cfg.zoxide {
+        zoxide("add", path);
+    }
+    let file = match env::var_os("_GZ_RESULT_FILE") {
+        Some(file) if !file.is_empty() => PathBuf::from(file),
+        _ => {
+            println!("{}", path.display());
+            return Ok(());
+        }
+    };
+    let content = format!(
+        "{}\nremote={}\nname={}\n",
+        path.display(),
+        repo.remote,
+        repo.name
+    );
+    fs::write(&file, content)
+        .with_context(|| format!("could not write result file {}", file.display()))
+}
+
 pub fn print_operation(s: impl AsRef<str>) {
-    _ = writeln!(io::stderr(), "{} {}", style("==>").green(), s.as_ref());
+    let banner = Palette::get().operation.apply_to("==>");
+    _ = writeln!(io::stderr(), "{} {}", banner, s.as_ref());
+}
+
+/// The styles used in output, built from the `theme` in config. Before the
+/// config is parsed, the dark theme is used.
+pub struct Palette {
+    pub operation: Style,
+    pub command: Style,
+    pub error: Style,
+    pub warning: Style,
+
+    pub branch_sync: Style,
+    pub branch_gone: Style,
+    pub branch_ahead: Style,
+    pub branch_behind: Style,
+    pub branch_conflict: Style,
+    pub branch_detached: Style,
+}
+
+static PALETTE: OnceLock<Palette> = OnceLock::new();
+
+impl Palette {
+    /// Init the palette from theme, only the first call takes effect.
+    pub fn init(theme: &Theme) {
+        if PALETTE.get().is_some() {
+            return;
+        }
+        if theme.base == ThemeBase::Plain {
+            console::set_colors_enabled(false);
+            console::set_colors_enabled_stderr(false);
+        }
+        let mut palette = Self::base(theme.base);
+        let overrides = [
+            (&mut palette.operation, &theme.operation),
+            (&mut palette.command, &theme.command),
+            (&mut palette.error, &theme.error),
+            (&mut palette.warning, &theme.warning),
+            (&mut palette.branch_sync, &theme.branch_sync),
+            (&mut palette.branch_gone, &theme.branch_gone),
+            (&mut palette.branch_ahead, &theme.branch_ahead),
+            (&mut palette.branch_behind, &theme.branch_behind),
+            (&mut palette.branch_conflict, &theme.branch_conflict),
+            (&mut palette.branch_detached, &theme.branch_detached),
+        ];
+        for (style, color) in overrides {
+            if !color.is_empty() {
+                *style = Style::from_dotted_str(color);
+            }
+        }
+        _ = PALETTE.set(palette);
+    }
+
+    pub fn get() -> &'static Palette {
+        PALETTE.get_or_init(|| Self::base(ThemeBase::Dark))
+    }
+
+    fn base(base: ThemeBase) -> Palette {
+        match base {
+            ThemeBase::Dark => Palette {
+                operation: Style::new().green(),
+                command: Style::new().cyan(),
+                error: Style::new().red(),
+                warning: Style::new().yellow(),
+                branch_sync: Style::new().green(),
+                branch_gone: Style::new().red(),
+                branch_ahead: Style::new().yellow(),
+                branch_behind: Style::new().yellow(),
+                branch_conflict: Style::new().yellow().bold(),
+                branch_detached: Style::new().red(),
+            },
+            ThemeBase::Light => Palette {
+                operation: Style::new().blue(),
+                command: Style::new().magenta(),
+                error: Style::new().red(),
+                warning: Style::new().magenta(),
+                branch_sync: Style::new().green(),
+                branch_gone: Style::new().red(),
+                branch_ahead: Style::new().blue(),
+                branch_behind: Style::new().blue(),
+                branch_conflict: Style::new().magenta().bold(),
+                branch_detached: Style::new().red(),
+            },
+            ThemeBase::Plain => Palette {
+                operation: Style::new(),
+                command: Style::new(),
+                error: Style::new(),
+                warning: Style::new(),
+                branch_sync: Style::new(),
+                branch_gone: Style::new(),
+                branch_ahead: Style::new(),
+                branch_behind: Style::new(),
+                branch_conflict: Style::new(),
+                branch_detached: Style::new(),
+            },
+        }
+    }
+}
+
+/// Match a slash-separated path against a glob pattern. In each segment,
+/// `*` matches any characters and `?` matches one character. The `**`
+/// segment matches zero or more segments, so `infra/**` matches `infra`
+/// and all of its nested groups.
+pub fn glob_match(pattern: impl AsRef<str>, path: impl AsRef<str>) -> bool {
+    let pattern: Vec<&str> = pattern.as_ref().split('/').collect();
+    let path: Vec<&str> = path.as_ref().split('/').collect();
+    glob_match_segments(&pattern, &path)
+}
+
+fn glob_match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(&"**") => (0..=path.len()).any(|n| glob_match_segments(&pattern[1..], &path[n..])),
+        Some(segment) => match path.first() {
+            Some(name) => {
+                glob_match_segment(segment.as_bytes(), name.as_bytes())
+                    && glob_match_segments(&pattern[1..], &path[1..])
+            }
+            None => false,
+        },
+    }
+}
+
+fn glob_match_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some(b'*') => (0..=name.len()).any(|n| glob_match_segment(&pattern[1..], &name[n..])),
+        Some(b'?') => !name.is_empty() && glob_match_segment(&pattern[1..], &name[1..]),
+        Some(c) => name.first() == Some(c) && glob_match_segment(&pattern[1..], &name[1..]),
+    }
+}
+
+/// Build the "did you mean" suggestion for a name not found. The candidates
+/// are `(key, display)` pairs, a candidate is suggested if the edit distance
+/// between its key and the name is within a third of the name length. At
+/// most 3 suggestions are shown, the closest first. Return an empty string
+/// if nothing is close, so the result can be appended to error message
+/// directly.
+pub fn did_you_mean<'a, I>(name: &str, candidates: I) -> String
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let max = (name.chars().count() / 3).max(1);
+    let mut matches: Vec<(usize, &str)> = vec![];
+    for (key, display) in candidates {
+        let distance = edit_distance(name, key);
+        if distance > max {
+            continue;
+        }
+        match matches.iter_mut().find(|(_, item)| *item == display) {
+            Some(item) => item.0 = item.0.min(distance),
+            None => matches.push((distance, display)),
+        }
+    }
+    if matches.is_empty() {
+        return String::new();
+    }
+    matches.sort();
+    let names: Vec<String> = matches
+        .iter()
+        .take(3)
+        .map(|(_, display)| style(display).yellow().to_string())
+        .collect();
+    format!(", did you mean {}?", names.join(", "))
+}
+
+/// The Levenshtein distance between two strings, in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { prev } else { prev + 1 };
+            prev = row[j + 1];
+            row[j + 1] = cost.min(row[j] + 1).min(prev + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Replace the `{key}` placeholders in template with values.
+pub fn render_template(template: impl AsRef<str>, values: &[(&str, &str)]) -> String {
+    let mut result = template.as_ref().to_string();
+    for (key, value) in values {
+        result = result.replace(&format!("{{{}}}", key), value);
+    }
+    result
+}
+
+/// Get the total size of files under the directory, symlinks are not
+/// followed.
+pub fn dir_size(path: &Path) -> Result<u64> {
+    let meta = match fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => {
+            return Err(err).with_context(|| format!("could not read meta of {}", path.display()))
+        }
+    };
+    if !meta.is_dir() {
+        return Ok(meta.len());
+    }
+    let mut size = 0;
+    let dir =
+        fs::read_dir(path).with_context(|| format!("could not read dir {}", path.display()))?;
+    for entry in dir {
+        let entry = entry.context("could not read dir entry")?;
+        size += dir_size(&entry.path())?;
+    }
+    Ok(size)
+}
+
+/// Format the duration since the time, like "3h ago".
+pub fn format_since(now: Epoch, time: Epoch) -> String {
+    let duration = now.saturating_sub(time);
+    if duration < MINUTE {
+        String::from("just now")
+    } else if duration < HOUR {
+        format!("{}m ago", duration / MINUTE)
+    } else if duration < DAY {
+        format!("{}h ago", duration / HOUR)
+    } else if duration < WEEK {
+        format!("{}d ago", duration / DAY)
+    } else {
+        format!("{}w ago", duration / WEEK)
+    }
+}
+
+/// Parse the duration like "30m", "12h", "7d" or "2w", the reverse of
+/// [`format_since`].
+pub fn parse_duration(s: impl AsRef<str>) -> Result<Epoch> {
+    let s = s.as_ref();
+    let unit = match s.chars().last() {
+        Some('m') => MINUTE,
+        Some('h') => HOUR,
+        Some('d') => DAY,
+        Some('w') => WEEK,
+        _ => bail!("invalid duration {}, expect like 7d", style(s).yellow()),
+    };
+    match s[..s.len() - 1].parse::<Epoch>() {
+        Ok(num) => Ok(num * unit),
+        Err(_) => bail!("invalid duration {}, expect like 7d", style(s).yellow()),
+    }
+}
+
+pub fn human_bytes(size: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{}{}", size, UNITS[unit]),
+        _ => format!("{:.1}{}", size, UNITS[unit]),
+    }
+}
+
+/// Parse the size like "512MiB", "5G" or "1024", the reverse of
+/// [`human_bytes`]. The units are binary, "K", "KB" and "KiB" are the same.
+pub fn parse_bytes(s: impl AsRef<str>) -> Result<u64> {
+    let s = s.as_ref();
+    let idx = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(idx);
+    let shift = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        "T" | "TB" | "TIB" => 40,
+        _ => bail!("invalid size {}, expect like 5GiB", style(s).yellow()),
+    };
+    let num = match num.parse::<u64>() {
+        Ok(num) => num,
+        Err(_) => bail!("invalid size {}, expect like 5GiB", style(s).yellow()),
+    };
+    match num.checked_mul(1 << shift) {
+        Some(bytes) => Ok(bytes),
+        None => bail!("size {} is too large", style(s).yellow()),
+    }
+}
+
+/// Get the free space available to user on the filesystem of path, the path
+/// does not need to exist, its nearest existing ancestor is checked.
+pub fn free_space(path: &Path) -> Result<u64> {
+    let mut path = path;
+    while !path.exists() {
+        path = match path.parent() {
+            Some(parent) => parent,
+            None => break,
+        };
+    }
+    let stat = nix::sys::statvfs::statvfs(path)
+        .with_context(|| format!("could not stat filesystem of {}", path.display()))?;
+    Ok(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+}
+
+/// Run the function for every item in parallel, the results are returned in
+/// the same order as items.
+pub fn parallel<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = match thread::available_parallelism() {
+        Ok(n) => n.get(),
+        Err(_) => 4,
+    };
+    let workers = workers.min(items.len());
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+    thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                if idx >= items.len() {
+                    break;
+                }
+                let result = f(&items[idx]);
+                results.lock().unwrap()[idx] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.unwrap())
+        .collect()
 }
 
 pub fn option_arg<'a>(args: &'a Vec<String>) -> Option<&'a str> {
@@ -245,10 +947,11 @@ const ERR_FZF_NOT_FOUND: &str = "could not find fzf, is it installed?";
 pub struct Fzf(Child);
 
 impl Fzf {
-    pub fn build() -> Result<Fzf> {
+    pub fn build(args: &[&str]) -> Result<Fzf> {
         // TODO: support Windows
         let program = "fzf";
         let mut cmd = Command::new(program);
+        cmd.args(args);
         cmd.stdin(Stdio::piped()).stdout(Stdio::piped());
 
         match cmd.spawn() {
@@ -296,10 +999,135 @@ impl Fzf {
     }
 }
 
+/// The tokens of remotes stored in the system keyring, by `security` on macOS
+/// and `secret-tool` (libsecret) on other systems. The remote name is used as
+/// the account of token.
+pub struct Keyring;
+
+impl Keyring {
+    const SERVICE: &str = "git-zoxide";
+
+    /// Get the token of remote, None if it is not stored. The other failures,
+    /// such as the keychain is locked, are returned as error.
+    pub fn get(remote: &str) -> Result<Option<String>> {
+        Self::ensure_tool()?;
+        let (mut cmd, not_found) = match cfg!(target_os = "macos") {
+            true => {
+                let mut cmd = Command::new("security");
+                cmd.args(["find-generic-password", "-s", Self::SERVICE])
+                    .args(["-a", remote, "-w"]);
+                // The `errSecItemNotFound`.
+                (cmd, 44)
+            }
+            false => {
+                let mut cmd = Command::new("secret-tool");
+                cmd.args(["lookup", "service", Self::SERVICE, "account", remote]);
+                // The secret-tool exits with 1 for both not found and errors,
+                // only the errors print message.
+                (cmd, 1)
+            }
+        };
+        let output = cmd
+            .stdin(Stdio::null())
+            .output()
+            .with_context(|| format!("could not read token of {} from keyring", remote))?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        if output.status.success() {
+            let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            return Ok(match token.is_empty() {
+                true => None,
+                false => Some(token),
+            });
+        }
+        if output.status.code() == Some(not_found)
+            && (cfg!(target_os = "macos") || stderr.is_empty())
+        {
+            return Ok(None);
+        }
+        bail!(
+            "could not read token of {} from keyring: {}",
+            remote,
+            match stderr.is_empty() {
+                true => format!("exit with {}", output.status),
+                false => stderr.to_string(),
+            }
+        )
+    }
+
+    /// Store the token of remote, the old one is replaced.
+    pub fn set(remote: &str, token: &str) -> Result<()> {
+        Self::ensure_tool()?;
+        match cfg!(target_os = "macos") {
+            // The `security` only accepts the password in arguments, which
+            // can be seen by other users with `ps`. Run the command in the
+            // interactive mode of `security` instead, the command is read
+            // from stdin.
+            true => Shell::new("security")
+                .mute()
+                .arg("-i")
+                .input(format!(
+                    "add-generic-password -U -s {} -a {} -w {}\n",
+                    Self::SERVICE,
+                    Self::security_quote(remote),
+                    Self::security_quote(token)
+                ))
+                .exec(),
+            false => Shell::new("secret-tool")
+                .mute()
+                .args(["store", "--label"])
+                .arg(format!("{} token for {}", Self::SERVICE, remote))
+                .args(["service", Self::SERVICE, "account", remote])
+                .input(token)
+                .exec(),
+        }
+        .with_context(|| format!("could not store token of {} to keyring", remote))?;
+        // The interactive mode does not report the failure of command in exit
+        // code, read it back to check.
+        if cfg!(target_os = "macos") && Self::get(remote)?.as_deref() != Some(token) {
+            bail!("could not store token of {} to keyring", remote)
+        }
+        Ok(())
+    }
+
+    /// Quote the argument for the interactive mode of `security`, which
+    /// splits the command by spaces and supports double quotes.
+    fn security_quote(arg: &str) -> String {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    /// Remove the token of remote, return false if it is not stored.
+    pub fn remove(remote: &str) -> Result<bool> {
+        if Self::get(remote)?.is_none() {
+            return Ok(false);
+        }
+        match cfg!(target_os = "macos") {
+            true => Shell::new("security")
+                .mute()
+                .args(["delete-generic-password", "-s", Self::SERVICE, "-a", remote])
+                .exec(),
+            false => Shell::new("secret-tool")
+                .mute()
+                .args(["clear", "service", Self::SERVICE, "account", remote])
+                .exec(),
+        }
+        .with_context(|| format!("could not remove token of {} from keyring", remote))?;
+        Ok(true)
+    }
+
+    fn ensure_tool() -> Result<()> {
+        if cfg!(target_os = "macos") || Shell::cmd_exists("secret-tool") {
+            return Ok(());
+        }
+        bail!("could not find secret-tool to access keyring, is libsecret installed?")
+    }
+}
+
 pub struct Shell {
     cmd: Command,
     program: OsString,
     mute: bool,
+    input: Option<String>,
 }
 
 impl Shell {
@@ -312,6 +1140,7 @@ impl Shell {
             cmd,
             program: name.as_ref().to_os_string(),
             mute: false,
+            input: None,
         }
     }
 
@@ -347,11 +1176,23 @@ impl Shell {
         cmd.arg(format!("command -v {}", str));
 
         match cmd.output() {
-            Ok(_) => true,
+            Ok(output) => output.status.success(),
             Err(_) => false,
         }
     }
 
+    pub fn mute(&mut self) -> &mut Self {
+        self.mute = true;
+        self
+    }
+
+    /// Write the input to the stdin of the command.
+    pub fn input(&mut self, input: impl Into<String>) -> &mut Self {
+        self.cmd.stdin(Stdio::piped());
+        self.input = Some(input.into());
+        self
+    }
+
     pub fn inherit(&mut self) -> &mut Self {
         self.cmd.stdout(Stdio::inherit());
         self
@@ -443,6 +1284,12 @@ impl Shell {
             Err(e) => return Err(e).with_context(|| format!("could not launch {}", "")),
         };
 
+        if let (Some(input), Some(mut stdin)) = (&self.input, child.stdin.take()) {
+            stdin
+                .write_all(input.as_bytes())
+                .with_context(|| format!("failed to write to {}", program))?;
+        }
+
         let mut output = String::new();
 
         if let Some(mut stdout) = child.stdout.take() {
@@ -479,7 +1326,7 @@ impl Shell {
         _ = writeln!(
             io::stderr(),
             "{} {}",
-            style("==>").cyan(),
+            Palette::get().command.apply_to("==>"),
             style(cmd_str).bold()
         );
         Ok(())
@@ -496,16 +1343,40 @@ pub enum BranchStatus {
 }
 
 impl BranchStatus {
-    pub fn display(&self) -> StyledObject<&'static str> {
+    /// Get the status of a branch having upstream by whether it is ahead or
+    /// behind of the upstream.
+    pub fn from_track(ahead: bool, behind: bool) -> BranchStatus {
+        match (ahead, behind) {
+            (true, true) => Self::Conflict,
+            (true, false) => Self::Ahead,
+            (false, true) => Self::Behind,
+            (false, false) => Self::Sync,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
         match self {
-            Self::Sync => style("sync").green(),
-            Self::Gone => style("gone").red(),
-            Self::Ahead => style("ahead").yellow(),
-            Self::Behind => style("behind").yellow(),
-            Self::Conflict => style("conflict").yellow().bold(),
-            Self::Detached => style("detached").red(),
+            Self::Sync => "sync",
+            Self::Gone => "gone",
+            Self::Ahead => "ahead",
+            Self::Behind => "behind",
+            Self::Conflict => "conflict",
+            Self::Detached => "detached",
         }
     }
+
+    pub fn display(&self) -> StyledObject<&'static str> {
+        let palette = Palette::get();
+        let style = match self {
+            Self::Sync => &palette.branch_sync,
+            Self::Gone => &palette.branch_gone,
+            Self::Ahead => &palette.branch_ahead,
+            Self::Behind => &palette.branch_behind,
+            Self::Conflict => &palette.branch_conflict,
+            Self::Detached => &palette.branch_detached,
+        };
+        style.apply_to(self.name())
+    }
 }
 
 pub struct GitBranch {
@@ -513,26 +1384,17 @@ pub struct GitBranch {
     pub status: BranchStatus,
 
     pub current: bool,
+
+    /// The path of the worktree where the branch is checked out, empty if
+    /// it is not checked out.
+    pub worktree: String,
 }
 
 impl GitBranch {
-    const BRANCH_REGEX: &str = r"^(\*)*[ ]*([^ ]*)[ ]*([^ ]*)[ ]*(\[[^\]]*\])*[ ]*(.*)$";
     const HEAD_BRANCH_PREFIX: &str = "HEAD branch:";
 
     pub fn list() -> Result<Vec<GitBranch>> {
-        let re = Regex::new(Self::BRANCH_REGEX).expect("parse git branch regex");
-        let mut git = Shell::git();
-        git.args(["branch", "-vv"]);
-
-        let output = git.exec().context("unable to execute git branch command")?;
-        let lines: Vec<&str> = output.split("\n").collect();
-        let mut branches: Vec<GitBranch> = Vec::with_capacity(lines.len());
-        for line in lines {
-            let branch = Self::parse(&re, line)?;
-            branches.push(branch);
-        }
-
-        Ok(branches)
+        git::reader(None, false).list_branches()
     }
 
     pub fn default() -> Result<String> {
@@ -578,23 +1440,20 @@ impl GitBranch {
     }
 
     pub fn current() -> Result<String> {
-        Shell::git().args(["branch", "--show-current"]).exec()
+        git::reader(None, false).current_branch()
     }
 
     pub fn ensure_no_uncommitted() -> Result<()> {
-        let mut git = Shell::git();
-        git.args(["status", "-s"]);
-        let output = git.exec()?;
-        if !output.is_empty() {
-            let lines: Vec<&str> = output.split("\n").collect();
-            let (word, call) = if lines.len() == 1 {
+        let count = git::reader(None, false).count_changes()?;
+        if count > 0 {
+            let (word, call) = if count == 1 {
                 ("change", "it")
             } else {
                 ("changes", "them")
             };
             bail!(
                 "you have {} uncommitted {}, please handle {} first",
-                lines.len(),
+                count,
                 word,
                 call
             )
@@ -602,62 +1461,181 @@ impl GitBranch {
         Ok(())
     }
 
-    fn parse(re: &Regex, line: impl AsRef<str>) -> Result<GitBranch> {
-        let parse_err = format!(
-            "invalid branch description {}, please check your git command",
-            style(line.as_ref()).yellow()
-        );
-        let mut iter = re.captures_iter(line.as_ref());
-        let caps = match iter.next() {
-            Some(caps) => caps,
-            None => bail!(parse_err),
+    /// Whether the branch is checked out in another worktree, such branch
+    /// cannot be checked out or deleted here.
+    pub fn checked_out_elsewhere(&self) -> bool {
+        !self.current && !self.worktree.is_empty()
+    }
+}
+
+/// The git operation that can be stopped by conflicts.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GitOperation {
+    Rebase,
+    Am,
+    Merge,
+    CherryPick,
+    Revert,
+}
+
+impl GitOperation {
+    /// Detect the operation in progress by the state files in git dir.
+    pub fn detect(root: &PathBuf) -> Result<Option<GitOperation>> {
+        let root_str = path_to_str(root)?;
+        let exists = |name: &str| -> Result<bool> {
+            let path = Shell::git()
+                .mute()
+                .with_git_path(root_str)
+                .args(["rev-parse", "--git-path", name])
+                .exec()?;
+            Ok(root.join(path).exists())
         };
-        // We have 6 captures:
-        //   0 -> line itself
-        //   1 -> current branch
-        //   2 -> branch name
-        //   3 -> commit id
-        //   4 -> remote description
-        //   5 -> commit message
-        if caps.len() != 6 {
-            bail!(parse_err)
-        }
-        let mut current = false;
-        if let Some(_) = caps.get(1) {
-            current = true;
-        }
-
-        let name = match caps.get(2) {
-            Some(name) => name.as_str().trim(),
-            None => bail!("{}: missing name", parse_err),
+        if exists("rebase-merge")? {
+            return Ok(Some(GitOperation::Rebase));
+        }
+        // The "git am" shares the state directory with the old rebase
+        // backend, it can be told by the "applying" file.
+        if exists("rebase-apply")? {
+            match exists("rebase-apply/applying")? {
+                true => return Ok(Some(GitOperation::Am)),
+                false => return Ok(Some(GitOperation::Rebase)),
+            }
+        }
+        let heads = [
+            ("MERGE_HEAD", GitOperation::Merge),
+            ("CHERRY_PICK_HEAD", GitOperation::CherryPick),
+            ("REVERT_HEAD", GitOperation::Revert),
+        ];
+        for (head, op) in heads {
+            if exists(head)? {
+                return Ok(Some(op));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The git command of the operation.
+    pub fn command(&self) -> &'static str {
+        match self {
+            GitOperation::Rebase => "rebase",
+            GitOperation::Am => "am",
+            GitOperation::Merge => "merge",
+            GitOperation::CherryPick => "cherry-pick",
+            GitOperation::Revert => "revert",
+        }
+    }
+}
+
+/// Run rebase and help user to resolve its conflicts interactively.
+pub struct GitRebase;
+
+impl GitRebase {
+    /// Run the rebase command, if it stops because of conflicts, list the
+    /// conflicted files and let user resolve them one by one with mergetool
+    /// or editor, then continue the rebase.
+    pub fn run(git: &mut Shell) -> Result<()> {
+        let err = match git.exec() {
+            Ok(_) => return Ok(()),
+            Err(err) => err,
         };
+        let root = Shell::git()
+            .mute()
+            .args(["rev-parse", "--show-toplevel"])
+            .exec()?;
+        let root = PathBuf::from(root);
+        if !Self::in_progress(&root)? || !Term::stderr().is_term() {
+            return Err(err);
+        }
 
-        let status = match caps.get(4) {
-            Some(remote_desc) => {
-                let remote_desc = remote_desc.as_str();
-                let behind = remote_desc.contains("behind");
-                let ahead = remote_desc.contains("ahead");
-
-                if remote_desc.contains("gone") {
-                    BranchStatus::Gone
-                } else if ahead && behind {
-                    BranchStatus::Conflict
-                } else if ahead {
-                    BranchStatus::Ahead
-                } else if behind {
-                    BranchStatus::Behind
-                } else {
-                    BranchStatus::Sync
+        loop {
+            let files = Self::conflicted_files(&root)?;
+            if files.is_empty() {
+                // Keep the commit message, no need to edit it.
+                let result = Shell::git()
+                    .with_git_path(path_to_str(&root)?)
+                    .env("GIT_EDITOR", "true")
+                    .args(["rebase", "--continue"])
+                    .exec();
+                if !Self::in_progress(&root)? {
+                    return result.map(|_| ());
+                }
+                if Self::conflicted_files(&root)?.is_empty() {
+                    bail!("rebase could not continue, please check `git status`")
                 }
+                continue;
+            }
+
+            _ = writeln!(io::stderr());
+            _ = writeln!(
+                io::stderr(),
+                "Found {} conflicted files:",
+                style(files.len()).yellow()
+            );
+            for file in &files {
+                _ = writeln!(io::stderr(), "  * {}", file);
+            }
+            _ = writeln!(io::stderr());
+            for file in &files {
+                Self::resolve(&root, file)?;
             }
-            None => BranchStatus::Detached,
-        };
 
-        Ok(GitBranch {
-            name: name.to_string(),
-            status,
-            current,
-        })
+            if !Self::conflicted_files(&root)?.is_empty()
+                && !ask("there are still conflicted files, do you want to resolve them")?
+            {
+                bail!("rebase is in progress, please resolve the conflicts and run `gz continue`")
+            }
+        }
+    }
+
+    fn resolve(root: &PathBuf, file: &str) -> Result<()> {
+        let root_str = path_to_str(root)?;
+        let options = ["launch mergetool", "open in editor", "skip", "abort rebase"];
+        match select(format!("how to resolve {}", style(file).yellow()), &options)? {
+            0 => {
+                // The mergetool marks the file as resolved if it succeeds.
+                _ = Shell::git()
+                    .with_git_path(root_str)
+                    .args(["mergetool", "--no-prompt", file])
+                    .inherit()
+                    .exec();
+            }
+            1 => {
+                let editor = env::var("EDITOR").ok();
+                Shell::edit_file(&editor, &root.join(file))?;
+                if ask(format!("mark {} as resolved", style(file).yellow()))? {
+                    Shell::git()
+                        .with_git_path(root_str)
+                        .args(["add", file])
+                        .exec()?;
+                }
+            }
+            2 => {}
+            _ => {
+                Shell::git()
+                    .with_git_path(root_str)
+                    .args(["rebase", "--abort"])
+                    .exec()?;
+                bail!(SilentExit { code: 101 })
+            }
+        }
+        Ok(())
+    }
+
+    fn in_progress(root: &PathBuf) -> Result<bool> {
+        Ok(GitOperation::detect(root)? == Some(GitOperation::Rebase))
+    }
+
+    fn conflicted_files(root: &PathBuf) -> Result<Vec<String>> {
+        let output = Shell::git()
+            .mute()
+            .with_git_path(path_to_str(root)?)
+            .args(["diff", "--name-only", "--diff-filter=U"])
+            .exec()?;
+        Ok(output
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect())
     }
 }
 
@@ -688,7 +1666,7 @@ impl GitRemote {
 
         let db = Database::open()?;
         let config = Config::parse()?;
-        let repo = db.current(&config.workspace)?;
+        let repo = db.current(&config)?;
         let remote_config = config.must_get_remote(&repo.remote)?;
         let provider = api::create_provider(&remote_config)?;
 
@@ -698,28 +1676,59 @@ impl GitRemote {
         ));
         let upstream = provider.get_upstream(&repo.name)?;
 
+        let url = Self::clone_url(remote_config, upstream)?;
+
+        confirm(format!(
+            "do you want to set upstream to {}",
+            style(&url).yellow()
+        ))?;
+        Shell::git()
+            .args(["remote", "add", "upstream", url.as_str()])
+            .exec()?;
+        Ok(GitRemote(String::from("upstream")))
+    }
+
+    /// Get the git remote pointing to the repo `name` of `remote_config`, a
+    /// remote named `alias` is added if there is none, such as the fork of
+    /// a merge.
+    pub fn ensure(remote_config: &Remote, name: &str, alias: &str) -> Result<GitRemote> {
+        for remote in Self::list()? {
+            let url = Shell::git()
+                .mute()
+                .args(["remote", "get-url", remote.as_str()])
+                .exec()?;
+            if matches!(parse_git_url(&url), Some((_, url_name)) if url_name == name) {
+                return Ok(remote);
+            }
+        }
+        let url = Self::clone_url(remote_config, name.to_string())?;
+        Shell::git()
+            .args(["remote", "add", alias, url.as_str()])
+            .exec()?;
+        Ok(GitRemote(alias.to_string()))
+    }
+
+    fn clone_url(remote_config: &Remote, name: String) -> Result<String> {
         let clone = match &remote_config.clone {
             Some(clone) => clone,
             None => bail!("require clone config for remote, please check your config"),
         };
-
-        let upstream_repo = Repo {
+        let repo = Repo {
             remote: remote_config.name.clone(),
-            name: upstream,
+            name,
             path: String::new(),
             last_accessed: 0,
             accessed: 0.0,
+            use_ssh: None,
+            history: vec![],
+            session: String::new(),
+            meta: None,
         };
-        let url = upstream_repo.clone_url(clone);
+        Ok(repo.clone_url(clone))
+    }
 
-        confirm(format!(
-            "do you want to set upstream to {}",
-            style(&url).yellow()
-        ))?;
-        Shell::git()
-            .args(["remote", "add", "upstream", url.as_str()])
-            .exec()?;
-        Ok(GitRemote(String::from("upstream")))
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
     }
 
     pub fn target(&self, branch: Option<&str>) -> Result<String> {
@@ -737,6 +1746,76 @@ impl GitRemote {
     }
 }
 
+/// Get the repositories registered to `git maintenance`.
+pub fn maintenance_repos() -> Result<Vec<String>> {
+    // Git exits with 1 if there is no registered repo.
+    let output = Shell::git()
+        .mute()
+        .args(["config", "--global", "--get-all", "maintenance.repo"])
+        .exec()
+        .unwrap_or_default();
+    Ok(output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect())
+}
+
+/// Work in progress changes, saved into a stash labeled with the branch
+/// name, so that they can be restored when we come back to the branch.
+pub struct GitWip;
+
+impl GitWip {
+    const LABEL_PREFIX: &str = "gz-wip:";
+
+    /// Save all changes (include untracked files), returns `false` if there
+    /// is nothing to save.
+    pub fn save(path: Option<&str>) -> Result<bool> {
+        if git::reader(path, false).count_changes()? == 0 {
+            return Ok(false);
+        }
+        let label = Self::label(path)?;
+        Self::git(path)
+            .args(["stash", "push", "--include-untracked", "-m", label.as_str()])
+            .exec()?;
+        Ok(true)
+    }
+
+    /// Restore the changes saved for current branch, returns `false` if
+    /// there is nothing to restore.
+    pub fn restore(path: Option<&str>) -> Result<bool> {
+        let label = Self::label(path)?;
+        let output = Self::git(path)
+            .args(["stash", "list", "--format=%gd %gs"])
+            .exec()?;
+        // The subject of stash created with message is "On <branch>: <msg>".
+        let stash = output
+            .lines()
+            .find(|line| line.ends_with(&label))
+            .and_then(|line| line.split_whitespace().next());
+        match stash {
+            Some(stash) => {
+                Self::git(path).args(["stash", "pop", stash]).exec()?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn label(path: Option<&str>) -> Result<String> {
+        let branch = git::reader(path, false).current_branch()?;
+        Ok(format!("{} {}", Self::LABEL_PREFIX, branch))
+    }
+
+    fn git(path: Option<&str>) -> Shell {
+        let mut git = Shell::git();
+        if let Some(path) = path {
+            git.with_git_path(path);
+        }
+        git
+    }
+}
+
 pub struct GitTag(String);
 
 impl Display for GitTag {
@@ -758,14 +1837,7 @@ impl GitTag {
     }
 
     pub fn list() -> Result<Vec<GitTag>> {
-        let tags: Vec<_> = Shell::git()
-            .arg("tag")
-            .exec()?
-            .lines()
-            .filter(|line| !line.trim().is_empty())
-            .map(|line| GitTag(line.trim().to_string()))
-            .collect();
-        Ok(tags)
+        git::reader(None, false).list_tags()
     }
 
     pub fn latest() -> Result<GitTag> {
@@ -938,3 +2010,122 @@ impl EmptyDir {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_repo_url_web() {
+        for (url, expect) in [
+            (
+                "https://github.com/fioncat/git-zoxide",
+                "fioncat/git-zoxide",
+            ),
+            (
+                "https://github.com/fioncat/git-zoxide/tree/main/src",
+                "fioncat/git-zoxide",
+            ),
+            (
+                "https://github.com/fioncat/git-zoxide/pull/1?tab=files#diff",
+                "fioncat/git-zoxide",
+            ),
+            ("https://gitlab.com/group/sub/repo", "group/sub/repo"),
+            (
+                "https://gitlab.com/group/sub/src/repo/-/merge_requests/1",
+                "group/sub/src/repo",
+            ),
+            (
+                "https://gitlab.com/group/sub/src/repo/-/tree/main",
+                "group/sub/src/repo",
+            ),
+        ] {
+            let (domain, name) = parse_repo_url(url).unwrap();
+            assert_eq!(domain, url.split('/').nth(2).unwrap(), "{}", url);
+            assert_eq!(name, expect, "{}", url);
+        }
+        // The user page.
+        assert_eq!(parse_repo_url("https://github.com/fioncat"), None);
+        assert_eq!(parse_repo_url("https://gitlab.com/group/-/issues"), None);
+    }
+
+    #[test]
+    fn parse_repo_url_clone() {
+        for (url, domain, expect) in [
+            (
+                "https://github.com/fioncat/git-zoxide.git",
+                "github.com",
+                "fioncat/git-zoxide",
+            ),
+            (
+                "git@github.com:fioncat/git-zoxide.git",
+                "github.com",
+                "fioncat/git-zoxide",
+            ),
+            (
+                "git@gitlab.com:group/sub/src/repo.git",
+                "gitlab.com",
+                "group/sub/src/repo",
+            ),
+            (
+                "ssh://git@gitlab.com:2222/group/sub/tree/repo.git",
+                "gitlab.com",
+                "group/sub/tree/repo",
+            ),
+            (
+                "ssh://git@github.com/fioncat/git-zoxide",
+                "github.com",
+                "fioncat/git-zoxide",
+            ),
+        ] {
+            let (parsed_domain, name) = parse_repo_url(url).unwrap();
+            assert_eq!(parsed_domain, domain, "{}", url);
+            assert_eq!(name, expect, "{}", url);
+        }
+        assert_eq!(parse_repo_url("git@github.com:fioncat"), None);
+        assert_eq!(parse_repo_url("not a url"), None);
+    }
+
+    #[test]
+    fn parse_bytes_units() {
+        for (s, expect) in [
+            ("1024", 1024),
+            ("0", 0),
+            ("12B", 12),
+            ("512MiB", 512 << 20),
+            ("5G", 5 << 30),
+            ("2 kb", 2 << 10),
+            ("1TiB", 1 << 40),
+            ("16777215TiB", 16777215 << 40),
+        ] {
+            assert_eq!(parse_bytes(s).unwrap(), expect, "{}", s);
+        }
+        for s in [
+            "",
+            "GiB",
+            "5PiB",
+            "-1",
+            "1.5G",
+            "16777216TiB",
+            "99999999999999999999",
+        ] {
+            assert!(parse_bytes(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn edit_distance_chars() {
+        for (a, b, expect) in [
+            ("", "", 0),
+            ("", "home", 4),
+            ("home", "home", 0),
+            ("hmoe", "home", 2),
+            ("jmp", "jump", 1),
+            ("kitten", "sitting", 3),
+            ("仓库", "仓", 1),
+        ] {
+            assert_eq!(edit_distance(a, b), expect, "{} {}", a, b);
+            assert_eq!(edit_distance(b, a), expect, "{} {}", b, a);
+        }
+    }
+}
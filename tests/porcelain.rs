@@ -0,0 +1,160 @@
+//! The contract tests of `--porcelain` output, the format is frozen, see
+//! `src/output.rs`. Each test runs the binary against a temporary config,
+//! database and workspace.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command as StdCommand;
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+struct Env {
+    dir: TempDir,
+}
+
+impl Env {
+    const REMOTE: &str = "gh";
+    const NAME: &str = "owner/repo";
+
+    /// Create the config with one remote and attach a repo with a commit
+    /// in workspace.
+    fn new() -> Env {
+        let dir = TempDir::new().unwrap();
+        let config = format!(
+            "workspace: {}\nremotes:\n  - name: {}\n  - name: empty\n",
+            dir.path().join("ws").display(),
+            Self::REMOTE
+        );
+        fs::write(dir.path().join("config.yaml"), config).unwrap();
+
+        let env = Env { dir };
+        let repo = env.repo_path();
+        fs::create_dir_all(&repo).unwrap();
+        env.git(&["init", "--quiet", "--initial-branch", "main"]);
+        fs::write(repo.join("file"), "content\n").unwrap();
+        env.git(&["add", "file"]);
+        env.git(&["commit", "--quiet", "-m", "init"]);
+
+        env.gz(&["attach", Self::REMOTE, Self::NAME])
+            .current_dir(&repo)
+            .assert()
+            .success();
+        env
+    }
+
+    fn repo_path(&self) -> PathBuf {
+        self.dir
+            .path()
+            .join("ws")
+            .join(Self::REMOTE)
+            .join(Self::NAME)
+    }
+
+    fn gz(&self, args: &[&str]) -> Command {
+        let mut cmd = Command::cargo_bin("git-zoxide").unwrap();
+        cmd.args(args)
+            .env("_GZ_CONFIG_PATH", self.dir.path().join("config.yaml"))
+            .env("_GZ_DATA_PATH", self.dir.path().join("data"))
+            .env_remove("_GZ_DATABASE_PATH")
+            .env_remove("_GZ_RESULT_FILE")
+            .env_remove("_GZ_SESSION")
+            // The git config of user might change the output, such as the
+            // branch names.
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1");
+        cmd
+    }
+
+    fn git(&self, args: &[&str]) {
+        let status = StdCommand::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@test"])
+            .args(args)
+            .current_dir(self.repo_path())
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// Run gz and split the stdout into records and fields.
+    fn records(&self, args: &[&str], dir: &Path) -> Vec<Vec<String>> {
+        let output = self.gz(args).current_dir(dir).output().unwrap();
+        assert!(output.status.success(), "gz {:?} failed", args);
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|line| line.split('\t').map(String::from).collect())
+            .collect()
+    }
+}
+
+#[test]
+fn list_remotes() {
+    let env = Env::new();
+    let records = env.records(&["list", "--porcelain"], env.dir.path());
+    assert_eq!(records, vec![vec!["gh", "1"], vec!["empty", "0"]]);
+}
+
+#[test]
+fn list_repos() {
+    let env = Env::new();
+    let records = env.records(&["list", "gh", "--porcelain"], env.dir.path());
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.len(), 6);
+    assert_eq!(record[0], "gh");
+    assert_eq!(record[1], "owner/repo");
+    assert_eq!(record[2], env.repo_path().display().to_string());
+    // The attached repo is never accessed.
+    assert_eq!(record[3], "0");
+    // No status cache, the branch and dirty are unknown.
+    assert_eq!(record[4], "");
+    assert_eq!(record[5], "");
+}
+
+#[test]
+fn list_describe() {
+    let env = Env::new();
+    let output = env
+        .gz(&["list", "--remote", "--porcelain-v2"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let output = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output, "gh:1 repos\nempty:0 repos\n");
+}
+
+#[test]
+fn du() {
+    let env = Env::new();
+    let records = env.records(&["du", "--porcelain"], env.dir.path());
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.len(), 4);
+    assert_eq!(record[0], "gh");
+    assert_eq!(record[1], "owner/repo");
+    assert!(record[2].parse::<u64>().unwrap() > 0);
+    assert!(record[3].parse::<u64>().unwrap() > 0);
+}
+
+#[test]
+fn branch() {
+    let env = Env::new();
+    env.git(&["branch", "feature"]);
+    let records = env.records(&["branch", "--porcelain"], &env.repo_path());
+    assert_eq!(records.len(), 2);
+    let find = |name: &str| {
+        records
+            .iter()
+            .find(|record| record[0] == name)
+            .unwrap_or_else(|| panic!("branch {} not found", name))
+    };
+    let main = find("main");
+    assert_eq!(main.len(), 4);
+    assert_eq!(main[1], "1");
+    let feature = find("feature");
+    assert_eq!(feature[1], "0");
+    assert_eq!(feature[3], "");
+}